@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{Column, Row, SqliteConnection, SqlitePool};
+use tauri::{AppHandle, Emitter};
+
+use crate::{db, query};
+
+/// How many actions are kept. Older ones can no longer be undone.
+const UNDO_DEPTH: i64 = 10;
+
+/// Tables an undo may write to, with their key column. Names are
+/// interpolated into SQL, so nothing else is accepted. Only backend-owned
+/// tables belong here; `trades` is the frontend's.
+const RESTORABLE: &[(&str, &str)] = &[("journal_entries", "id"), ("symbol_notes", "symbol")];
+
+/// Put rows back the way they were: delete whatever now has `keys`, then
+/// reinsert `rows`. Covers deletes (rows are the deleted ones), edits
+/// (rows are the previous versions) and inserts (no rows).
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Restore {
+    table: String,
+    keys: Vec<Value>,
+    rows: Vec<Map<String, Value>>,
+}
+
+fn key_column(table: &str) -> Result<&'static str, String> {
+    RESTORABLE
+        .iter()
+        .find(|(t, _)| *t == table)
+        .map(|(_, key)| *key)
+        .ok_or_else(|| format!("Table '{}' can't be restored", table))
+}
+
+fn bind_value<'q>(
+    query: sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    value: &Value,
+) -> sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+    match value {
+        Value::Null => query.bind(None::<String>),
+        Value::Bool(b) => query.bind(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => query.bind(i),
+            None => query.bind(n.as_f64()),
+        },
+        Value::String(s) => query.bind(s.clone()),
+        other => query.bind(other.to_string()),
+    }
+}
+
+impl Restore {
+    /// Capture the rows with `keys` in `table` before changing them.
+    pub(crate) async fn capture(
+        conn: &mut SqliteConnection,
+        table: &str,
+        keys: Vec<Value>,
+    ) -> Result<Self, String> {
+        let key = key_column(table)?;
+        let sql = format!("SELECT * FROM {} WHERE {} = ?", table, key);
+        let mut rows = Vec::new();
+        for value in &keys {
+            for row in bind_value(sqlx::query(&sql), value)
+                .fetch_all(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())?
+            {
+                let mut captured = Map::new();
+                for (i, column) in row.columns().iter().enumerate() {
+                    captured.insert(column.name().to_string(), query::json_value(&row, i)?);
+                }
+                rows.push(captured);
+            }
+        }
+        Ok(Self {
+            table: table.to_string(),
+            keys,
+            rows,
+        })
+    }
+
+    async fn apply(&self, conn: &mut SqliteConnection) -> Result<(), String> {
+        let key = key_column(&self.table)?;
+        let delete = format!("DELETE FROM {} WHERE {} = ?", self.table, key);
+        for value in &self.keys {
+            bind_value(sqlx::query(&delete), value)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+
+        for row in &self.rows {
+            if row
+                .keys()
+                .any(|c| !c.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_'))
+            {
+                return Err("Stored action has an invalid column name".into());
+            }
+            let columns: Vec<&str> = row.keys().map(String::as_str).collect();
+            let insert = format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                self.table,
+                columns.join(", "),
+                vec!["?"; columns.len()].join(", ")
+            );
+            row.values()
+                .fold(sqlx::query(&insert), bind_value)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Action log
+// ---------------------------------------------------------------------------
+
+/// Log an action. `undo` is `None` for actions that can't be reversed;
+/// they stay in the log for the record but undo passes over them. Call
+/// inside the mutation's transaction so the two can't disagree.
+pub(crate) async fn record(
+    conn: &mut SqliteConnection,
+    description: &str,
+    undo: Option<Vec<Restore>>,
+) -> Result<(), String> {
+    let undo = undo
+        .map(|steps| serde_json::to_string(&steps))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    sqlx::query("INSERT INTO action_log (description, undo) VALUES (?, ?)")
+        .bind(description)
+        .bind(undo)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query(
+        "DELETE FROM action_log WHERE id NOT IN \
+         (SELECT id FROM action_log ORDER BY id DESC LIMIT ?)",
+    )
+    .bind(UNDO_DEPTH)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Reverse the most recent undoable action in one transaction and return
+/// its description.
+pub(crate) async fn undo_last(pool: &SqlitePool) -> Result<String, String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let (id, description, undo): (i64, String, String) = sqlx::query_as(
+        "SELECT id, description, undo FROM action_log WHERE undo IS NOT NULL \
+         ORDER BY id DESC LIMIT 1",
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or("Nothing to undo")?;
+
+    let steps: Vec<Restore> = serde_json::from_str(&undo).map_err(|e| e.to_string())?;
+    for step in &steps {
+        step.apply(&mut tx).await?;
+    }
+    sqlx::query("DELETE FROM action_log WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(description)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Undo the latest note edit, journal deletion or dedupe journal move. Emits
+/// `action-undone` with the description so views can reload.
+#[tauri::command]
+pub async fn undo_last_action(app: AppHandle) -> Result<String, String> {
+    logged!("undo_last_action", [], async {
+        let pool = db::pool(&app).await?;
+        let description = undo_last(&pool).await?;
+        let _ = app.emit("action-undone", &description);
+        Ok(description)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing;
+    use crate::journal::{add_entry, delete_entry};
+    use crate::notes::write_note;
+
+    async fn note(pool: &SqlitePool) -> Option<String> {
+        sqlx::query_scalar("SELECT note FROM symbol_notes WHERE symbol = 'AAPL'")
+            .fetch_optional(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn undo_restores_a_deleted_journal_entry() {
+        let pool = testing::pool().await;
+        testing::insert_open(&pool, "AAPL", 10, 150.0).await;
+        add_entry(&pool, 1, "breakout", None).await.unwrap();
+        delete_entry(&pool, 1).await.unwrap();
+
+        assert_eq!(undo_last(&pool).await.unwrap(), "Deleted a journal entry");
+        let restored: (i64, String) = sqlx::query_as("SELECT id, text FROM journal_entries")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(restored, (1, "breakout".to_string()));
+        assert!(undo_last(&pool).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn undo_reverts_note_edits_one_at_a_time() {
+        let pool = testing::pool().await;
+        write_note(&pool, "AAPL", "first thesis").await.unwrap();
+        write_note(&pool, "AAPL", "revised thesis").await.unwrap();
+
+        undo_last(&pool).await.unwrap();
+        assert_eq!(note(&pool).await.as_deref(), Some("first thesis"));
+        undo_last(&pool).await.unwrap();
+        assert_eq!(note(&pool).await, None);
+    }
+
+    #[tokio::test]
+    async fn irreversible_actions_are_skipped_and_the_stack_is_capped() {
+        let pool = testing::pool().await;
+        for i in 0..UNDO_DEPTH + 2 {
+            write_note(&pool, "AAPL", &format!("v{}", i)).await.unwrap();
+        }
+        let mut conn = pool.acquire().await.unwrap();
+        record(&mut conn, "Irreversible", None).await.unwrap();
+        drop(conn);
+
+        let mut undone = 0;
+        while undo_last(&pool).await.is_ok() {
+            undone += 1;
+        }
+        // the irreversible entry took one slot and was passed over
+        assert_eq!(undone, UNDO_DEPTH - 1);
+        assert_eq!(note(&pool).await.as_deref(), Some("v2"));
+    }
+
+    #[test]
+    fn frontend_tables_are_never_restorable() {
+        assert!(key_column("trades")
+            .unwrap_err()
+            .contains("can't be restored"));
+        assert_eq!(key_column("symbol_notes").unwrap(), "symbol");
+    }
+}
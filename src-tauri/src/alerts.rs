@@ -0,0 +1,1524 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::delivery::{self, AlertMessage};
+use crate::{db, export, session, targets, tray};
+
+/// Words that introduce a direction. A following "above"/"below" takes over,
+/// so "drops below" and "crosses above" read naturally.
+const BELOW: &[&str] = &[
+    "below", "under", "beneath", "drops", "falls", "dips", "sinks",
+];
+const ABOVE: &[&str] = &["above", "over", "rises", "climbs", "exceeds", "tops"];
+const CROSSES: &[&str] = &["crosses", "hits", "reaches", "touches"];
+
+/// Words that can sit between the symbol and the operator.
+const FILLER: &[&str] = &["price", "stock", "shares", "is", "goes", "gets", "trades"];
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ParsedAlert {
+    symbol: String,
+    /// `above`, `below` or `crosses`.
+    condition: &'static str,
+    threshold: f64,
+}
+
+fn condition_of(word: &str) -> Option<&'static str> {
+    if BELOW.contains(&word) {
+        Some("below")
+    } else if ABOVE.contains(&word) {
+        Some("above")
+    } else if CROSSES.contains(&word) {
+        Some("crosses")
+    } else {
+        None
+    }
+}
+
+fn number(word: &str) -> Option<f64> {
+    let cleaned: String = word
+        .trim_start_matches('$')
+        .chars()
+        .filter(|c| *c != ',')
+        .collect();
+    cleaned
+        .parse()
+        .ok()
+        .filter(|n: &f64| n.is_finite() && *n > 0.0)
+}
+
+/// Pull an alert out of text like "alert me when AAPL drops below 180".
+/// The symbol is a `$TICKER` anywhere, or else the word before the
+/// operator.
+pub(crate) fn parse(text: &str) -> Result<ParsedAlert, String> {
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .map(|w| w.trim_end_matches(['.', '!', '?']))
+        .filter(|w| !w.is_empty())
+        .collect();
+    let lower: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+
+    let (at, mut condition) = lower
+        .iter()
+        .enumerate()
+        .find_map(|(i, w)| condition_of(w).map(|c| (i, c)))
+        .ok_or("Couldn't find a condition; try \"above\", \"below\" or \"crosses\"")?;
+
+    let mut rest = at + 1;
+    if let Some(next) = lower.get(rest).and_then(|w| condition_of(w)) {
+        if next != "crosses" {
+            condition = next;
+        }
+        rest += 1;
+    }
+
+    let threshold = lower[rest..]
+        .iter()
+        .find_map(|w| number(w))
+        .ok_or("Couldn't find a price to compare against")?;
+
+    let candidate = words
+        .iter()
+        .find_map(|w| {
+            w.strip_prefix('$')
+                .filter(|s| number(w).is_none() && !s.is_empty())
+        })
+        .or_else(|| {
+            lower[..at]
+                .iter()
+                .rposition(|w| !FILLER.contains(&w.as_str()))
+                .map(|i| words[i].trim_end_matches("'s"))
+        })
+        .ok_or("Couldn't find a symbol; try something like \"AAPL below 180\"")?;
+    let looks_like_ticker = candidate.len() <= 10
+        && candidate
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    if !looks_like_ticker {
+        return Err(format!("'{}' doesn't look like a symbol", candidate));
+    }
+    let symbol = db::clean_symbol(candidate)?;
+
+    Ok(ParsedAlert {
+        symbol,
+        condition,
+        threshold,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Compound conditions
+// ---------------------------------------------------------------------------
+
+/// Deep enough for "(A and B) or (C and D)" with room to spare.
+const MAX_DEPTH: usize = 5;
+const MAX_LEAVES: usize = 20;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    Price,
+    /// Percent change on the day.
+    PctChange,
+    Rsi,
+    Volume,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    Above,
+    Below,
+}
+
+/// Stored as JSON, e.g. `{"type": "all_of", "conditions": [{"type":
+/// "leaf", "metric": "price", "op": "below", "value": 180}, ...]}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Condition {
+    AllOf {
+        conditions: Vec<Condition>,
+    },
+    AnyOf {
+        conditions: Vec<Condition>,
+    },
+    Leaf {
+        metric: Metric,
+        op: Comparison,
+        value: f64,
+    },
+    /// Price has fallen `trail_pct` percent below the highest price seen
+    /// since the alert was saved.
+    TrailingStop {
+        trail_pct: f64,
+    },
+}
+
+/// What the frontend knows about a symbol at one refresh. Metrics it
+/// couldn't compute are left out, and leaves on them don't match.
+#[derive(Debug, Default, Deserialize)]
+pub struct MarketSnapshot {
+    price: f64,
+    change_pct: Option<f64>,
+    rsi: Option<f64>,
+    volume: Option<f64>,
+}
+
+/// Evaluate against `high`, the highest price seen so far including this
+/// snapshot's.
+fn evaluate(condition: &Condition, snapshot: &MarketSnapshot, high: f64) -> bool {
+    match condition {
+        Condition::AllOf { conditions } => conditions.iter().all(|c| evaluate(c, snapshot, high)),
+        Condition::AnyOf { conditions } => conditions.iter().any(|c| evaluate(c, snapshot, high)),
+        Condition::TrailingStop { trail_pct } => snapshot.price <= high * (1.0 - trail_pct / 100.0),
+        Condition::Leaf { metric, op, value } => {
+            let actual = match metric {
+                Metric::Price => Some(snapshot.price),
+                Metric::PctChange => snapshot.change_pct,
+                Metric::Rsi => snapshot.rsi,
+                Metric::Volume => snapshot.volume,
+            };
+            match (actual, op) {
+                (Some(actual), Comparison::Above) => actual > *value,
+                (Some(actual), Comparison::Below) => actual < *value,
+                (None, _) => false,
+            }
+        }
+    }
+}
+
+/// One line describing `condition`, e.g. "Price above 180".
+pub(crate) fn summary(condition: &Condition) -> String {
+    match condition {
+        Condition::Leaf { metric, op, value } => {
+            let metric = match metric {
+                Metric::Price => "Price",
+                Metric::PctChange => "Change %",
+                Metric::Rsi => "RSI",
+                Metric::Volume => "Volume",
+            };
+            let op = match op {
+                Comparison::Above => "above",
+                Comparison::Below => "below",
+            };
+            format!("{} {} {}", metric, op, value)
+        }
+        Condition::TrailingStop { trail_pct } => format!("{}% below its high", trail_pct),
+        Condition::AllOf { conditions } => format!("All of {} conditions", conditions.len()),
+        Condition::AnyOf { conditions } => format!("Any of {} conditions", conditions.len()),
+    }
+}
+
+/// Whether `condition` needs the running high kept for it.
+fn trails(condition: &Condition) -> bool {
+    match condition {
+        Condition::AllOf { conditions } | Condition::AnyOf { conditions } => {
+            conditions.iter().any(trails)
+        }
+        Condition::TrailingStop { .. } => true,
+        Condition::Leaf { .. } => false,
+    }
+}
+
+fn check_node(condition: &Condition, depth: usize, leaves: &mut usize) -> Result<(), String> {
+    if depth > MAX_DEPTH {
+        return Err(format!("Conditions can nest at most {} deep", MAX_DEPTH));
+    }
+    match condition {
+        Condition::AllOf { conditions } | Condition::AnyOf { conditions } => {
+            if conditions.is_empty() {
+                return Err("A condition group needs at least one condition".into());
+            }
+            conditions
+                .iter()
+                .try_for_each(|c| check_node(c, depth + 1, leaves))
+        }
+        Condition::Leaf { metric, value, .. } => {
+            *leaves += 1;
+            if *leaves > MAX_LEAVES {
+                return Err(format!("At most {} conditions per alert", MAX_LEAVES));
+            }
+            let valid = value.is_finite()
+                && match metric {
+                    Metric::Price | Metric::Volume => *value > 0.0,
+                    Metric::Rsi => (0.0..=100.0).contains(value),
+                    Metric::PctChange => true,
+                };
+            if !valid {
+                return Err(format!("Invalid threshold {} for {:?}", value, metric));
+            }
+            Ok(())
+        }
+        Condition::TrailingStop { trail_pct } => {
+            *leaves += 1;
+            if *leaves > MAX_LEAVES {
+                return Err(format!("At most {} conditions per alert", MAX_LEAVES));
+            }
+            if !(trail_pct.is_finite() && *trail_pct > 0.0 && *trail_pct < 100.0) {
+                return Err(format!(
+                    "Trailing stop must be between 0% and 100%, got {}",
+                    trail_pct
+                ));
+            }
+            Ok(())
+        }
+    }
+}
+
+pub(crate) fn validate(condition: &Condition) -> Result<(), String> {
+    check_node(condition, 1, &mut 0)
+}
+
+#[derive(Debug, Serialize)]
+pub struct Alert {
+    id: i64,
+    symbol: String,
+    condition: Condition,
+    created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewAlert {
+    symbol: String,
+    condition: Condition,
+}
+
+/// Outcome for one alert in a batch, in request order.
+#[derive(Debug, Serialize)]
+pub struct BatchItem {
+    id: Option<i64>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchResult {
+    created: usize,
+    results: Vec<BatchItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TriggeredAlert {
+    pub(crate) id: i64,
+    pub(crate) symbol: String,
+    pub(crate) condition: Condition,
+    /// Price in the snapshot it fired on.
+    pub(crate) price: f64,
+}
+
+#[derive(sqlx::FromRow)]
+struct AlertRow {
+    id: i64,
+    symbol: String,
+    condition: String,
+    last_met: bool,
+    /// Highest price seen, for alerts with a trailing stop.
+    high_water: Option<f64>,
+    created_at: String,
+}
+
+impl AlertRow {
+    fn condition(&self) -> Result<Condition, String> {
+        serde_json::from_str(&self.condition)
+            .map_err(|e| format!("Alert {} has an unreadable condition: {}", self.id, e))
+    }
+
+    /// The condition, or `None` once it's been logged as unreadable, so one
+    /// bad row doesn't take every other alert down with it.
+    fn readable_condition(&self) -> Option<Condition> {
+        self.condition()
+            .map_err(|e| eprintln!("[alerts] Skipping alert: {}", e))
+            .ok()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Queries
+// ---------------------------------------------------------------------------
+
+async fn insert_on(
+    conn: &mut SqliteConnection,
+    symbol: &str,
+    condition: &Condition,
+) -> Result<i64, String> {
+    validate(condition)?;
+    let symbol = db::clean_symbol(symbol)?;
+    let json = serde_json::to_string(condition).map_err(|e| e.to_string())?;
+    sqlx::query_scalar("INSERT INTO alerts (symbol, condition) VALUES (?, ?) RETURNING id")
+        .bind(&symbol)
+        .bind(&json)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub(crate) async fn insert_alert(
+    pool: &SqlitePool,
+    symbol: &str,
+    condition: &Condition,
+) -> Result<i64, String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    insert_on(&mut conn, symbol, condition).await
+}
+
+/// Insert every valid alert in one transaction. A bad entry is reported in
+/// its slot and doesn't stop the rest.
+pub(crate) async fn insert_batch(
+    pool: &SqlitePool,
+    alerts: &[NewAlert],
+) -> Result<BatchResult, String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let mut results = Vec::with_capacity(alerts.len());
+    for alert in alerts {
+        results.push(
+            match insert_on(&mut tx, &alert.symbol, &alert.condition).await {
+                Ok(id) => BatchItem {
+                    id: Some(id),
+                    error: None,
+                },
+                Err(e) => BatchItem {
+                    id: None,
+                    error: Some(e),
+                },
+            },
+        );
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(BatchResult {
+        created: results.iter().filter(|r| r.id.is_some()).count(),
+        results,
+    })
+}
+
+async fn load_rows(pool: &SqlitePool) -> Result<Vec<AlertRow>, String> {
+    sqlx::query_as(
+        "SELECT id, symbol, condition, last_met, high_water, created_at FROM alerts ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+pub(crate) async fn load_alerts(pool: &SqlitePool) -> Result<Vec<Alert>, String> {
+    Ok(load_rows(pool)
+        .await?
+        .into_iter()
+        .filter_map(|row| {
+            Some(Alert {
+                condition: row.readable_condition()?,
+                id: row.id,
+                symbol: row.symbol,
+                created_at: row.created_at,
+            })
+        })
+        .collect())
+}
+
+/// Evaluate every alert with a snapshot for its symbol and return the ones
+/// whose condition just became true. An alert fires again only after its
+/// condition has gone false in between. Trailing stops keep the highest
+/// price seen in the row, so restarts don't lose it. The new states are
+/// written in one transaction, so a failed pass leaves every alert as it
+/// was. Rows with an unreadable condition are logged and left alone.
+pub(crate) async fn check(
+    pool: &SqlitePool,
+    snapshots: &HashMap<String, MarketSnapshot>,
+) -> Result<Vec<TriggeredAlert>, String> {
+    let rows = load_rows(pool).await?;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let mut triggered = Vec::new();
+    for row in rows {
+        let Some(snapshot) = snapshots.get(&row.symbol) else {
+            continue;
+        };
+        let Some(condition) = row.readable_condition() else {
+            continue;
+        };
+        let high_water = trails(&condition).then(|| {
+            row.high_water
+                .map_or(snapshot.price, |high| high.max(snapshot.price))
+        });
+        let met = evaluate(&condition, snapshot, high_water.unwrap_or(snapshot.price));
+        if met != row.last_met || high_water != row.high_water {
+            sqlx::query("UPDATE alerts SET last_met = ?, high_water = ? WHERE id = ?")
+                .bind(met)
+                .bind(high_water)
+                .bind(row.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        if met && !row.last_met {
+            sqlx::query(
+                "INSERT INTO alert_history (alert_id, symbol, price, message) VALUES (?, ?, ?, ?)",
+            )
+            .bind(row.id)
+            .bind(&row.symbol)
+            .bind(snapshot.price)
+            .bind(summary(&condition))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+            triggered.push(TriggeredAlert {
+                id: row.id,
+                symbol: row.symbol,
+                condition,
+                price: snapshot.price,
+            });
+        }
+    }
+    sqlx::query("DELETE FROM alert_history WHERE fired_at < datetime('now', ?)")
+        .bind(format!("-{} days", HISTORY_DAYS))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(triggered)
+}
+
+// ---------------------------------------------------------------------------
+// Mute
+// ---------------------------------------------------------------------------
+
+/// Whether fired alerts skip delivery, and the Unix time in seconds at which
+/// that lapses on its own. `check` runs either way so crossings made while
+/// muted aren't replayed afterwards.
+#[derive(Default)]
+pub struct AlertMute(Mutex<(bool, Option<i64>)>);
+
+impl AlertMute {
+    pub fn set(&self, muted: bool, until: Option<i64>) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = (muted, until.filter(|_| muted));
+    }
+
+    /// Whether alerts are muted at `now`, unmuting first if `until` has
+    /// passed.
+    pub fn muted_at(&self, now: i64) -> bool {
+        let mut state = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if matches!(state.1, Some(until) if until <= now) {
+            *state = (false, None);
+        }
+        state.0
+    }
+}
+
+/// The alerts to send to the delivery channels: none while muted.
+fn deliverable(mute: &AlertMute, now: i64, triggered: &[TriggeredAlert]) -> Vec<TriggeredAlert> {
+    if mute.muted_at(now) {
+        Vec::new()
+    } else {
+        triggered.to_vec()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// History
+// ---------------------------------------------------------------------------
+
+/// Fires older than this are pruned at the end of each `check` pass.
+const HISTORY_DAYS: u32 = 90;
+const MAX_HISTORY: i64 = 1000;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AlertFire {
+    id: i64,
+    alert_id: i64,
+    symbol: String,
+    price: f64,
+    fired_at: String,
+    message: String,
+}
+
+/// The latest `limit` fires, newest first.
+pub(crate) async fn load_history(pool: &SqlitePool, limit: i64) -> Result<Vec<AlertFire>, String> {
+    sqlx::query_as(
+        "SELECT id, alert_id, symbol, price, fired_at, message FROM alert_history \
+         ORDER BY fired_at DESC, id DESC LIMIT ?",
+    )
+    .bind(limit.clamp(1, MAX_HISTORY))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+pub(crate) async fn delete_alert_row(pool: &SqlitePool, id: i64) -> Result<(), String> {
+    let deleted = sqlx::query("DELETE FROM alerts WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .rows_affected();
+    if deleted == 0 {
+        return Err(format!("No alert with id {}", id));
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Position summary
+// ---------------------------------------------------------------------------
+
+/// Latest price per symbol from the snapshots `check_alerts` was given, so
+/// the summary doesn't need quotes of its own.
+#[derive(Default)]
+pub struct AlertQuotes(Mutex<HashMap<String, f64>>);
+
+impl AlertQuotes {
+    fn record(&self, snapshots: &HashMap<String, MarketSnapshot>) {
+        let mut quotes = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        for (symbol, snapshot) in snapshots {
+            quotes.insert(symbol.clone(), snapshot.price);
+        }
+    }
+
+    fn snapshot(&self) -> HashMap<String, f64> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PositionAlertStatus {
+    symbol: String,
+    /// Alerts waiting for their condition.
+    active_alerts: usize,
+    /// Alerts whose condition is met right now.
+    triggered_alerts: usize,
+    /// Armed price level closest to the latest quote.
+    nearest_threshold: Option<f64>,
+    /// How far that level is from the quote, in percent; negative when it
+    /// is below.
+    distance_pct: Option<f64>,
+}
+
+/// Every price level in `condition`: price leaves, and trailing stops at
+/// their trail below `high`.
+fn price_levels(condition: &Condition, high: f64, levels: &mut Vec<f64>) {
+    match condition {
+        Condition::AllOf { conditions } | Condition::AnyOf { conditions } => {
+            for c in conditions {
+                price_levels(c, high, levels);
+            }
+        }
+        Condition::Leaf {
+            metric: Metric::Price,
+            value,
+            ..
+        } => levels.push(*value),
+        Condition::Leaf { .. } => {}
+        Condition::TrailingStop { trail_pct } => levels.push(high * (1.0 - trail_pct / 100.0)),
+    }
+}
+
+/// Alert counts for each open position that has alerts, by symbol, with
+/// the armed level nearest to its price in `quotes`.
+pub(crate) async fn position_summary(
+    pool: &SqlitePool,
+    quotes: &HashMap<String, f64>,
+) -> Result<Vec<PositionAlertStatus>, String> {
+    let open: HashSet<String> =
+        sqlx::query_scalar("SELECT DISTINCT symbol FROM trades WHERE status = 'open'")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .collect();
+
+    let mut by_symbol: BTreeMap<String, (PositionAlertStatus, Vec<f64>)> = BTreeMap::new();
+    for row in load_rows(pool).await? {
+        if !open.contains(&row.symbol) {
+            continue;
+        }
+        let (status, levels) = by_symbol.entry(row.symbol.clone()).or_insert_with(|| {
+            let status = PositionAlertStatus {
+                symbol: row.symbol.clone(),
+                active_alerts: 0,
+                triggered_alerts: 0,
+                nearest_threshold: None,
+                distance_pct: None,
+            };
+            (status, Vec::new())
+        });
+        if row.last_met {
+            status.triggered_alerts += 1;
+            continue;
+        }
+        status.active_alerts += 1;
+        if let (Some(condition), Some(&price)) = (row.readable_condition(), quotes.get(&row.symbol))
+        {
+            let high = row.high_water.map_or(price, |high| high.max(price));
+            price_levels(&condition, high, levels);
+        }
+    }
+
+    Ok(by_symbol
+        .into_values()
+        .map(|(mut status, levels)| {
+            if let Some(&price) = quotes.get(&status.symbol).filter(|p| **p > 0.0) {
+                let nearest = levels
+                    .into_iter()
+                    .min_by(|a, b| (a - price).abs().total_cmp(&(b - price).abs()));
+                status.nearest_threshold = nearest;
+                status.distance_pct = nearest.map(|level| (level - price) / price * 100.0);
+            }
+            status
+        })
+        .collect())
+}
+
+/// Condition types an [`AlertFilter`] can match, by their JSON `type`.
+const CONDITION_TYPES: &[&str] = &["leaf", "all_of", "any_of", "trailing_stop"];
+
+/// Which alerts `bulk_delete_alerts` removes. Set fields must all match,
+/// and at least one must be set.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AlertFilter {
+    symbol: Option<String>,
+    /// Top-level condition type, one of [`CONDITION_TYPES`].
+    kind: Option<String>,
+    /// Only alerts whose condition is currently met.
+    triggered: bool,
+}
+
+/// Delete every alert matching `filter` in one transaction, returning how
+/// many went. An empty filter is refused rather than deleting everything.
+pub(crate) async fn delete_matching(
+    pool: &SqlitePool,
+    filter: &AlertFilter,
+) -> Result<u64, String> {
+    let mut clauses = Vec::new();
+    let mut binds = Vec::new();
+    if let Some(symbol) = &filter.symbol {
+        clauses.push("symbol = ?");
+        binds.push(db::clean_symbol(symbol)?);
+    }
+    if let Some(kind) = &filter.kind {
+        if !CONDITION_TYPES.contains(&kind.as_str()) {
+            return Err(format!(
+                "Unknown alert type '{}', expected one of: {}",
+                kind,
+                CONDITION_TYPES.join(", ")
+            ));
+        }
+        clauses.push("json_extract(condition, '$.type') = ?");
+        binds.push(kind.clone());
+    }
+    if filter.triggered {
+        clauses.push("last_met = 1");
+    }
+    if clauses.is_empty() {
+        return Err("Pick a symbol, type or triggered alerts to delete".into());
+    }
+
+    let sql = format!("DELETE FROM alerts WHERE {}", clauses.join(" AND "));
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let mut query = sqlx::query(&sql);
+    for value in &binds {
+        query = query.bind(value);
+    }
+    let deleted = query
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .rows_affected();
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(deleted)
+}
+
+// ---------------------------------------------------------------------------
+// Config files
+// ---------------------------------------------------------------------------
+
+/// Layout written by `export_alerts_config`. Files from a newer build are
+/// refused rather than half-imported.
+const ALERTS_CONFIG_VERSION: u32 = 1;
+
+/// Alerts and price targets as a portable file. Conditions are kept as raw
+/// JSON so one bad entry can be skipped instead of failing the file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AlertsConfig {
+    version: u32,
+    alerts: Vec<ConfigAlert>,
+    price_targets: Vec<ConfigTarget>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ConfigAlert {
+    symbol: String,
+    condition: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+struct ConfigTarget {
+    symbol: String,
+    target: f64,
+    #[serde(default)]
+    rationale: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    alerts_added: usize,
+    targets_added: usize,
+    /// Entries already present exactly as in the file.
+    unchanged: usize,
+    /// Price targets that differ from the saved one, which is kept.
+    conflicts: Vec<String>,
+    /// Entries that failed validation, with the reason.
+    skipped: Vec<String>,
+}
+
+/// Alerts merge on symbol plus condition, so the same rule is never saved
+/// twice. The JSON goes through `Condition` on both sides to compare equal.
+fn alert_key(symbol: &str, condition: &Condition) -> Result<(String, String), String> {
+    let json = serde_json::to_string(condition).map_err(|e| e.to_string())?;
+    Ok((symbol.to_string(), json))
+}
+
+pub(crate) async fn export_config(pool: &SqlitePool) -> Result<AlertsConfig, String> {
+    let alerts = load_alerts(pool)
+        .await?
+        .into_iter()
+        .map(|alert| {
+            Ok(ConfigAlert {
+                condition: serde_json::to_value(&alert.condition).map_err(|e| e.to_string())?,
+                symbol: alert.symbol,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+    let price_targets =
+        sqlx::query_as("SELECT symbol, target, rationale FROM price_targets ORDER BY symbol")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    Ok(AlertsConfig {
+        version: ALERTS_CONFIG_VERSION,
+        alerts,
+        price_targets,
+    })
+}
+
+/// Merge `config` into the saved alerts and price targets in one
+/// transaction. Nothing saved is overwritten: a differing price target is
+/// reported as a conflict.
+pub(crate) async fn import_config(
+    pool: &SqlitePool,
+    config: AlertsConfig,
+) -> Result<ImportReport, String> {
+    if config.version == 0 || config.version > ALERTS_CONFIG_VERSION {
+        return Err(format!(
+            "Unsupported alerts file version {}, expected {} or older",
+            config.version, ALERTS_CONFIG_VERSION
+        ));
+    }
+    let mut report = ImportReport::default();
+
+    let mut existing = HashSet::new();
+    for row in load_rows(pool).await? {
+        if let Some(condition) = row.readable_condition() {
+            existing.insert(alert_key(&row.symbol, &condition)?);
+        }
+    }
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    for (i, alert) in config.alerts.into_iter().enumerate() {
+        let checked = serde_json::from_value::<Condition>(alert.condition)
+            .map_err(|e| e.to_string())
+            .and_then(|condition| {
+                validate(&condition)?;
+                Ok((db::clean_symbol(&alert.symbol)?, condition))
+            });
+        let (symbol, condition) = match checked {
+            Ok(checked) => checked,
+            Err(e) => {
+                report
+                    .skipped
+                    .push(format!("Alert {} ({}): {}", i + 1, alert.symbol.trim(), e));
+                continue;
+            }
+        };
+        if !existing.insert(alert_key(&symbol, &condition)?) {
+            report.unchanged += 1;
+            continue;
+        }
+        insert_on(&mut tx, &symbol, &condition).await?;
+        report.alerts_added += 1;
+    }
+
+    for target in config.price_targets {
+        let symbol = match db::clean_symbol(&target.symbol) {
+            Ok(symbol) => symbol,
+            Err(e) => {
+                report.skipped.push(format!("Price target: {}", e));
+                continue;
+            }
+        };
+        let saved: Option<f64> =
+            sqlx::query_scalar("SELECT target FROM price_targets WHERE symbol = ?")
+                .bind(&symbol)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        match saved {
+            Some(saved) if (saved - target.target).abs() < 1e-9 => report.unchanged += 1,
+            Some(saved) => report.conflicts.push(format!(
+                "{}: kept price target {} over imported {}",
+                symbol, saved, target.target
+            )),
+            None => {
+                match targets::write_target_on(&mut tx, &symbol, target.target, &target.rationale)
+                    .await
+                {
+                    Ok(_) => report.targets_added += 1,
+                    Err(e) => report
+                        .skipped
+                        .push(format!("Price target ({}): {}", symbol, e)),
+                }
+            }
+        }
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(report)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Turn a typed sentence into a structured alert for the user to confirm.
+#[tauri::command]
+pub fn parse_alert(text: String) -> Result<ParsedAlert, String> {
+    logged!("parse_alert", [], { parse(&text) })
+}
+
+/// Check a compound condition before saving it.
+#[tauri::command]
+pub fn validate_condition(condition: Condition) -> Result<(), String> {
+    logged!("validate_condition", [], { validate(&condition) })
+}
+
+/// Save an alert on `symbol`. Returns the new alert's id.
+#[tauri::command]
+pub async fn create_alert(
+    app: AppHandle,
+    symbol: String,
+    condition: Condition,
+) -> Result<i64, String> {
+    logged!("create_alert", [symbol], async {
+        let pool = db::pool(&app).await?;
+        let id = insert_alert(&pool, &symbol, &condition).await?;
+        let _ = app.emit("alerts-changed", ());
+        Ok(id)
+    })
+}
+
+/// Create alerts for a whole watchlist at once. Each entry succeeds or
+/// fails on its own; only a failed transaction fails the batch. Emits one
+/// `alerts-changed` at the end.
+#[tauri::command]
+pub async fn batch_create_alerts(
+    app: AppHandle,
+    alerts: Vec<NewAlert>,
+) -> Result<BatchResult, String> {
+    let count = alerts.len();
+    logged!("batch_create_alerts", [count], async {
+        let pool = db::pool(&app).await?;
+        let result = insert_batch(&pool, &alerts).await?;
+        if result.created > 0 {
+            let _ = app.emit("alerts-changed", ());
+        }
+        Ok(result)
+    })
+}
+
+#[tauri::command]
+pub async fn list_alerts(app: AppHandle) -> Result<Vec<Alert>, String> {
+    logged!("list_alerts", [], async {
+        let pool = db::pool(&app).await?;
+        load_alerts(&pool).await
+    })
+}
+
+#[tauri::command]
+pub async fn delete_alert(app: AppHandle, id: i64) -> Result<(), String> {
+    logged!("delete_alert", [id], async {
+        let pool = db::pool(&app).await?;
+        delete_alert_row(&pool, id).await?;
+        let _ = app.emit("alerts-changed", ());
+        Ok(())
+    })
+}
+
+/// Which open positions have alerts, how many are armed or met, and how
+/// close the price is to the nearest armed level. Prices are the ones the
+/// last `check_alerts` pass saw; without one, the distance is left out.
+#[tauri::command]
+pub async fn position_alerts_summary(
+    app: AppHandle,
+    quotes: State<'_, AlertQuotes>,
+) -> Result<Vec<PositionAlertStatus>, String> {
+    logged!("position_alerts_summary", [], async {
+        let pool = db::pool(&app).await?;
+        position_summary(&pool, &quotes.snapshot()).await
+    })
+}
+
+/// Silence alert notifications and webhooks, optionally until `until` (Unix
+/// seconds). Alerts keep being evaluated meanwhile. Emits `alerts-muted`.
+#[tauri::command]
+pub fn set_alerts_muted(
+    app: AppHandle,
+    mute: State<'_, AlertMute>,
+    muted: bool,
+    until: Option<i64>,
+) -> Result<(), String> {
+    logged!("set_alerts_muted", [muted, until], {
+        mute.set(muted, until);
+        tray::sync_alerts_muted(&app, muted);
+        let _ = app.emit("alerts-muted", muted);
+        Ok(())
+    })
+}
+
+/// Fires at or after `since` (UTC, `YYYY-MM-DD HH:MM:SS` like `fired_at`),
+/// oldest first.
+pub(crate) async fn load_history_since(
+    pool: &SqlitePool,
+    since: &str,
+) -> Result<Vec<AlertFire>, String> {
+    sqlx::query_as(
+        "SELECT id, alert_id, symbol, price, fired_at, message FROM alert_history \
+         WHERE fired_at >= ? ORDER BY fired_at, id LIMIT ?",
+    )
+    .bind(since)
+    .bind(MAX_HISTORY)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// When the main window last lost focus, in `fired_at` format. `None` while
+/// it has focus, or before it has had any.
+#[derive(Default)]
+pub struct LastFocus(Mutex<Option<String>>);
+
+/// Track focus on the main window. On regaining it, emit `missed-alerts`
+/// with whatever fired while it was away, if anything did. Nothing is
+/// notified again.
+pub(crate) fn main_window_focused(app: &AppHandle, focused: bool) {
+    let last_focus = app.state::<LastFocus>();
+    let mut away_since = last_focus.0.lock().unwrap_or_else(|e| e.into_inner());
+    if !focused {
+        away_since.get_or_insert_with(|| Utc::now().format("%Y-%m-%d %H:%M:%S").to_string());
+        return;
+    }
+    let Some(since) = away_since.take() else {
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let missed = match db::pool(&app).await {
+            Ok(pool) => load_history_since(&pool, &since).await,
+            Err(e) => Err(e),
+        };
+        match missed {
+            Ok(missed) if !missed.is_empty() => {
+                let _ = app.emit("missed-alerts", missed);
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("[alerts] Failed to load missed alerts: {}", e),
+        }
+    });
+}
+
+/// Alerts that have fired in the last 90 days, newest first.
+#[tauri::command]
+pub async fn get_alert_history(app: AppHandle, limit: i64) -> Result<Vec<AlertFire>, String> {
+    logged!("get_alert_history", [limit], async {
+        let pool = db::pool(&app).await?;
+        load_history(&pool, limit).await
+    })
+}
+
+/// Delete every alert matching `filter`, returning the count. Emits
+/// `alerts-changed` when anything went.
+#[tauri::command]
+pub async fn bulk_delete_alerts(app: AppHandle, filter: AlertFilter) -> Result<u64, String> {
+    logged!("bulk_delete_alerts", [], async {
+        let pool = db::pool(&app).await?;
+        let deleted = delete_matching(&pool, &filter).await?;
+        if deleted > 0 {
+            let _ = app.emit("alerts-changed", ());
+        }
+        Ok(deleted)
+    })
+}
+
+/// Write every alert and price target to a versioned `.json` file at
+/// `dest`, for backup or moving to another machine.
+#[tauri::command]
+pub async fn export_alerts_config(app: AppHandle, dest: String) -> Result<(), String> {
+    logged!("export_alerts_config", [dest], async {
+        let path = export::validate_destination(&dest, "json")?;
+        let pool = db::pool(&app).await?;
+        let config = export_config(&pool).await?;
+        let json = serde_json::to_vec_pretty(&config).map_err(|e| e.to_string())?;
+        export::write_file(&app, path, &json)
+    })
+}
+
+/// Merge a file written by `export_alerts_config` into the saved alerts
+/// and price targets, reporting what was added, skipped or in conflict.
+#[tauri::command]
+pub async fn import_alerts_config(app: AppHandle, src: String) -> Result<ImportReport, String> {
+    logged!("import_alerts_config", [src], async {
+        let path = export::validate_destination(&src, "json")?;
+        let text = export::read_file(&app, path)?;
+        let config: AlertsConfig =
+            serde_json::from_str(&text).map_err(|e| format!("Not an alerts file: {}", e))?;
+        let pool = db::pool(&app).await?;
+        let report = import_config(&pool, config).await?;
+        if report.alerts_added > 0 {
+            let _ = app.emit("alerts-changed", ());
+        }
+        Ok(report)
+    })
+}
+
+/// Evaluate saved alerts against the latest snapshots, keyed by symbol,
+/// and emit `alert-triggered` for each one that fires. Each is then sent
+/// through the delivery channels in the background, so a slow webhook
+/// doesn't hold up the monitor, which calls this after each refresh.
+#[tauri::command]
+pub async fn check_alerts(
+    app: AppHandle,
+    quotes: State<'_, AlertQuotes>,
+    mute: State<'_, AlertMute>,
+    snapshots: HashMap<String, MarketSnapshot>,
+) -> Result<Vec<TriggeredAlert>, String> {
+    logged!("check_alerts", [], async {
+        quotes.record(&snapshots);
+        let pool = db::pool(&app).await?;
+        let triggered = check(&pool, &snapshots).await?;
+        session::alerts_fired(triggered.len());
+        for alert in &triggered {
+            let _ = app.emit("alert-triggered", alert);
+        }
+        let now = Utc::now().timestamp();
+        tray::sync_alerts_muted(&app, mute.muted_at(now));
+        let deliver = deliverable(&mute, now, &triggered);
+        if !deliver.is_empty() {
+            let channels = delivery::channels(&app);
+            let messages: Vec<AlertMessage> = deliver
+                .into_iter()
+                .map(|alert| AlertMessage::new(&app, alert))
+                .collect();
+            tauri::async_runtime::spawn(async move {
+                delivery::deliver_all(&channels, &messages).await;
+            });
+        }
+        Ok(triggered)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(symbol: &str, condition: &'static str, threshold: f64) -> ParsedAlert {
+        ParsedAlert {
+            symbol: symbol.into(),
+            condition,
+            threshold,
+        }
+    }
+
+    #[test]
+    fn parses_common_phrasings() {
+        let cases = [
+            (
+                "alert me when AAPL drops below 180",
+                parsed("AAPL", "below", 180.0),
+            ),
+            ("tsla rises above $250.50", parsed("TSLA", "above", 250.5)),
+            ("MSFT crosses 400", parsed("MSFT", "crosses", 400.0)),
+            (
+                "tell me if nvda hits 1,000!",
+                parsed("NVDA", "crosses", 1000.0),
+            ),
+            ("AMD under 120", parsed("AMD", "below", 120.0)),
+            ("when $spy goes over 500", parsed("SPY", "above", 500.0)),
+            (
+                "if AAPL's price crosses below 170.",
+                parsed("AAPL", "below", 170.0),
+            ),
+        ];
+
+        for (text, expected) in cases {
+            assert_eq!(parse(text).unwrap(), expected, "{}", text);
+        }
+    }
+
+    fn leaf(metric: Metric, op: Comparison, value: f64) -> Condition {
+        Condition::Leaf { metric, op, value }
+    }
+
+    fn snapshot(price: f64, rsi: f64) -> MarketSnapshot {
+        MarketSnapshot {
+            price,
+            rsi: Some(rsi),
+            ..Default::default()
+        }
+    }
+
+    /// Evaluate with no price history, as for a freshly saved alert.
+    fn evaluate_now(condition: &Condition, snapshot: &MarketSnapshot) -> bool {
+        evaluate(condition, snapshot, snapshot.price)
+    }
+
+    #[test]
+    fn nested_groups_evaluate_and_or() {
+        // price < 180 AND (RSI < 30 OR volume > 1M)
+        let condition = Condition::AllOf {
+            conditions: vec![
+                leaf(Metric::Price, Comparison::Below, 180.0),
+                Condition::AnyOf {
+                    conditions: vec![
+                        leaf(Metric::Rsi, Comparison::Below, 30.0),
+                        leaf(Metric::Volume, Comparison::Above, 1_000_000.0),
+                    ],
+                },
+            ],
+        };
+
+        assert!(evaluate_now(&condition, &snapshot(175.0, 25.0)));
+        assert!(!evaluate_now(&condition, &snapshot(175.0, 45.0)));
+        assert!(!evaluate_now(&condition, &snapshot(185.0, 25.0)));
+
+        let heavy = MarketSnapshot {
+            volume: Some(2_000_000.0),
+            ..snapshot(175.0, 45.0)
+        };
+        assert!(evaluate_now(&condition, &heavy));
+    }
+
+    #[test]
+    fn missing_metrics_never_match() {
+        let condition = leaf(Metric::PctChange, Comparison::Below, -5.0);
+        assert!(!evaluate_now(&condition, &snapshot(100.0, 50.0)));
+    }
+
+    #[test]
+    fn validation_rejects_bad_trees() {
+        assert!(validate(&Condition::AnyOf { conditions: vec![] }).is_err());
+        assert!(validate(&leaf(Metric::Rsi, Comparison::Above, 120.0)).is_err());
+        assert!(validate(&leaf(Metric::Price, Comparison::Above, f64::NAN)).is_err());
+
+        let mut deep = leaf(Metric::Price, Comparison::Above, 1.0);
+        for _ in 0..MAX_DEPTH {
+            deep = Condition::AllOf {
+                conditions: vec![deep],
+            };
+        }
+        assert!(validate(&deep).is_err());
+    }
+
+    #[tokio::test]
+    async fn alerts_fire_on_the_rising_edge_only() {
+        let pool = crate::db::testing::pool().await;
+        let id = insert_alert(
+            &pool,
+            "aapl",
+            &leaf(Metric::Price, Comparison::Below, 180.0),
+        )
+        .await
+        .unwrap();
+        let at = |price| HashMap::from([("AAPL".to_string(), snapshot(price, 50.0))]);
+
+        assert!(check(&pool, &at(185.0)).await.unwrap().is_empty());
+        let fired = check(&pool, &at(179.0)).await.unwrap();
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].id, id);
+        assert!(check(&pool, &at(178.0)).await.unwrap().is_empty());
+
+        check(&pool, &at(181.0)).await.unwrap();
+        assert_eq!(check(&pool, &at(179.0)).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn trailing_stop_fires_once_after_falling_from_the_high() {
+        let pool = crate::db::testing::pool().await;
+        let id = insert_alert(&pool, "AAPL", &Condition::TrailingStop { trail_pct: 10.0 })
+            .await
+            .unwrap();
+        let at = |price| HashMap::from([("AAPL".to_string(), snapshot(price, 50.0))]);
+
+        let mut fired = 0;
+        for price in [100.0, 110.0, 120.0, 115.0, 107.0, 105.0, 106.0] {
+            fired += check(&pool, &at(price)).await.unwrap().len();
+        }
+        assert_eq!(fired, 1);
+        let high: f64 = sqlx::query_scalar("SELECT high_water FROM alerts WHERE id = ?")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(high, 120.0);
+        assert!(
+            insert_alert(&pool, "AAPL", &Condition::TrailingStop { trail_pct: 0.0 })
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn bulk_delete_filters_by_symbol_type_and_state() {
+        let pool = crate::db::testing::pool().await;
+        let below = leaf(Metric::Price, Comparison::Below, 180.0);
+        for symbol in ["AAPL", "AAPL", "MSFT"] {
+            insert_alert(&pool, symbol, &below).await.unwrap();
+        }
+        insert_alert(&pool, "MSFT", &Condition::TrailingStop { trail_pct: 5.0 })
+            .await
+            .unwrap();
+        let symbols = |pool| async move {
+            load_alerts(pool)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|a| a.symbol)
+                .collect::<Vec<_>>()
+        };
+
+        assert!(delete_matching(&pool, &AlertFilter::default())
+            .await
+            .is_err());
+        let by_symbol = AlertFilter {
+            symbol: Some("aapl".into()),
+            ..Default::default()
+        };
+        assert_eq!(delete_matching(&pool, &by_symbol).await.unwrap(), 2);
+        assert_eq!(symbols(&pool).await, ["MSFT", "MSFT"]);
+
+        let by_kind = AlertFilter {
+            kind: Some("trailing_stop".into()),
+            ..Default::default()
+        };
+        assert_eq!(delete_matching(&pool, &by_kind).await.unwrap(), 1);
+        let triggered = AlertFilter {
+            triggered: true,
+            ..Default::default()
+        };
+        assert_eq!(delete_matching(&pool, &triggered).await.unwrap(), 0);
+        let msft = HashMap::from([("MSFT".to_string(), snapshot(170.0, 50.0))]);
+        check(&pool, &msft).await.unwrap();
+        assert_eq!(delete_matching(&pool, &triggered).await.unwrap(), 1);
+        assert!(symbols(&pool).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_malformed_row_is_skipped_not_fatal() {
+        let pool = crate::db::testing::pool().await;
+        sqlx::query(
+            "INSERT INTO alerts (symbol, condition) VALUES ('AAPL', '{\"type\": \"bogus\"}')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let condition = leaf(Metric::Price, Comparison::Below, 100.0);
+        let id = insert_alert(&pool, "AAPL", &condition).await.unwrap();
+
+        let snapshots = HashMap::from([("AAPL".to_string(), snapshot(95.0, 50.0))]);
+        let triggered = check(&pool, &snapshots).await.unwrap();
+        assert_eq!(triggered.len(), 1);
+        assert_eq!(triggered[0].id, id);
+        let alerts = load_alerts(&pool).await.unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].id, id);
+    }
+
+    #[tokio::test]
+    async fn each_fire_writes_one_history_row() {
+        let pool = crate::db::testing::pool().await;
+        let condition = leaf(Metric::Price, Comparison::Below, 100.0);
+        let id = insert_alert(&pool, "AAPL", &condition).await.unwrap();
+        for price in [95.0, 90.0] {
+            let snapshots = HashMap::from([("AAPL".to_string(), snapshot(price, 50.0))]);
+            check(&pool, &snapshots).await.unwrap();
+        }
+
+        let history = load_history(&pool, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].alert_id, id);
+        assert_eq!(history[0].price, 95.0);
+        assert_eq!(history[0].message, "Price below 100");
+        assert_eq!(
+            load_history_since(&pool, "2000-01-01 00:00:00")
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(load_history_since(&pool, "2999-01-01 00:00:00")
+            .await
+            .unwrap()
+            .is_empty());
+
+        sqlx::query("UPDATE alert_history SET fired_at = datetime('now', '-100 days')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        check(&pool, &HashMap::new()).await.unwrap();
+        assert!(load_history(&pool, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn muted_alerts_advance_without_delivering() {
+        let pool = crate::db::testing::pool().await;
+        let condition = leaf(Metric::Price, Comparison::Below, 100.0);
+        insert_alert(&pool, "AAPL", &condition).await.unwrap();
+        let low = HashMap::from([("AAPL".to_string(), snapshot(95.0, 50.0))]);
+        let mute = AlertMute::default();
+        mute.set(true, Some(1_000));
+
+        let triggered = check(&pool, &low).await.unwrap();
+        assert_eq!(triggered.len(), 1);
+        assert!(deliverable(&mute, 999, &triggered).is_empty());
+        let rows = load_rows(&pool).await.unwrap();
+        assert!(rows[0].last_met);
+
+        // Unmuted by `until`, the crossing made while muted isn't replayed
+        assert!(!mute.muted_at(1_000));
+        assert!(check(&pool, &low).await.unwrap().is_empty());
+        assert_eq!(deliverable(&mute, 1_000, &triggered).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn summary_covers_open_positions_with_alerts() {
+        let pool = crate::db::testing::pool().await;
+        crate::db::testing::insert_open(&pool, "AAPL", 10, 150.0).await;
+        crate::db::testing::insert_open(&pool, "MSFT", 5, 400.0).await;
+        for value in [180.0, 210.0] {
+            insert_alert(
+                &pool,
+                "AAPL",
+                &leaf(Metric::Price, Comparison::Above, value),
+            )
+            .await
+            .unwrap();
+        }
+        insert_alert(
+            &pool,
+            "AAPL",
+            &leaf(Metric::Price, Comparison::Below, 150.0),
+        )
+        .await
+        .unwrap();
+        // Not a position
+        insert_alert(
+            &pool,
+            "TSLA",
+            &leaf(Metric::Price, Comparison::Above, 300.0),
+        )
+        .await
+        .unwrap();
+        let quotes = HashMap::from([("AAPL".to_string(), 140.0)]);
+        let snapshots = HashMap::from([("AAPL".to_string(), snapshot(140.0, 50.0))]);
+        check(&pool, &snapshots).await.unwrap();
+
+        let summary = position_summary(&pool, &quotes).await.unwrap();
+        assert_eq!(summary.len(), 1);
+        let aapl = &summary[0];
+        assert_eq!((aapl.active_alerts, aapl.triggered_alerts), (2, 1));
+        assert_eq!(aapl.nearest_threshold, Some(180.0));
+        assert!((aapl.distance_pct.unwrap() - 40.0 / 1.4).abs() < 1e-9);
+
+        let unpriced = position_summary(&pool, &HashMap::new()).await.unwrap();
+        assert_eq!(unpriced[0].nearest_threshold, None);
+        assert_eq!(unpriced[0].active_alerts, 2);
+    }
+
+    #[tokio::test]
+    async fn batch_keeps_valid_alerts_and_reports_bad_ones() {
+        let pool = crate::db::testing::pool().await;
+        let new = |symbol: &str, value| NewAlert {
+            symbol: symbol.into(),
+            condition: leaf(Metric::Rsi, Comparison::Below, value),
+        };
+        let batch = [
+            new("AAPL", 30.0),
+            new("MSFT", 150.0),
+            new(" ", 30.0),
+            new("nvda", 25.0),
+        ];
+
+        let result = insert_batch(&pool, &batch).await.unwrap();
+        assert_eq!(result.created, 2);
+        let ok: Vec<bool> = result.results.iter().map(|r| r.id.is_some()).collect();
+        assert_eq!(ok, [true, false, false, true]);
+        assert!(result.results[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("threshold"));
+
+        let symbols: Vec<String> = load_alerts(&pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|a| a.symbol)
+            .collect();
+        assert_eq!(symbols, ["AAPL", "NVDA"]);
+    }
+
+    #[tokio::test]
+    async fn config_round_trips_and_merges() {
+        let pool = crate::db::testing::pool().await;
+        let rsi = leaf(Metric::Rsi, Comparison::Below, 30.0);
+        insert_alert(&pool, "AAPL", &rsi).await.unwrap();
+        let dip = Condition::AllOf {
+            conditions: vec![leaf(Metric::Price, Comparison::Below, 400.0), rsi.clone()],
+        };
+        insert_alert(&pool, "MSFT", &dip).await.unwrap();
+        targets::write_target(&pool, "AAPL", 210.0, "services growth")
+            .await
+            .unwrap();
+
+        let json = serde_json::to_string(&export_config(&pool).await.unwrap()).unwrap();
+        let fresh = crate::db::testing::pool().await;
+        let config = serde_json::from_str(&json).unwrap();
+        let report = import_config(&fresh, config).await.unwrap();
+        assert_eq!((report.alerts_added, report.targets_added), (2, 1));
+        let exported: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let reexported = serde_json::to_value(export_config(&fresh).await.unwrap()).unwrap();
+        assert_eq!(reexported, exported);
+
+        // importing into the original merges: nothing new, one conflict
+        targets::write_target(&pool, "AAPL", 200.0, "")
+            .await
+            .unwrap();
+        let mut config: AlertsConfig = serde_json::from_str(&json).unwrap();
+        config.alerts.push(ConfigAlert {
+            symbol: "NVDA".into(),
+            condition: serde_json::json!({"type": "leaf", "metric": "rsi", "op": "below", "value": 150}),
+        });
+        let report = import_config(&pool, config).await.unwrap();
+        assert_eq!((report.alerts_added, report.unchanged), (0, 2));
+        assert_eq!(report.conflicts.len(), 1);
+        assert!(report.skipped[0].starts_with("Alert 3 (NVDA)"));
+        assert_eq!(load_alerts(&pool).await.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn unparseable_input_explains_what_is_missing() {
+        assert!(parse("buy more apple").unwrap_err().contains("condition"));
+        assert!(parse("AAPL above").unwrap_err().contains("price"));
+        assert!(parse("above 180").unwrap_err().contains("symbol"));
+        assert!(parse("when apple-inc,ltd drops below 180")
+            .unwrap_err()
+            .contains("symbol"));
+    }
+}
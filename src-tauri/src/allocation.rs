@@ -0,0 +1,270 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+
+use crate::db;
+
+/// What a target is set on: one symbol, or a GICS sector as a whole.
+const GROUPS: &[&str] = &["symbol", "sector"];
+
+/// Targets in a group must add up to 100% give or take this much, so
+/// rounded figures like three times 33.3 are accepted.
+const SUM_TOLERANCE_PCT: f64 = 0.5;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AllocationTarget {
+    group: String,
+    name: String,
+    target_pct: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetDrift {
+    group: String,
+    pub(crate) name: String,
+    pub(crate) weight_pct: f64,
+    pub(crate) target_pct: f64,
+    /// Weight minus target; negative when underweight.
+    drift_pct: f64,
+}
+
+fn check_group(group: &str) -> Result<(), String> {
+    if GROUPS.contains(&group) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unknown target group '{}', expected one of: {}",
+            group,
+            GROUPS.join(", ")
+        ))
+    }
+}
+
+/// Normalized names and targets for `group`, checked to be in range and to
+/// sum to 100%. An empty set is allowed and clears the group.
+pub(crate) fn validate_targets(
+    group: &str,
+    targets: &HashMap<String, f64>,
+) -> Result<BTreeMap<String, f64>, String> {
+    check_group(group)?;
+    let mut clean = BTreeMap::new();
+    for (name, pct) in targets {
+        let name = if group == "symbol" {
+            db::clean_symbol(name)?
+        } else {
+            let name = name.trim();
+            if name.is_empty() {
+                return Err("Sector is required".into());
+            }
+            name.to_string()
+        };
+        if !pct.is_finite() || !(0.0..=100.0).contains(pct) {
+            return Err(format!("Target for {} must be between 0% and 100%", name));
+        }
+        if clean.insert(name.clone(), *pct).is_some() {
+            return Err(format!("{} has more than one target", name));
+        }
+    }
+
+    let sum: f64 = clean.values().sum();
+    if !clean.is_empty() && (sum - 100.0).abs() > SUM_TOLERANCE_PCT {
+        return Err(format!("Targets add up to {:.1}%, expected 100%", sum));
+    }
+    Ok(clean)
+}
+
+/// Current weights, in percent of gross exposure, per symbol and per
+/// sector. Symbols missing from `sectors` count towards the total but no
+/// sector.
+pub(crate) fn group_weights(
+    exposures: &[(String, f64)],
+    sectors: &HashMap<String, String>,
+) -> HashMap<(String, String), f64> {
+    let total: f64 = exposures.iter().map(|(_, v)| v).sum();
+    let mut weights = HashMap::new();
+    if total <= 0.0 {
+        return weights;
+    }
+    for (symbol, value) in exposures {
+        let pct = value / total * 100.0;
+        weights.insert(("symbol".to_string(), symbol.clone()), pct);
+        if let Some(sector) = sectors.get(symbol) {
+            *weights
+                .entry(("sector".to_string(), sector.trim().to_string()))
+                .or_default() += pct;
+        }
+    }
+    weights
+}
+
+/// Targets whose weight has just moved more than `band_pct` away from
+/// them, either way. `drifted` carries which targets were already outside
+/// the band between checks, so each excursion alerts once.
+pub(crate) fn target_drifts(
+    weights: &HashMap<(String, String), f64>,
+    targets: &[AllocationTarget],
+    band_pct: f64,
+    drifted: &mut HashSet<(String, String)>,
+) -> Vec<TargetDrift> {
+    let mut alerts = Vec::new();
+    let mut now_drifted = HashSet::new();
+    for target in targets {
+        let key = (target.group.clone(), target.name.clone());
+        let weight_pct = weights.get(&key).copied().unwrap_or(0.0);
+        let drift_pct = weight_pct - target.target_pct;
+        if drift_pct.abs() <= band_pct {
+            continue;
+        }
+        if !drifted.contains(&key) {
+            alerts.push(TargetDrift {
+                group: target.group.clone(),
+                name: target.name.clone(),
+                weight_pct,
+                target_pct: target.target_pct,
+                drift_pct,
+            });
+        }
+        now_drifted.insert(key);
+    }
+    *drifted = now_drifted;
+    alerts
+}
+
+// ---------------------------------------------------------------------------
+// Queries
+// ---------------------------------------------------------------------------
+
+/// Replace every target in `group` at once, so the sum is never checked
+/// against a half-updated set.
+pub(crate) async fn save_targets(
+    pool: &SqlitePool,
+    group: &str,
+    targets: &HashMap<String, f64>,
+) -> Result<(), String> {
+    let targets = validate_targets(group, targets)?;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM allocation_targets WHERE target_group = ?")
+        .bind(group)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    for (name, pct) in &targets {
+        sqlx::query(
+            "INSERT INTO allocation_targets (target_group, name, target_pct) VALUES (?, ?, ?)",
+        )
+        .bind(group)
+        .bind(name)
+        .bind(pct)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+pub(crate) async fn load_targets(pool: &SqlitePool) -> Result<Vec<AllocationTarget>, String> {
+    sqlx::query_as(
+        "SELECT target_group AS \"group\", name, target_pct FROM allocation_targets \
+         ORDER BY target_group DESC, name",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Set the target allocation for a `group` (`symbol` or `sector`) as
+/// percentages summing to 100. Replaces the group's previous targets; an
+/// empty map clears them. `check_allocation` monitors drift from these.
+#[tauri::command]
+pub async fn set_allocation_targets(
+    app: AppHandle,
+    group: String,
+    targets: HashMap<String, f64>,
+) -> Result<(), String> {
+    logged!("set_allocation_targets", [group, targets], async {
+        let pool = db::pool(&app).await?;
+        save_targets(&pool, &group, &targets).await
+    })
+}
+
+#[tauri::command]
+pub async fn get_allocation_targets(app: AppHandle) -> Result<Vec<AllocationTarget>, String> {
+    logged!("get_allocation_targets", [], async {
+        let pool = db::pool(&app).await?;
+        load_targets(&pool).await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing;
+
+    #[test]
+    fn drift_alerts_once_per_excursion_either_way() {
+        let targets = vec![AllocationTarget {
+            group: "symbol".into(),
+            name: "AAPL".into(),
+            target_pct: 50.0,
+        }];
+        let mut drifted = HashSet::new();
+        let mut check = |aapl: f64| {
+            let exposures = vec![
+                ("AAPL".to_string(), aapl),
+                ("MSFT".to_string(), 100.0 - aapl),
+            ];
+            let weights = group_weights(&exposures, &HashMap::new());
+            target_drifts(&weights, &targets, 5.0, &mut drifted)
+        };
+
+        assert!(check(54.0).is_empty());
+        let alerts = check(57.0);
+        assert_eq!(alerts.len(), 1);
+        assert!((alerts[0].drift_pct - 7.0).abs() < 1e-9);
+        assert!(check(60.0).is_empty());
+        assert!(check(50.0).is_empty());
+        assert!((check(44.0)[0].drift_pct + 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sector_weights_sum_their_symbols() {
+        let exposures = vec![
+            ("JPM".to_string(), 300.0),
+            ("BAC".to_string(), 200.0),
+            ("XYZ".to_string(), 500.0),
+        ];
+        let sectors = HashMap::from([
+            ("JPM".to_string(), "Financials".to_string()),
+            ("BAC".to_string(), "Financials".to_string()),
+        ]);
+        let weights = group_weights(&exposures, &sectors);
+        let financials = weights[&("sector".to_string(), "Financials".to_string())];
+        assert!((financials - 50.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn targets_must_sum_to_a_hundred() {
+        let pool = testing::pool().await;
+        let thirds = HashMap::from([
+            ("aapl".to_string(), 33.3),
+            ("MSFT".to_string(), 33.3),
+            ("NVDA".to_string(), 33.3),
+        ]);
+        save_targets(&pool, "symbol", &thirds).await.unwrap();
+        assert_eq!(load_targets(&pool).await.unwrap()[0].name, "AAPL");
+
+        let short = HashMap::from([("AAPL".to_string(), 60.0)]);
+        assert!(save_targets(&pool, "symbol", &short).await.is_err());
+        assert!(save_targets(&pool, "industry", &HashMap::new())
+            .await
+            .is_err());
+        // the failed update left the saved set alone
+        assert_eq!(load_targets(&pool).await.unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,397 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tax::trade_date;
+
+const MAX_BINS: usize = 1_000;
+
+#[derive(Debug, Serialize)]
+pub struct HistogramBin {
+    lower: f64,
+    upper: f64,
+    count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistogramResult {
+    bins: Vec<HistogramBin>,
+    mean: f64,
+    /// Sample standard deviation; `None` with a single return.
+    std: Option<f64>,
+    /// Adjusted Fisher-Pearson skewness; needs at least 3 returns.
+    skew: Option<f64>,
+    /// Sample excess kurtosis; needs at least 4 returns.
+    kurtosis: Option<f64>,
+}
+
+fn validate_prices(prices: &[f64]) -> Result<(), String> {
+    if prices.len() < 2 {
+        return Err("At least 2 prices are needed".into());
+    }
+    if prices.iter().any(|p| !p.is_finite() || *p <= 0.0) {
+        return Err("Prices must be positive".into());
+    }
+    Ok(())
+}
+
+/// Simple period-over-period returns, one shorter than `prices`.
+pub(crate) fn returns(prices: &[f64]) -> Vec<f64> {
+    prices.windows(2).map(|w| w[1] / w[0] - 1.0).collect()
+}
+
+fn histogram(values: &[f64], bins: usize) -> Vec<HistogramBin> {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let width = (max - min) / bins as f64;
+
+    let mut counts = vec![0; bins];
+    for value in values {
+        let bin = if width > 0.0 {
+            (((value - min) / width) as usize).min(bins - 1)
+        } else {
+            0
+        };
+        counts[bin] += 1;
+    }
+
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| HistogramBin {
+            lower: min + width * i as f64,
+            upper: if i + 1 == bins {
+                max
+            } else {
+                min + width * (i + 1) as f64
+            },
+            count,
+        })
+        .collect()
+}
+
+pub(crate) fn distribution(prices: &[f64], bins: usize) -> Result<HistogramResult, String> {
+    validate_prices(prices)?;
+    if bins == 0 || bins > MAX_BINS {
+        return Err(format!("Bins must be between 1 and {}", MAX_BINS));
+    }
+
+    let values = returns(prices);
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let moment = |power: i32| values.iter().map(|r| (r - mean).powi(power)).sum::<f64>();
+
+    let std = (values.len() >= 2).then(|| (moment(2) / (n - 1.0)).sqrt());
+    let spread = std.filter(|s| *s > 0.0);
+    let skew = spread
+        .filter(|_| values.len() >= 3)
+        .map(|s| n / ((n - 1.0) * (n - 2.0)) * moment(3) / s.powi(3));
+    let kurtosis = spread.filter(|_| values.len() >= 4).map(|s| {
+        n * (n + 1.0) / ((n - 1.0) * (n - 2.0) * (n - 3.0)) * moment(4) / s.powi(4)
+            - 3.0 * (n - 1.0).powi(2) / ((n - 2.0) * (n - 3.0))
+    });
+
+    Ok(HistogramResult {
+        bins: histogram(&values, bins),
+        mean,
+        std,
+        skew,
+        kurtosis,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Drawdowns
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize)]
+pub struct EquityPoint {
+    date: String,
+    value: f64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DrawdownPeriod {
+    peak_date: String,
+    trough_date: String,
+    /// `None` while the drawdown is ongoing.
+    recovery_date: Option<String>,
+    depth_pct: f64,
+    /// Peak to recovery, or to the last point if not yet recovered.
+    duration_days: i64,
+}
+
+fn days_between(from: &str, to: &str) -> Result<i64, String> {
+    let parse = |date: &str| trade_date(date).ok_or_else(|| format!("Invalid date '{}'", date));
+    Ok((parse(to)? - parse(from)?).num_days())
+}
+
+fn episode(
+    peak: &EquityPoint,
+    trough: &EquityPoint,
+    end: &EquityPoint,
+    recovered: bool,
+) -> Result<DrawdownPeriod, String> {
+    Ok(DrawdownPeriod {
+        peak_date: peak.date.clone(),
+        trough_date: trough.date.clone(),
+        recovery_date: recovered.then(|| end.date.clone()),
+        depth_pct: (peak.value - trough.value) / peak.value * 100.0,
+        duration_days: days_between(&peak.date, &end.date)?,
+    })
+}
+
+/// Every peak-to-trough-to-recovery episode in a date-ordered equity curve,
+/// deepest first.
+pub(crate) fn find_drawdowns(equity: &[EquityPoint]) -> Result<Vec<DrawdownPeriod>, String> {
+    if equity
+        .iter()
+        .any(|p| !p.value.is_finite() || p.value <= 0.0)
+    {
+        return Err("Equity values must be positive".into());
+    }
+
+    let mut periods = Vec::new();
+    let Some(first) = equity.first() else {
+        return Ok(periods);
+    };
+    let mut peak = first;
+    let mut trough: Option<&EquityPoint> = None;
+
+    for point in &equity[1..] {
+        if point.value >= peak.value {
+            if let Some(low) = trough.take() {
+                periods.push(episode(peak, low, point, true)?);
+            }
+            peak = point;
+        } else if trough.is_none_or(|low| point.value < low.value) {
+            trough = Some(point);
+        }
+    }
+    if let Some(low) = trough {
+        periods.push(episode(
+            peak,
+            low,
+            equity.last().expect("not empty"),
+            false,
+        )?);
+    }
+
+    periods.sort_by(|a, b| b.depth_pct.total_cmp(&a.depth_pct));
+    Ok(periods)
+}
+
+// ---------------------------------------------------------------------------
+// Correlation
+// ---------------------------------------------------------------------------
+
+/// Pearson correlation, or `None` if either side doesn't vary.
+fn pearson(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    let n = xs.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = ys.iter().sum::<f64>() / n;
+
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in xs.iter().zip(ys) {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    (var_x > 0.0 && var_y > 0.0).then(|| cov / (var_x * var_y).sqrt())
+}
+
+/// Correlation of the last `window` returns at each price, aligned with the
+/// input. The first `window` entries are `None` while returns accumulate.
+pub(crate) fn rolling_pearson(
+    asset: &[f64],
+    benchmark: &[f64],
+    window: usize,
+) -> Result<Vec<Option<f64>>, String> {
+    if asset.len() != benchmark.len() {
+        return Err(format!(
+            "Series lengths differ ({} vs {})",
+            asset.len(),
+            benchmark.len()
+        ));
+    }
+    if window < 2 {
+        return Err("Window must be at least 2".into());
+    }
+    validate_prices(asset)?;
+    validate_prices(benchmark)?;
+
+    let (asset, benchmark) = (returns(asset), returns(benchmark));
+    let mut values = vec![None; window.min(asset.len() + 1)];
+    for end in window..=asset.len() {
+        values.push(pearson(
+            &asset[end - window..end],
+            &benchmark[end - window..end],
+        ));
+    }
+    Ok(values)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Histogram and moments of the daily returns in a price series.
+#[tauri::command]
+pub fn returns_distribution(prices: Vec<f64>, bins: usize) -> Result<HistogramResult, String> {
+    logged!("returns_distribution", [bins], {
+        distribution(&prices, bins)
+    })
+}
+
+#[tauri::command]
+pub fn drawdown_periods(equity: Vec<EquityPoint>) -> Result<Vec<DrawdownPeriod>, String> {
+    logged!("drawdown_periods", [], { find_drawdowns(&equity) })
+}
+
+#[tauri::command]
+pub fn rolling_correlation(
+    asset: Vec<f64>,
+    benchmark: Vec<f64>,
+    window: usize,
+) -> Result<Vec<Option<f64>>, String> {
+    logged!("rolling_correlation", [window], {
+        rolling_pearson(&asset, &benchmark, window)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Prices that produce exactly these returns.
+    fn prices_from(returns: &[f64]) -> Vec<f64> {
+        let mut prices = vec![100.0];
+        for r in returns {
+            prices.push(prices.last().unwrap() * (1.0 + r));
+        }
+        prices
+    }
+
+    #[test]
+    fn symmetric_sample_moments() {
+        let prices = prices_from(&[-0.02, -0.01, 0.0, 0.01, 0.02]);
+        let result = distribution(&prices, 5).unwrap();
+
+        assert!(result.mean.abs() < 1e-9);
+        assert!((result.std.unwrap() - 0.00025f64.sqrt()).abs() < 1e-9);
+        assert!(result.skew.unwrap().abs() < 1e-6);
+        assert!((result.kurtosis.unwrap() + 1.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn bins_cover_every_return() {
+        let prices = prices_from(&[-0.03, 0.01, 0.01, 0.02, 0.05, -0.01]);
+        let result = distribution(&prices, 4).unwrap();
+
+        assert_eq!(result.bins.len(), 4);
+        assert_eq!(result.bins.iter().map(|b| b.count).sum::<usize>(), 6);
+        assert!((result.bins[0].lower + 0.03).abs() < 1e-9);
+        assert!((result.bins[3].upper - 0.05).abs() < 1e-9);
+    }
+
+    #[test]
+    fn right_tail_skews_positive() {
+        let prices = prices_from(&[0.0, 0.0, 0.0, 0.0, 0.1]);
+        assert!(distribution(&prices, 3).unwrap().skew.unwrap() > 1.0);
+    }
+
+    #[test]
+    fn rejects_short_series_and_zero_bins() {
+        assert!(distribution(&[100.0], 5).is_err());
+        assert!(distribution(&[100.0, 101.0], 0).is_err());
+        assert!(distribution(&[100.0, -1.0], 5).is_err());
+
+        let single = distribution(&[100.0, 101.0], 3).unwrap();
+        assert!(single.std.is_none() && single.skew.is_none());
+    }
+
+    fn curve(points: &[(&str, f64)]) -> Vec<EquityPoint> {
+        points
+            .iter()
+            .map(|&(date, value)| EquityPoint {
+                date: date.into(),
+                value,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_two_drawdowns_deepest_first() {
+        let equity = curve(&[
+            ("2024-01-01", 100.0),
+            ("2024-01-05", 90.0),
+            ("2024-01-10", 101.0),
+            ("2024-01-15", 120.0),
+            ("2024-01-20", 90.0),
+            ("2024-01-25", 96.0),
+            ("2024-01-31", 125.0),
+        ]);
+
+        let periods = find_drawdowns(&equity).unwrap();
+        assert_eq!(periods.len(), 2);
+        assert_eq!(
+            periods[0],
+            DrawdownPeriod {
+                peak_date: "2024-01-15".into(),
+                trough_date: "2024-01-20".into(),
+                recovery_date: Some("2024-01-31".into()),
+                depth_pct: 25.0,
+                duration_days: 16,
+            }
+        );
+        assert_eq!(periods[1].depth_pct, 10.0);
+        assert_eq!(periods[1].duration_days, 9);
+    }
+
+    #[test]
+    fn ongoing_drawdown_has_no_recovery() {
+        let equity = curve(&[
+            ("2024-01-01", 100.0),
+            ("2024-01-02", 80.0),
+            ("2024-01-04", 90.0),
+        ]);
+
+        let periods = find_drawdowns(&equity).unwrap();
+        assert_eq!(periods.len(), 1);
+        assert_eq!(periods[0].recovery_date, None);
+        assert_eq!(periods[0].trough_date, "2024-01-02");
+        assert_eq!(periods[0].duration_days, 3);
+    }
+
+    #[test]
+    fn rising_curve_has_no_drawdowns() {
+        let equity = curve(&[("2024-01-01", 100.0), ("2024-01-02", 110.0)]);
+        assert!(find_drawdowns(&equity).unwrap().is_empty());
+        assert!(find_drawdowns(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rolling_correlation_tracks_direction() {
+        let moves = [0.01, -0.02, 0.03, -0.01, 0.02];
+        let asset = prices_from(&moves);
+        let same = prices_from(&moves.map(|r| r * 2.0));
+        let inverted = prices_from(&moves.map(|r| -r));
+
+        let values = rolling_pearson(&asset, &same, 3).unwrap();
+        assert_eq!(values.len(), asset.len());
+        assert_eq!(values[..3], [None, None, None]);
+        assert!(values[3..].iter().all(|v| (v.unwrap() - 1.0).abs() < 1e-9));
+
+        let values = rolling_pearson(&asset, &inverted, 3).unwrap();
+        assert!(values[3..].iter().all(|v| (v.unwrap() + 1.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn rolling_correlation_validates_input() {
+        let prices = [100.0, 101.0, 102.0];
+        assert!(rolling_pearson(&prices, &prices[..2], 2).is_err());
+        assert!(rolling_pearson(&prices, &prices, 1).is_err());
+        assert_eq!(
+            rolling_pearson(&prices, &prices, 5).unwrap(),
+            vec![None, None, None]
+        );
+    }
+}
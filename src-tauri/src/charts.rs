@@ -0,0 +1,136 @@
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::settings::{self, SettingsPatch};
+
+/// A chart range and the Alpha Vantage series that covers it. Compact
+/// output is the latest 100 points, so longer ranges need the full series.
+#[derive(Debug, Serialize)]
+pub struct RangePreset {
+    id: &'static str,
+    label: &'static str,
+    /// Calendar days shown; the series is trimmed to these.
+    days: u32,
+    function: &'static str,
+    interval: Option<&'static str>,
+    outputsize: Option<&'static str>,
+}
+
+const fn preset(
+    id: &'static str,
+    label: &'static str,
+    days: u32,
+    function: &'static str,
+    interval: Option<&'static str>,
+    outputsize: Option<&'static str>,
+) -> RangePreset {
+    RangePreset {
+        id,
+        label,
+        days,
+        function,
+        interval,
+        outputsize,
+    }
+}
+
+pub(crate) const RANGES: &[RangePreset] = &[
+    preset(
+        "1D",
+        "1 day",
+        1,
+        "TIME_SERIES_INTRADAY",
+        Some("5min"),
+        Some("compact"),
+    ),
+    preset(
+        "1W",
+        "1 week",
+        7,
+        "TIME_SERIES_INTRADAY",
+        Some("60min"),
+        Some("compact"),
+    ),
+    preset(
+        "1M",
+        "1 month",
+        31,
+        "TIME_SERIES_DAILY_ADJUSTED",
+        None,
+        Some("compact"),
+    ),
+    preset(
+        "3M",
+        "3 months",
+        92,
+        "TIME_SERIES_DAILY_ADJUSTED",
+        None,
+        Some("compact"),
+    ),
+    preset(
+        "6M",
+        "6 months",
+        183,
+        "TIME_SERIES_DAILY_ADJUSTED",
+        None,
+        Some("full"),
+    ),
+    preset(
+        "1Y",
+        "1 year",
+        365,
+        "TIME_SERIES_DAILY_ADJUSTED",
+        None,
+        Some("full"),
+    ),
+    preset(
+        "5Y",
+        "5 years",
+        1826,
+        "TIME_SERIES_WEEKLY_ADJUSTED",
+        None,
+        None,
+    ),
+];
+
+pub(crate) fn range_ids() -> Vec<&'static str> {
+    RANGES.iter().map(|r| r.id).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Supported chart ranges, shortest first, with the series to request for
+/// each.
+#[tauri::command]
+pub fn get_chart_ranges() -> &'static [RangePreset] {
+    RANGES
+}
+
+/// Range a chart opens at, by preset id such as `1M`.
+#[tauri::command]
+pub fn set_default_chart_range(app: AppHandle, range: String) -> Result<(), String> {
+    logged!("set_default_chart_range", [range], {
+        let patch = SettingsPatch {
+            default_chart_range: Some(range),
+            ..Default::default()
+        };
+        settings::update_settings(&app, patch).map(|_| ())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_are_unique_and_ordered() {
+        let ids = range_ids();
+        let mut unique = ids.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), ids.len());
+        assert!(RANGES.windows(2).all(|w| w[0].days < w[1].days));
+    }
+}
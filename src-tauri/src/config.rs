@@ -0,0 +1,191 @@
+//! Startup validation and versioned migration of the settings store.
+
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_store::StoreExt;
+
+use crate::{drafts, settings, STORE_FILE};
+
+const CONFIG_VERSION_KEY: &str = "config_version";
+
+/// Layout version written by this build. Stores without a version key
+/// predate versioning and are treated as version 1.
+const CONFIG_VERSION: u64 = 3;
+
+/// `MIGRATIONS[i]` upgrades a store from version `i + 1` to `i + 2`.
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[v1_to_v2, v2_to_v3];
+
+/// v1 is every store written before versioning existed. Its keys carried
+/// over unchanged; anything malformed is dropped by validation afterwards.
+fn v1_to_v2(_config: &mut Map<String, Value>) {}
+
+/// v3 groups user preferences into a single `settings` object.
+fn v2_to_v3(config: &mut Map<String, Value>) {
+    let mut grouped = Map::new();
+    for key in ["startup_view", "timezone_override"] {
+        if let Some(value) = config.remove(key) {
+            grouped.insert(key.into(), value);
+        }
+    }
+    if !grouped.is_empty() {
+        config
+            .entry(settings::SETTINGS_KEY)
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Some(Value::Object(existing)) = config.get_mut(settings::SETTINGS_KEY) {
+            for (key, value) in grouped {
+                existing.entry(key).or_insert(value);
+            }
+        }
+    }
+}
+
+struct KnownKey {
+    name: &'static str,
+    valid: fn(&Value) -> bool,
+    default: Option<fn() -> Value>,
+}
+
+fn valid_view(value: &Value) -> bool {
+    value
+        .as_str()
+        .is_some_and(|v| settings::validate_view(v).is_ok())
+}
+
+/// Every key the backend reads. Anything else in the store is junk from an
+/// older build and gets removed.
+const KNOWN_KEYS: &[KnownKey] = &[
+    KnownKey {
+        name: settings::ONBOARDED_KEY,
+        valid: Value::is_boolean,
+        default: Some(|| Value::Bool(false)),
+    },
+    KnownKey {
+        name: settings::SETTINGS_KEY,
+        valid: Value::is_object,
+        default: None,
+    },
+    KnownKey {
+        name: settings::LAST_VIEW_KEY,
+        valid: valid_view,
+        default: None,
+    },
+    KnownKey {
+        name: drafts::DRAFT_KEY,
+        valid: |v| !v.is_null(),
+        default: None,
+    },
+];
+
+/// Bring a raw store snapshot up to the current layout: run pending
+/// migrations, drop unknown or invalid keys, fill defaults and stamp the
+/// version. Returns whether anything changed.
+///
+/// A store from a newer build is left alone so a downgrade doesn't destroy
+/// settings the newer build still understands.
+pub(crate) fn normalize(config: &mut Map<String, Value>) -> bool {
+    let before = config.clone();
+
+    let version = config
+        .get(CONFIG_VERSION_KEY)
+        .and_then(Value::as_u64)
+        .unwrap_or(1);
+    if version > CONFIG_VERSION {
+        return false;
+    }
+    for migrate in MIGRATIONS.iter().skip(version.saturating_sub(1) as usize) {
+        migrate(config);
+    }
+
+    config.retain(|name, value| {
+        KNOWN_KEYS
+            .iter()
+            .any(|key| key.name == name && (key.valid)(value))
+    });
+    for key in KNOWN_KEYS {
+        if let Some(default) = key.default {
+            config.entry(key.name).or_insert_with(default);
+        }
+    }
+    config.insert(CONFIG_VERSION_KEY.into(), CONFIG_VERSION.into());
+
+    *config != before
+}
+
+// ---------------------------------------------------------------------------
+// Startup
+// ---------------------------------------------------------------------------
+
+/// Validate and migrate the settings store before anything reads it.
+pub fn migrate_store<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    let mut config: Map<String, Value> = store.entries().into_iter().collect();
+    if !normalize(&mut config) {
+        return Ok(());
+    }
+
+    store.clear();
+    for (key, value) in config {
+        store.set(key, value);
+    }
+    store.save().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: &str) -> Map<String, Value> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn migrates_unversioned_store_to_current() {
+        let mut store = config(
+            r#"{
+                "onboarded": true,
+                "startup_view": "watchlist",
+                "timezone_override": "Europe/Paris",
+                "last_view": "no-such-route",
+                "window_size": [800, 600]
+            }"#,
+        );
+
+        assert!(normalize(&mut store));
+        assert_eq!(
+            Value::Object(store),
+            serde_json::json!({
+                "config_version": CONFIG_VERSION,
+                "onboarded": true,
+                "settings": {
+                    "startup_view": "watchlist",
+                    "timezone_override": "Europe/Paris"
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn fills_defaults_on_empty_store() {
+        let mut store = Map::new();
+
+        assert!(normalize(&mut store));
+        assert_eq!(store["onboarded"], false);
+        assert_eq!(store[CONFIG_VERSION_KEY], CONFIG_VERSION);
+    }
+
+    #[test]
+    fn current_store_is_left_alone() {
+        let mut store = Map::new();
+        normalize(&mut store);
+
+        assert!(!normalize(&mut store));
+    }
+
+    #[test]
+    fn newer_store_is_not_downgraded() {
+        let mut store = config(r#"{ "config_version": 99, "future_key": 1 }"#);
+
+        assert!(!normalize(&mut store));
+        assert!(store.contains_key("future_key"));
+    }
+}
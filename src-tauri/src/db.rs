@@ -0,0 +1,561 @@
+use serde::Serialize;
+use sqlx::{Row, SqlitePool};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_sql::{DbInstances, DbPool, Migration, MigrationKind};
+
+/// Connection string the frontend passes to `Database.load`.
+pub(crate) const DB_URL: &str = "sqlite:stockadvisors.db";
+
+/// Trim and upper-case a symbol the way the trades table stores it.
+pub(crate) fn clean_symbol(symbol: &str) -> Result<String, String> {
+    let symbol = symbol.trim().to_uppercase();
+    if symbol.is_empty() {
+        return Err("Symbol is required".into());
+    }
+    Ok(symbol)
+}
+
+/// Clone the SQLite pool opened by the SQL plugin.
+///
+/// The frontend owns the connection lifecycle, so this fails until the
+/// webview has called `initializeDatabase()`.
+pub(crate) async fn pool<R: Runtime>(app: &AppHandle<R>) -> Result<SqlitePool, String> {
+    let instances = app.state::<DbInstances>();
+    let instances = instances.0.read().await;
+    instances
+        .get(DB_URL)
+        .map(|db| match db {
+            DbPool::Sqlite(pool) => pool.clone(),
+        })
+        .ok_or_else(|| "Database is not loaded yet".to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Migrations
+// ---------------------------------------------------------------------------
+
+/// Backend-owned tables. The SQL plugin applies these when the frontend
+/// loads the database; the frontend's own schema.sql covers everything else.
+pub(crate) fn migrations() -> Vec<Migration> {
+    vec![
+        Migration {
+            version: 1,
+            description: "create_symbol_notes",
+            sql: "CREATE TABLE IF NOT EXISTS symbol_notes (
+                symbol TEXT PRIMARY KEY,
+                note TEXT NOT NULL,
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+              );",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 2,
+            description: "create_tags",
+            sql: "CREATE TABLE IF NOT EXISTS tags (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE
+              );
+              CREATE TABLE IF NOT EXISTS position_tags (
+                tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+                symbol TEXT NOT NULL,
+                PRIMARY KEY (tag_id, symbol)
+              );
+              CREATE INDEX IF NOT EXISTS idx_position_tags_symbol ON position_tags(symbol);",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 3,
+            description: "create_price_targets",
+            sql: "CREATE TABLE IF NOT EXISTS price_targets (
+                symbol TEXT PRIMARY KEY,
+                target REAL NOT NULL,
+                rationale TEXT NOT NULL,
+                set_at TEXT NOT NULL DEFAULT (datetime('now'))
+              );",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 4,
+            description: "create_scheduled_jobs",
+            sql: "CREATE TABLE IF NOT EXISTS scheduled_jobs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                time_of_day TEXT NOT NULL,
+                days TEXT NOT NULL,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                last_fired TEXT
+              );",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 5,
+            description: "create_paper_orders",
+            sql: "CREATE TABLE IF NOT EXISTS paper_orders (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                symbol TEXT NOT NULL,
+                side TEXT NOT NULL,
+                quantity INTEGER NOT NULL,
+                order_type TEXT NOT NULL,
+                limit_price REAL,
+                stop_price REAL,
+                status TEXT NOT NULL DEFAULT 'open',
+                filled_quantity INTEGER NOT NULL DEFAULT 0,
+                triggered INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+              );
+              CREATE TABLE IF NOT EXISTS paper_fills (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                order_id INTEGER NOT NULL REFERENCES paper_orders(id),
+                symbol TEXT NOT NULL,
+                side TEXT NOT NULL,
+                quantity INTEGER NOT NULL,
+                price REAL NOT NULL,
+                filled_at TEXT NOT NULL DEFAULT (datetime('now'))
+              );
+              CREATE INDEX IF NOT EXISTS idx_paper_orders_status ON paper_orders(status);",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 6,
+            description: "create_journal_entries",
+            // No foreign key: `trades` belongs to the frontend's schema
+            sql: "CREATE TABLE IF NOT EXISTS journal_entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                trade_id INTEGER NOT NULL,
+                symbol TEXT NOT NULL,
+                text TEXT NOT NULL,
+                image_path TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+              );
+              CREATE INDEX IF NOT EXISTS idx_journal_entries_symbol ON journal_entries(symbol);",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 7,
+            description: "create_portfolio_snapshots",
+            sql: "CREATE TABLE IF NOT EXISTS portfolio_snapshots (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                taken_at TEXT NOT NULL DEFAULT (datetime('now')),
+                total_value REAL NOT NULL
+              );
+              CREATE TABLE IF NOT EXISTS portfolio_snapshot_holdings (
+                snapshot_id INTEGER NOT NULL REFERENCES portfolio_snapshots(id),
+                symbol TEXT NOT NULL,
+                quantity INTEGER NOT NULL,
+                value REAL NOT NULL,
+                PRIMARY KEY (snapshot_id, symbol)
+              );",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 8,
+            description: "create_alerts",
+            // `condition` is an `alerts::Condition` as JSON
+            sql: "CREATE TABLE IF NOT EXISTS alerts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                symbol TEXT NOT NULL,
+                condition TEXT NOT NULL,
+                last_met INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+              );",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 9,
+            description: "create_action_log",
+            // `undo` is a JSON list of `actions::Restore` steps, or NULL for
+            // actions that can't be undone
+            sql: "CREATE TABLE IF NOT EXISTS action_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                description TEXT NOT NULL,
+                undo TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+              );",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 10,
+            description: "create_symbol_meta",
+            // Instrument details a provider reports; NULL where it didn't
+            sql: "CREATE TABLE IF NOT EXISTS symbol_meta (
+                symbol TEXT PRIMARY KEY,
+                price_precision INTEGER,
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+              );",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 11,
+            description: "create_allocation_targets",
+            sql: "CREATE TABLE IF NOT EXISTS allocation_targets (
+                target_group TEXT NOT NULL CHECK (target_group IN ('symbol', 'sector')),
+                name TEXT NOT NULL,
+                target_pct REAL NOT NULL,
+                PRIMARY KEY (target_group, name)
+              );",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 12,
+            description: "add_alert_high_water",
+            // Running high for trailing-stop alerts, NULL for the rest
+            sql: "ALTER TABLE alerts ADD COLUMN high_water REAL;",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 13,
+            description: "create_alert_history",
+            // alert_id is not a foreign key so history outlives deleted alerts
+            sql: "CREATE TABLE IF NOT EXISTS alert_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                alert_id INTEGER NOT NULL,
+                symbol TEXT NOT NULL,
+                price REAL NOT NULL,
+                fired_at TEXT NOT NULL DEFAULT (datetime('now')),
+                message TEXT NOT NULL
+              );
+              CREATE INDEX IF NOT EXISTS idx_alert_history_fired_at ON alert_history (fired_at);",
+            kind: MigrationKind::Up,
+        },
+    ]
+}
+
+// ---------------------------------------------------------------------------
+// Trades
+// ---------------------------------------------------------------------------
+
+/// Read-only view of a `trades` row. The table itself is owned by the
+/// frontend's TradeMemory service; the backend never writes to it.
+#[derive(Clone, sqlx::FromRow)]
+pub(crate) struct TradeRow {
+    pub id: i64,
+    pub symbol: String,
+    pub action: String,
+    pub quantity: i64,
+    pub entry_price: f64,
+    pub entry_date: String,
+    pub exit_price: Option<f64>,
+    pub exit_date: Option<String>,
+    pub status: String,
+    pub pnl_dollars: Option<f64>,
+    pub pnl_percent: Option<f64>,
+}
+
+impl TradeRow {
+    pub fn is_open(&self) -> bool {
+        self.status == "open"
+    }
+
+    /// Mirrors `isLongAction` in the frontend TradingEngine.
+    pub fn is_long(&self) -> bool {
+        self.action == "BUY" || self.action == "STRONG_BUY"
+    }
+}
+
+pub(crate) async fn load_trades(pool: &SqlitePool) -> Result<Vec<TradeRow>, String> {
+    sqlx::query_as(
+        "SELECT id, symbol, action, quantity, entry_price, entry_date, exit_price, \
+         exit_date, status, pnl_dollars, pnl_percent \
+         FROM trades ORDER BY entry_date ASC",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Integrity check
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize)]
+pub struct IntegrityReport {
+    ok: bool,
+    issues: Vec<String>,
+    recommendation: Option<String>,
+}
+
+/// SQLite's own consistency checks. Both pragmas are read-only, so this
+/// runs alongside normal reads.
+pub(crate) async fn check_integrity(pool: &SqlitePool) -> Result<IntegrityReport, String> {
+    let mut issues = Vec::new();
+
+    let integrity: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    issues.extend(integrity.into_iter().filter(|line| line != "ok"));
+
+    let fk_rows = sqlx::query("PRAGMA foreign_key_check")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    for row in fk_rows {
+        let table: String = row.try_get(0).unwrap_or_default();
+        let rowid: Option<i64> = row.try_get(1).unwrap_or_default();
+        let parent: String = row.try_get(2).unwrap_or_default();
+        issues.push(match rowid {
+            Some(rowid) => format!("{} row {} references missing {} row", table, rowid, parent),
+            None => format!("{} references missing {} row", table, parent),
+        });
+    }
+
+    let ok = issues.is_empty();
+    Ok(IntegrityReport {
+        ok,
+        issues,
+        recommendation: (!ok).then(|| {
+            "The database is damaged. Export or back up your data and recover it \
+             before making further changes."
+                .to_string()
+        }),
+    })
+}
+
+#[tauri::command]
+pub async fn integrity_check(app: AppHandle) -> Result<IntegrityReport, String> {
+    logged!("integrity_check", [], async {
+        let pool = pool(&app).await?;
+        check_integrity(&pool).await
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Schema
+// ---------------------------------------------------------------------------
+
+/// Migration bookkeeping kept by the SQL plugin.
+const MIGRATIONS_TABLE: &str = "_sqlx_migrations";
+
+#[derive(Serialize)]
+pub struct DatabaseSchema {
+    /// Latest backend migration applied, if the plugin has run any.
+    schema_version: Option<i64>,
+    tables: Vec<TableSchema>,
+}
+
+#[derive(Serialize)]
+pub struct TableSchema {
+    table: String,
+    columns: Vec<ColumnSchema>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct ColumnSchema {
+    name: String,
+    #[serde(rename = "type")]
+    #[sqlx(rename = "type")]
+    column_type: String,
+    nullable: bool,
+    /// Position in the primary key, starting at 1, or 0 if not part of it.
+    pk: i64,
+}
+
+/// Every user table and its columns, leaving out SQLite's and the
+/// migration runner's own tables.
+pub(crate) async fn load_schema(pool: &SqlitePool) -> Result<DatabaseSchema, String> {
+    let names: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' \
+         ORDER BY name",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut schema_version = None;
+    let mut tables = Vec::new();
+    for table in names {
+        if table == MIGRATIONS_TABLE {
+            schema_version = sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations")
+                .fetch_one(pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            continue;
+        }
+        let columns = sqlx::query_as(
+            "SELECT name, type, \"notnull\" = 0 AS nullable, pk FROM pragma_table_info(?) \
+             ORDER BY cid",
+        )
+        .bind(&table)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+        tables.push(TableSchema { table, columns });
+    }
+
+    Ok(DatabaseSchema {
+        schema_version,
+        tables,
+    })
+}
+
+/// The current data model, for power users querying the database directly.
+#[tauri::command]
+pub async fn get_schema(app: AppHandle) -> Result<DatabaseSchema, String> {
+    logged!("get_schema", [], async {
+        let pool = pool(&app).await?;
+        load_schema(&pool).await
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Test fixtures
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::SqlitePool;
+
+    use super::{migrations, TradeRow};
+
+    /// The frontend's `trades` DDL from Database.ts.
+    const TRADES_SQL: &str = "CREATE TABLE trades (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        symbol TEXT NOT NULL,
+        action TEXT NOT NULL,
+        quantity INTEGER NOT NULL,
+        entry_price REAL NOT NULL,
+        entry_date TEXT NOT NULL,
+        exit_price REAL,
+        exit_date TEXT,
+        stop_loss REAL,
+        take_profit REAL,
+        status TEXT DEFAULT 'open',
+        pnl_dollars REAL,
+        pnl_percent REAL,
+        holding_days INTEGER,
+        recommended_by TEXT NOT NULL,
+        confidence INTEGER,
+        pipeline_id TEXT,
+        notes TEXT,
+        created_at TEXT DEFAULT (datetime('now'))
+    );";
+
+    /// Fresh in-memory database with the frontend schema and every backend
+    /// migration applied. One connection, since each in-memory connection
+    /// is its own database.
+    pub(crate) async fn pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("open in-memory database");
+
+        sqlx::raw_sql(TRADES_SQL)
+            .execute(&pool)
+            .await
+            .expect("create trades");
+        for migration in migrations() {
+            sqlx::raw_sql(migration.sql)
+                .execute(&pool)
+                .await
+                .expect(migration.description);
+        }
+        pool
+    }
+
+    /// Open long trade, for tests that need rows in `trades`.
+    pub(crate) async fn insert_open(pool: &SqlitePool, symbol: &str, quantity: i64, price: f64) {
+        sqlx::query(
+            "INSERT INTO trades (symbol, action, quantity, entry_price, entry_date, recommended_by) \
+             VALUES (?, 'BUY', ?, ?, '2024-01-02T14:30:00Z', 'test')",
+        )
+        .bind(symbol)
+        .bind(quantity)
+        .bind(price)
+        .execute(pool)
+        .await
+        .expect("insert trade");
+    }
+
+    /// Closed long trade, for the pure ledger functions.
+    pub(crate) fn closed(
+        id: i64,
+        symbol: &str,
+        quantity: i64,
+        entry_date: &str,
+        exit_date: &str,
+        pnl_dollars: f64,
+    ) -> TradeRow {
+        TradeRow {
+            id,
+            symbol: symbol.into(),
+            action: "BUY".into(),
+            quantity,
+            entry_price: 100.0,
+            entry_date: entry_date.into(),
+            exit_price: Some(100.0 + pnl_dollars / quantity as f64),
+            exit_date: Some(exit_date.into()),
+            status: "closed".into(),
+            pnl_dollars: Some(pnl_dollars),
+            pnl_percent: Some(pnl_dollars / (quantity as f64 * 100.0) * 100.0),
+        }
+    }
+
+    /// Open long trade as a row, for the pure ledger functions.
+    pub(crate) fn open(id: i64, symbol: &str, quantity: i64, entry_date: &str) -> TradeRow {
+        TradeRow {
+            id,
+            symbol: symbol.into(),
+            action: "BUY".into(),
+            quantity,
+            entry_price: 100.0,
+            entry_date: entry_date.into(),
+            exit_price: None,
+            exit_date: None,
+            status: "open".into(),
+            pnl_dollars: None,
+            pnl_percent: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn integrity_flags_dangling_foreign_keys() {
+        let pool = testing::pool().await;
+        assert!(check_integrity(&pool).await.unwrap().ok);
+
+        // a row written while enforcement was off, as by an older build
+        sqlx::raw_sql(
+            "PRAGMA foreign_keys = OFF; \
+             INSERT INTO position_tags (tag_id, symbol) VALUES (42, 'AAPL');",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let report = check_integrity(&pool).await.unwrap();
+        assert!(!report.ok);
+        assert!(report.issues[0].contains("position_tags"));
+        assert!(report.recommendation.is_some());
+    }
+
+    #[tokio::test]
+    async fn schema_lists_user_tables_and_columns() {
+        let pool = testing::pool().await;
+        let schema = load_schema(&pool).await.unwrap();
+
+        let names: Vec<&str> = schema.tables.iter().map(|t| t.table.as_str()).collect();
+        assert!(names.contains(&"trades"));
+        assert!(names.contains(&"symbol_notes"));
+        assert!(!names.iter().any(|n| n.starts_with("sqlite_")));
+
+        let notes = schema
+            .tables
+            .iter()
+            .find(|t| t.table == "symbol_notes")
+            .unwrap();
+        let symbol = &notes.columns[0];
+        assert_eq!(
+            (symbol.name.as_str(), symbol.column_type.as_str()),
+            ("symbol", "TEXT")
+        );
+        assert_eq!(symbol.pk, 1);
+        assert!(!notes.columns[1].nullable);
+        // The test database has no migration runner
+        assert_eq!(schema.schema_version, None);
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+
+use crate::actions::{self, Restore};
+use crate::db::{self, TradeRow};
+
+/// Rows entered this close together by a re-run import are treated as the
+/// same trade. Anything further apart is kept, however alike.
+const WINDOW_SECS: i64 = 60;
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateTrade {
+    id: i64,
+    /// The identical row to keep.
+    kept_id: i64,
+    symbol: String,
+    action: String,
+    quantity: i64,
+    entry_price: f64,
+    entry_date: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DedupeReport {
+    dry_run: bool,
+    /// Rows to delete. Nothing here has been deleted yet.
+    duplicates: Vec<DuplicateTrade>,
+}
+
+/// Entry time of a trade, or `None` if the stored date can't be read, in
+/// which case the row is never treated as a duplicate.
+fn entered_at(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(date)
+        .map(|t| t.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+            Some(day.and_hms_opt(0, 0, 0)?.and_utc())
+        })
+}
+
+/// Rows matching an earlier row on symbol, side, quantity, prices and exit,
+/// entered within [`WINDOW_SECS`] of it. The earliest row of each set is
+/// the one to keep.
+pub(crate) fn find_duplicates(trades: &[TradeRow]) -> Vec<DuplicateTrade> {
+    type Key<'a> = (&'a str, &'a str, i64, u64, Option<u64>, Option<&'a str>);
+    let mut groups: BTreeMap<Key, Vec<(DateTime<Utc>, &TradeRow)>> = BTreeMap::new();
+    for trade in trades {
+        let Some(at) = entered_at(&trade.entry_date) else {
+            continue;
+        };
+        let key = (
+            trade.symbol.as_str(),
+            trade.action.as_str(),
+            trade.quantity,
+            trade.entry_price.to_bits(),
+            trade.exit_price.map(f64::to_bits),
+            trade.exit_date.as_deref(),
+        );
+        groups.entry(key).or_default().push((at, trade));
+    }
+
+    let mut duplicates = Vec::new();
+    for rows in groups.values_mut() {
+        rows.sort_by_key(|(at, trade)| (*at, trade.id));
+        let mut kept = rows[0];
+        for &(at, trade) in &rows[1..] {
+            // Measured from the kept row so a chain of rows can't stretch
+            // the window
+            if (at - kept.0).num_seconds() > WINDOW_SECS {
+                kept = (at, trade);
+                continue;
+            }
+            duplicates.push(DuplicateTrade {
+                id: trade.id,
+                kept_id: kept.1.id,
+                symbol: trade.symbol.clone(),
+                action: trade.action.clone(),
+                quantity: trade.quantity,
+                entry_price: trade.entry_price,
+                entry_date: trade.entry_date.clone(),
+            });
+        }
+    }
+    duplicates.sort_by_key(|r| r.id);
+    duplicates
+}
+
+/// Find duplicate trades and, unless `dry_run`, move journal entries on a
+/// duplicate to the row being kept, in one logged transaction so it can be
+/// undone. Tags are per symbol, which duplicates share, so they need no
+/// moving. The trades themselves are left alone: the table belongs to the
+/// frontend's TradeMemory, and deleting the reported rows is up to it.
+pub(crate) async fn dedupe(pool: &SqlitePool, dry_run: bool) -> Result<DedupeReport, String> {
+    let duplicates = find_duplicates(&db::load_trades(pool).await?);
+    if dry_run || duplicates.is_empty() {
+        return Ok(DedupeReport {
+            dry_run,
+            duplicates,
+        });
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let mut journal_ids = Vec::new();
+    for row in &duplicates {
+        let on_row: Vec<i64> =
+            sqlx::query_scalar("SELECT id FROM journal_entries WHERE trade_id = ?")
+                .bind(row.id)
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        journal_ids.extend(on_row.into_iter().map(serde_json::Value::from));
+    }
+    if !journal_ids.is_empty() {
+        let journal = Restore::capture(&mut tx, "journal_entries", journal_ids).await?;
+        for row in &duplicates {
+            sqlx::query("UPDATE journal_entries SET trade_id = ? WHERE trade_id = ?")
+                .bind(row.kept_id)
+                .bind(row.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        let description = format!(
+            "Moved journal entries off {} duplicate trades",
+            duplicates.len()
+        );
+        actions::record(&mut tx, &description, Some(vec![journal])).await?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(DedupeReport {
+        dry_run,
+        duplicates,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Find duplicate trade rows left by a re-run import. Run with `dry_run`
+/// first to review them; the real run re-points their journal entries but
+/// deletes nothing. Removing the reported ids is a TradeMemory delete on
+/// the frontend's side.
+#[tauri::command]
+pub async fn dedupe_trades(app: AppHandle, dry_run: bool) -> Result<DedupeReport, String> {
+    logged!("dedupe_trades", [dry_run], async {
+        let pool = db::pool(&app).await?;
+        dedupe(&pool, dry_run).await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing;
+
+    async fn insert(pool: &SqlitePool, quantity: i64, price: f64, entry_date: &str) {
+        sqlx::query(
+            "INSERT INTO trades (symbol, action, quantity, entry_price, entry_date, recommended_by) \
+             VALUES ('AAPL', 'BUY', ?, ?, ?, 'import')",
+        )
+        .bind(quantity)
+        .bind(price)
+        .bind(entry_date)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn seed(pool: &SqlitePool) {
+        insert(pool, 10, 150.0, "2024-01-02T14:30:00Z").await;
+        // genuine duplicates: same instant, and re-imported 30s later
+        insert(pool, 10, 150.0, "2024-01-02T14:30:00Z").await;
+        insert(pool, 10, 150.0, "2024-01-02T14:30:30.000Z").await;
+        // near-duplicates that are separate trades
+        insert(pool, 10, 150.0, "2024-01-02T14:32:00Z").await;
+        insert(pool, 10, 150.5, "2024-01-02T14:30:00Z").await;
+        insert(pool, 12, 150.0, "2024-01-02T14:30:00Z").await;
+    }
+
+    async fn trade_ids(pool: &SqlitePool) -> Vec<i64> {
+        sqlx::query_scalar("SELECT id FROM trades ORDER BY id")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reports_duplicates_and_moves_their_journal_entries() {
+        let pool = testing::pool().await;
+        seed(&pool).await;
+        sqlx::query("INSERT INTO journal_entries (trade_id, symbol, text) VALUES (2, 'AAPL', 'x')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let report = dedupe(&pool, false).await.unwrap();
+        let duplicates: Vec<(i64, i64)> = report
+            .duplicates
+            .iter()
+            .map(|r| (r.id, r.kept_id))
+            .collect();
+        assert_eq!(duplicates, [(2, 1), (3, 1)]);
+
+        // Left for TradeMemory to delete
+        assert_eq!(trade_ids(&pool).await, [1, 2, 3, 4, 5, 6]);
+        let journaled: i64 = sqlx::query_scalar("SELECT trade_id FROM journal_entries")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(journaled, 1);
+
+        crate::actions::undo_last(&pool).await.unwrap();
+        let journaled: i64 = sqlx::query_scalar("SELECT trade_id FROM journal_entries")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(journaled, 2);
+        assert_eq!(dedupe(&pool, true).await.unwrap().duplicates.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn dry_run_leaves_journal_entries_alone() {
+        let pool = testing::pool().await;
+        seed(&pool).await;
+        sqlx::query("INSERT INTO journal_entries (trade_id, symbol, text) VALUES (2, 'AAPL', 'x')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(dedupe(&pool, true).await.unwrap().duplicates.len(), 2);
+        let journaled: i64 = sqlx::query_scalar("SELECT trade_id FROM journal_entries")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(journaled, 2);
+        assert_eq!(trade_ids(&pool).await, [1, 2, 3, 4, 5, 6]);
+    }
+}
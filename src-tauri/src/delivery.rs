@@ -0,0 +1,609 @@
+//! Where a fired alert goes besides the in-app `alert-triggered` event: the
+//! OS notification and, once a URL is set, a webhook. Delivery is
+//! best-effort; a channel that fails never stops the ones after it.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+use crate::alerts::{self, Comparison, Condition, TriggeredAlert};
+use crate::settings::{self, SettingsPatch};
+use crate::{i18n, notifications};
+
+/// Long enough for a push relay on a slow link, short enough that a dead
+/// endpoint doesn't hold up the alerts queued behind it.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A fired alert as every channel receives it.
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertMessage {
+    #[serde(flatten)]
+    pub(crate) alert: TriggeredAlert,
+    pub(crate) title: String,
+    pub(crate) body: String,
+    pub(crate) fired_at: String,
+}
+
+impl AlertMessage {
+    pub(crate) fn new(app: &AppHandle, alert: TriggeredAlert) -> Self {
+        let price = format!("{:.2}", alert.price);
+        let symbol = [("symbol", alert.symbol.as_str())];
+        Self {
+            title: i18n::user_text(app, "notify.alert_triggered", &symbol),
+            body: i18n::user_text(
+                app,
+                "notify.alert_price",
+                &[("symbol", &alert.symbol), ("price", &price)],
+            ),
+            fired_at: Utc::now().to_rfc3339(),
+            alert,
+        }
+    }
+}
+
+pub(crate) type Delivery<'a> = Pin<Box<dyn Future<Output = Result<(), String>> + Send + 'a>>;
+
+/// Somewhere a fired alert can be sent.
+pub(crate) trait AlertChannel: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn deliver<'a>(&'a self, message: &'a AlertMessage) -> Delivery<'a>;
+}
+
+/// The OS notification, under the same rate limit as every other one.
+pub(crate) struct NotificationChannel(AppHandle);
+
+impl AlertChannel for NotificationChannel {
+    fn name(&self) -> &'static str {
+        "notification"
+    }
+
+    fn deliver<'a>(&'a self, message: &'a AlertMessage) -> Delivery<'a> {
+        Box::pin(async move {
+            let icon = direction(&message.alert.condition).and_then(|d| icon_path(&self.0, d));
+            notifications::show_limited_with_icon(&self.0, &message.title, &message.body, icon)
+                .map(|_| ())
+        })
+    }
+}
+
+/// The bundled arrow for `direction`, or `None` if it can't be found, in
+/// which case the notification keeps the app icon.
+fn icon_path(app: &AppHandle, direction: Direction) -> Option<String> {
+    let file = match direction {
+        Direction::Up => "icons/alert-up.png",
+        Direction::Down => "icons/alert-down.png",
+    };
+    let path = app.path().resolve(file, BaseDirectory::Resource).ok()?;
+    path.exists().then(|| path.to_string_lossy().into_owned())
+}
+
+/// Only https is accepted: a webhook URL usually carries a secret token, and
+/// the phone push services this is for refuse plain http anyway.
+pub(crate) fn validate_webhook_url(url: &str) -> Result<reqwest::Url, String> {
+    let parsed =
+        reqwest::Url::parse(url.trim()).map_err(|e| format!("Invalid webhook URL: {}", e))?;
+    if parsed.scheme() != "https" {
+        return Err("Webhook URL must start with https://".into());
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err("Webhook URL has no host".into());
+    }
+    Ok(parsed)
+}
+
+fn client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Report a request error without its URL, which may embed a token.
+fn request_error(e: reqwest::Error) -> String {
+    format!("Webhook delivery failed: {}", e.without_url())
+}
+
+/// POSTs each alert as JSON to a user-configured URL.
+pub(crate) struct WebhookChannel {
+    url: reqwest::Url,
+    client: reqwest::Client,
+}
+
+impl WebhookChannel {
+    pub(crate) fn new(url: &str) -> Result<Self, String> {
+        Ok(Self {
+            url: validate_webhook_url(url)?,
+            client: client()?,
+        })
+    }
+
+    /// The JSON body sent for `message`.
+    pub(crate) fn payload(message: &AlertMessage) -> Value {
+        json!({ "event": "alert_triggered", "alert": message })
+    }
+}
+
+impl AlertChannel for WebhookChannel {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn deliver<'a>(&'a self, message: &'a AlertMessage) -> Delivery<'a> {
+        Box::pin(async move {
+            self.client
+                .post(self.url.clone())
+                .json(&Self::payload(message))
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(request_error)?;
+            Ok(())
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Discord
+// ---------------------------------------------------------------------------
+
+/// Payload formats for the webhook. `auto` picks `discord` for Discord
+/// webhook URLs and plain `json` for everything else.
+pub(crate) const WEBHOOK_FORMATS: &[&str] = &["auto", "json", "discord"];
+
+/// Retries after a 429 before the alert is given up on.
+const DISCORD_RETRIES: u32 = 2;
+/// Longest `retry_after` worth waiting for; anything longer means the
+/// webhook is limited well past this alert being useful.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Up,
+    Down,
+}
+
+/// Which way the price moved to meet `condition`. Compound conditions have
+/// no single direction.
+fn direction(condition: &Condition) -> Option<Direction> {
+    match condition {
+        Condition::Leaf {
+            op: Comparison::Above,
+            ..
+        } => Some(Direction::Up),
+        Condition::Leaf {
+            op: Comparison::Below,
+            ..
+        }
+        | Condition::TrailingStop { .. } => Some(Direction::Down),
+        Condition::AllOf { .. } | Condition::AnyOf { .. } => None,
+    }
+}
+
+const COLOR_UP: u32 = 0x2E_CC_71;
+const COLOR_DOWN: u32 = 0xE7_4C_3C;
+const COLOR_NEUTRAL: u32 = 0x58_65_F2;
+
+pub(crate) fn is_discord_url(url: &reqwest::Url) -> bool {
+    let host = url.host_str().unwrap_or_default();
+    let discord = ["discord.com", "discordapp.com"]
+        .iter()
+        .any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)));
+    discord && url.path().starts_with("/api/webhooks/")
+}
+
+/// Embed colour and threshold text for a condition: green for above, red
+/// for below, and a count for groups.
+fn describe(condition: &Condition) -> (u32, String) {
+    let color = match direction(condition) {
+        Some(Direction::Up) => COLOR_UP,
+        Some(Direction::Down) => COLOR_DOWN,
+        None => COLOR_NEUTRAL,
+    };
+    (color, alerts::summary(condition))
+}
+
+/// How long Discord asked us to wait after a 429: `retry_after` in the
+/// JSON body, else the `Retry-After` header, both in seconds. `None` when
+/// neither can be read or the wait is over [`MAX_RETRY_AFTER`].
+pub(crate) fn retry_delay(header: Option<&str>, body: &str) -> Option<Duration> {
+    let from_body = serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|body| body.get("retry_after")?.as_f64());
+    let secs = from_body.or_else(|| header?.trim().parse().ok())?;
+    let delay = Duration::try_from_secs_f64(secs).ok()?;
+    (delay <= MAX_RETRY_AFTER).then_some(delay)
+}
+
+/// Posts each alert as a Discord embed, waiting out rate limits.
+pub(crate) struct DiscordWebhookChannel {
+    url: reqwest::Url,
+    client: reqwest::Client,
+}
+
+impl DiscordWebhookChannel {
+    pub(crate) fn new(url: &str) -> Result<Self, String> {
+        Ok(Self {
+            url: validate_webhook_url(url)?,
+            client: client()?,
+        })
+    }
+
+    /// The embed body sent for `message`.
+    pub(crate) fn payload(message: &AlertMessage) -> Value {
+        let alert = &message.alert;
+        let (color, threshold) = describe(&alert.condition);
+        json!({
+            "embeds": [{
+                "title": message.title,
+                "description": message.body,
+                "color": color,
+                "fields": [
+                    { "name": "Symbol", "value": alert.symbol, "inline": true },
+                    { "name": "Price", "value": format!("{:.2}", alert.price), "inline": true },
+                    { "name": "Threshold", "value": threshold, "inline": true },
+                ],
+                "timestamp": message.fired_at,
+            }]
+        })
+    }
+}
+
+impl AlertChannel for DiscordWebhookChannel {
+    fn name(&self) -> &'static str {
+        "discord"
+    }
+
+    fn deliver<'a>(&'a self, message: &'a AlertMessage) -> Delivery<'a> {
+        Box::pin(async move {
+            let payload = Self::payload(message);
+            let mut retries = 0;
+            loop {
+                let response = self
+                    .client
+                    .post(self.url.clone())
+                    .json(&payload)
+                    .send()
+                    .await
+                    .map_err(request_error)?;
+                if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    response.error_for_status().map_err(request_error)?;
+                    return Ok(());
+                }
+
+                let header = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
+                let body = response.text().await.unwrap_or_default();
+                let delay = retry_delay(header.as_deref(), &body)
+                    .filter(|_| retries < DISCORD_RETRIES)
+                    .ok_or("Discord is rate limiting the webhook")?;
+                retries += 1;
+                tokio::time::sleep(delay).await;
+            }
+        })
+    }
+}
+
+/// The channel for a webhook URL in the given format.
+fn webhook_channel(url: &str, format: &str) -> Result<Box<dyn AlertChannel>, String> {
+    let discord = match format {
+        "discord" => true,
+        "json" => false,
+        _ => is_discord_url(&validate_webhook_url(url)?),
+    };
+    Ok(if discord {
+        Box::new(DiscordWebhookChannel::new(url)?)
+    } else {
+        Box::new(WebhookChannel::new(url)?)
+    })
+}
+
+/// The OS notification first, then the webhook if one is set, so a slow or
+/// failing webhook never delays the notification.
+pub(crate) fn channels(app: &AppHandle) -> Vec<Box<dyn AlertChannel>> {
+    let mut channels: Vec<Box<dyn AlertChannel>> = vec![Box::new(NotificationChannel(app.clone()))];
+    let settings = settings::load_settings(app);
+    if let Some(url) = settings.alert_webhook_url {
+        match webhook_channel(&url, &settings.alert_webhook_format) {
+            Ok(webhook) => channels.push(webhook),
+            Err(e) => eprintln!("[delivery] Skipping the webhook: {}", e),
+        }
+    }
+    channels
+}
+
+/// Send every message through every channel in order. Failures are logged
+/// and returned as `channel: error`; they never stop the other deliveries.
+pub(crate) async fn deliver_all(
+    channels: &[Box<dyn AlertChannel>],
+    messages: &[AlertMessage],
+) -> Vec<String> {
+    let mut failures = Vec::new();
+    for message in messages {
+        for channel in channels {
+            if let Err(e) = channel.deliver(message).await {
+                eprintln!(
+                    "[delivery] {} failed for alert {}: {}",
+                    channel.name(),
+                    message.alert.id,
+                    e
+                );
+                failures.push(format!("{}: {}", channel.name(), e));
+            }
+        }
+    }
+    failures
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Mirror fired alerts to `url` alongside the OS notification, or stop
+/// with `None`. Only https URLs are accepted. `format` is one of
+/// [`WEBHOOK_FORMATS`] and is left as it was when omitted.
+#[tauri::command]
+pub fn set_alert_webhook(
+    app: AppHandle,
+    url: Option<String>,
+    format: Option<String>,
+) -> Result<(), String> {
+    // The URL is left out of the log since it usually carries a token
+    logged!("set_alert_webhook", [format], {
+        let patch = SettingsPatch {
+            alert_webhook_url: Some(url),
+            alert_webhook_format: format,
+            ..Default::default()
+        };
+        settings::update_settings(&app, patch).map(|_| ())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    fn message() -> AlertMessage {
+        let condition: Condition = serde_json::from_value(json!({
+            "type": "leaf", "metric": "price", "op": "below", "value": 180.0
+        }))
+        .unwrap();
+        AlertMessage {
+            alert: TriggeredAlert {
+                id: 7,
+                symbol: "AAPL".into(),
+                condition,
+                price: 179.5,
+            },
+            title: "Alert triggered: AAPL".into(),
+            body: "AAPL is at 179.50".into(),
+            fired_at: "2024-03-01T14:30:00+00:00".into(),
+        }
+    }
+
+    /// Stands in for the OS notification, which needs a running app.
+    struct Recorder(Arc<Mutex<Vec<i64>>>);
+
+    impl AlertChannel for Recorder {
+        fn name(&self) -> &'static str {
+            "recorder"
+        }
+
+        fn deliver<'a>(&'a self, message: &'a AlertMessage) -> Delivery<'a> {
+            self.0.lock().unwrap().push(message.alert.id);
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    /// A webhook on plain http, which only a local test server can offer.
+    fn local_webhook(url: &str) -> WebhookChannel {
+        WebhookChannel {
+            url: reqwest::Url::parse(url).unwrap(),
+            client: client().unwrap(),
+        }
+    }
+
+    /// Answer one request per entry of `responses`, each a status line and
+    /// body, and hand back the request bodies.
+    fn serve(
+        responses: &'static [(&'static str, &'static str)],
+    ) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut bodies = Vec::new();
+            for (status, response) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end().to_ascii_lowercase();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(value) = line.strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                bodies.push(String::from_utf8(body).unwrap());
+                let reply = format!(
+                    "HTTP/1.1 {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    response.len(),
+                    response
+                );
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+            bodies
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn direction_follows_the_condition() {
+        let condition = |value| serde_json::from_value::<Condition>(value).unwrap();
+        let above = condition(json!({"type": "leaf", "metric": "rsi", "op": "above", "value": 70}));
+        let trailing = condition(json!({"type": "trailing_stop", "trail_pct": 5}));
+        let group = condition(json!({"type": "any_of", "conditions": []}));
+
+        assert_eq!(direction(&above), Some(Direction::Up));
+        assert_eq!(direction(&message().alert.condition), Some(Direction::Down));
+        assert_eq!(direction(&trailing), Some(Direction::Down));
+        assert_eq!(direction(&group), None);
+    }
+
+    #[test]
+    fn webhook_urls_must_be_https() {
+        assert!(validate_webhook_url(" https://ntfy.sh/my-topic ").is_ok());
+        assert!(validate_webhook_url("http://ntfy.sh/my-topic")
+            .unwrap_err()
+            .contains("https"));
+        assert!(validate_webhook_url("ntfy.sh/my-topic").is_err());
+        assert!(validate_webhook_url("").is_err());
+    }
+
+    #[test]
+    fn webhook_payload_carries_the_alert() {
+        let payload = WebhookChannel::payload(&message());
+        assert_eq!(payload["event"], "alert_triggered");
+        let alert = &payload["alert"];
+        assert_eq!(alert["id"], 7);
+        assert_eq!(alert["symbol"], "AAPL");
+        assert_eq!(alert["price"], 179.5);
+        assert_eq!(alert["condition"]["op"], "below");
+        assert_eq!(alert["title"], "Alert triggered: AAPL");
+        assert_eq!(alert["body"], "AAPL is at 179.50");
+        assert_eq!(alert["fired_at"], "2024-03-01T14:30:00+00:00");
+    }
+
+    #[tokio::test]
+    async fn both_channels_receive_a_fired_alert() {
+        let (url, server) = serve(&[("200 OK", "")]);
+        let notified = Arc::default();
+        let channels: Vec<Box<dyn AlertChannel>> = vec![
+            Box::new(Recorder(Arc::clone(&notified))),
+            Box::new(local_webhook(&url)),
+        ];
+
+        let failures = deliver_all(&channels, &[message()]).await;
+        assert!(failures.is_empty(), "{:?}", failures);
+        assert_eq!(*notified.lock().unwrap(), [7]);
+        let received: Value = serde_json::from_str(&server.join().unwrap()[0]).unwrap();
+        assert_eq!(received, WebhookChannel::payload(&message()));
+    }
+
+    #[tokio::test]
+    async fn a_failed_webhook_doesnt_stop_the_other_channels() {
+        // Nothing listens on a port once its listener is dropped
+        let closed = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", closed.local_addr().unwrap());
+        drop(closed);
+        let notified = Arc::default();
+        let channels: Vec<Box<dyn AlertChannel>> = vec![
+            Box::new(local_webhook(&url)),
+            Box::new(Recorder(Arc::clone(&notified))),
+        ];
+
+        let failures = deliver_all(&channels, &[message()]).await;
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].starts_with("webhook: "));
+        assert!(!failures[0].contains("127.0.0.1"));
+        assert_eq!(*notified.lock().unwrap(), [7]);
+    }
+
+    #[test]
+    fn discord_urls_are_detected() {
+        let detected = |url: &str| is_discord_url(&validate_webhook_url(url).unwrap());
+        assert!(detected("https://discord.com/api/webhooks/1/abc"));
+        assert!(detected("https://canary.discordapp.com/api/webhooks/1/abc"));
+        assert!(!detected("https://discord.com/channels/1/2"));
+        assert!(!detected("https://notdiscord.com/api/webhooks/1/abc"));
+        assert!(!detected("https://ntfy.sh/my-topic"));
+    }
+
+    #[test]
+    fn discord_embed_has_fields_and_direction_colour() {
+        let payload = DiscordWebhookChannel::payload(&message());
+        let embeds = payload["embeds"].as_array().unwrap();
+        assert_eq!(embeds.len(), 1);
+        let embed = &embeds[0];
+        assert_eq!(embed["title"], "Alert triggered: AAPL");
+        assert_eq!(embed["description"], "AAPL is at 179.50");
+        assert_eq!(embed["color"], COLOR_DOWN);
+        assert_eq!(embed["timestamp"], "2024-03-01T14:30:00+00:00");
+        let fields: Vec<(&str, &str)> = embed["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| (f["name"].as_str().unwrap(), f["value"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("Symbol", "AAPL"),
+                ("Price", "179.50"),
+                ("Threshold", "Price below 180")
+            ]
+        );
+
+        let mut rising = message();
+        rising.alert.condition = serde_json::from_value(json!({
+            "type": "leaf", "metric": "rsi", "op": "above", "value": 70.0
+        }))
+        .unwrap();
+        let embed = &DiscordWebhookChannel::payload(&rising)["embeds"][0];
+        assert_eq!(embed["color"], COLOR_UP);
+        assert_eq!(embed["fields"][2]["value"], "RSI above 70");
+    }
+
+    #[test]
+    fn retry_delay_prefers_the_body_and_caps_the_wait() {
+        let body =
+            r#"{"message": "You are being rate limited.", "retry_after": 1.5, "global": false}"#;
+        assert_eq!(
+            retry_delay(Some("3"), body),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(retry_delay(Some(" 2 "), ""), Some(Duration::from_secs(2)));
+        assert_eq!(
+            retry_delay(Some("0.25"), "not json"),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(retry_delay(Some("60"), ""), None);
+        assert_eq!(retry_delay(None, r#"{"retry_after": -1}"#), None);
+        assert_eq!(retry_delay(Some("soon"), ""), None);
+        assert_eq!(retry_delay(None, ""), None);
+    }
+
+    #[tokio::test]
+    async fn discord_waits_out_a_rate_limit() {
+        let (url, server) = serve(&[
+            ("429 Too Many Requests", r#"{"retry_after": 0.05}"#),
+            ("204 No Content", ""),
+        ]);
+        let discord = DiscordWebhookChannel {
+            url: reqwest::Url::parse(&url).unwrap(),
+            client: client().unwrap(),
+        };
+
+        discord.deliver(&message()).await.unwrap();
+        let bodies = server.join().unwrap();
+        assert_eq!(bodies.len(), 2);
+        assert_eq!(bodies[0], bodies[1]);
+    }
+}
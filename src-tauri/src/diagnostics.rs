@@ -0,0 +1,252 @@
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::{db, drafts, export, ipc, metrics, STORE_FILE};
+
+/// Store keys that hold user content rather than configuration.
+const EXCLUDED_KEYS: &[&str] = &[drafts::DRAFT_KEY];
+
+/// Any store key containing one of these is treated as a credential.
+/// Webhook URLs carry their token in the path.
+const SECRET_MARKERS: &[&str] = &[
+    "key",
+    "token",
+    "secret",
+    "password",
+    "credential",
+    "webhook",
+];
+
+const REDACTED: &str = "[redacted]";
+
+#[derive(Serialize)]
+struct BuildInfo {
+    version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+    debug: bool,
+}
+
+#[derive(Serialize)]
+struct DatabaseInfo {
+    /// Highest backend migration applied, if the database is loaded.
+    schema_version: Option<i64>,
+    /// Combined size of the database file and its WAL/SHM sidecars.
+    size_bytes: u64,
+}
+
+#[derive(Serialize)]
+pub struct DiskUsage {
+    /// Database file plus its WAL/SHM sidecars.
+    database_bytes: u64,
+    logs_bytes: u64,
+    cache_bytes: u64,
+    total_bytes: u64,
+}
+
+/// Strip credentials from a store value, at any depth.
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if SECRET_MARKERS.iter().any(|marker| key.contains(marker)) {
+                    *v = Value::String(REDACTED.into());
+                } else {
+                    redact(v);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+fn store_snapshot(app: &AppHandle) -> Result<Value, String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    let mut settings = Value::Object(
+        store
+            .entries()
+            .into_iter()
+            .filter(|(key, _)| !EXCLUDED_KEYS.contains(&key.as_str()))
+            .collect(),
+    );
+    redact(&mut settings);
+    Ok(settings)
+}
+
+/// Where the SQL plugin keeps the database: relative connection strings
+/// resolve inside the app config directory.
+pub(crate) fn db_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    let file = db::DB_URL.trim_start_matches("sqlite:");
+    Ok(dir.join(file))
+}
+
+fn database_size(path: &Path) -> u64 {
+    ["", "-wal", "-shm"]
+        .iter()
+        .filter_map(|suffix| {
+            let mut file = path.as_os_str().to_owned();
+            file.push(suffix);
+            std::fs::metadata(file).ok()
+        })
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// Total size of the files under `dir`. Anything missing or unreadable
+/// counts as zero.
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+            Ok(_) => entry.metadata().map(|meta| meta.len()).unwrap_or(0),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+async fn database_info(app: &AppHandle) -> Result<DatabaseInfo, String> {
+    let size_bytes = database_size(&db_path(app)?);
+
+    let schema_version = match db::pool(app).await {
+        Ok(pool) => sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations")
+            .fetch_one(&pool)
+            .await
+            .map_err(|e| e.to_string())?,
+        Err(_) => None,
+    };
+
+    Ok(DatabaseInfo {
+        schema_version,
+        size_bytes,
+    })
+}
+
+fn add_json<T: Serialize>(
+    zip: &mut ZipWriter<Cursor<Vec<u8>>>,
+    name: &str,
+    value: &T,
+) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(value).map_err(|e| e.to_string())?;
+    add_file(zip, name, &json)
+}
+
+fn add_file(zip: &mut ZipWriter<Cursor<Vec<u8>>>, name: &str, bytes: &[u8]) -> Result<(), String> {
+    zip.start_file(name, SimpleFileOptions::default())
+        .map_err(|e| e.to_string())?;
+    zip.write_all(bytes).map_err(|e| e.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Write a support bundle to `dest`. It never includes database contents
+/// or credentials: only build info, redacted settings, database metadata,
+/// recent command failures and command timings.
+#[tauri::command]
+pub async fn export_diagnostics(app: AppHandle, dest: String) -> Result<(), String> {
+    logged!("export_diagnostics", [dest], async {
+        let path = export::validate_destination(&dest, "zip")?;
+
+        let build = BuildInfo {
+            version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            debug: cfg!(debug_assertions),
+        };
+        let settings = store_snapshot(&app)?;
+        let database = database_info(&app).await?;
+        let mut log = ipc::recent_errors().join("\n");
+        log.push('\n');
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        add_json(&mut zip, "build.json", &build)?;
+        add_json(&mut zip, "settings.json", &settings)?;
+        add_json(&mut zip, "database.json", &database)?;
+        add_file(&mut zip, "recent-errors.log", log.as_bytes())?;
+        add_json(&mut zip, "command-metrics.json", &metrics::snapshot())?;
+        let bytes = zip.finish().map_err(|e| e.to_string())?.into_inner();
+
+        export::write_file(&app, path, &bytes)
+    })
+}
+
+/// How much disk the app is using, to help decide when to vacuum or prune.
+#[tauri::command]
+pub fn get_disk_usage(app: AppHandle) -> Result<DiskUsage, String> {
+    logged!("get_disk_usage", [], {
+        let database_bytes = database_size(&db_path(&app)?);
+        let logs_bytes = app
+            .path()
+            .app_log_dir()
+            .map(|dir| dir_size(&dir))
+            .unwrap_or(0);
+        let cache_bytes = app
+            .path()
+            .app_cache_dir()
+            .map(|dir| dir_size(&dir))
+            .unwrap_or(0);
+
+        Ok(DiskUsage {
+            database_bytes,
+            logs_bytes,
+            cache_bytes,
+            total_bytes: database_bytes + logs_bytes + cache_bytes,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_count_sidecars_and_nested_files() {
+        let dir = std::env::temp_dir().join(format!("sa-disk-usage-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("app.db"), [0u8; 100]).unwrap();
+        std::fs::write(dir.join("app.db-wal"), [0u8; 20]).unwrap();
+        std::fs::write(dir.join("nested/other.log"), [0u8; 5]).unwrap();
+
+        assert_eq!(database_size(&dir.join("app.db")), 120);
+        assert_eq!(dir_size(&dir), 125);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stored_webhook_urls_are_redacted() {
+        let mut store = serde_json::json!({
+            "settings": {
+                "alert_webhook_url": "https://discord.com/api/webhooks/1/abc",
+                "alert_webhook_format": "discord",
+                "theme": "dark"
+            }
+        });
+        redact(&mut store);
+        let settings = &store["settings"];
+        assert_eq!(settings["alert_webhook_url"], REDACTED);
+        assert_eq!(settings["theme"], "dark");
+        assert!(!store.to_string().contains("webhooks/1/abc"));
+    }
+
+    #[test]
+    fn missing_paths_are_zero() {
+        let missing = std::env::temp_dir().join("sa-disk-usage-missing");
+        assert_eq!(database_size(&missing), 0);
+        assert_eq!(dir_size(&missing), 0);
+    }
+}
@@ -0,0 +1,60 @@
+use tauri::{AppHandle, Emitter, Runtime};
+use tauri_plugin_store::StoreExt;
+
+use crate::STORE_FILE;
+
+pub(crate) const DRAFT_KEY: &str = "analysis_draft";
+
+/// Drafts only hold form parameters, so anything larger is almost certainly
+/// the frontend pushing results or chart data by mistake.
+const MAX_DRAFT_BYTES: usize = 64 * 1024;
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn save_draft(app: AppHandle, json: String) -> Result<(), String> {
+    logged!("save_draft", [], {
+        if json.len() > MAX_DRAFT_BYTES {
+            return Err(format!(
+                "Draft is too large ({} bytes, max {})",
+                json.len(),
+                MAX_DRAFT_BYTES
+            ));
+        }
+
+        let draft: serde_json::Value =
+            serde_json::from_str(&json).map_err(|e| format!("Draft is not valid JSON: {}", e))?;
+
+        let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+        store.set(DRAFT_KEY, draft);
+        store.save().map_err(|e| e.to_string())
+    })
+}
+
+#[tauri::command]
+pub fn clear_draft(app: AppHandle) -> Result<(), String> {
+    logged!("clear_draft", [], {
+        let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+        if store.delete(DRAFT_KEY) {
+            store.save().map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Startup restore
+// ---------------------------------------------------------------------------
+
+/// Emit `restore-draft` with the persisted draft, if there is one.
+pub fn emit_restore_draft<R: Runtime>(app: &AppHandle<R>) {
+    let Ok(store) = app.store(STORE_FILE) else {
+        return;
+    };
+
+    if let Some(draft) = store.get(DRAFT_KEY) {
+        let _ = app.emit("restore-draft", draft);
+    }
+}
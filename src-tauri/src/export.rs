@@ -0,0 +1,80 @@
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use tauri::AppHandle;
+use tauri_plugin_fs::{FsExt, OpenOptions};
+
+const PNG_DATA_URL_PREFIX: &str = "data:image/png;base64,";
+const PNG_MAGIC: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Validate a user-chosen output path: absolute, with the expected file
+/// extension, inside an existing directory.
+pub(crate) fn validate_destination(dest: &str, extension: &str) -> Result<PathBuf, String> {
+    let path = Path::new(dest);
+
+    if !path.is_absolute() {
+        return Err("Destination must be an absolute path".into());
+    }
+
+    let has_extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension));
+    if !has_extension {
+        return Err(format!("Destination must be a .{} file", extension));
+    }
+
+    match path.parent() {
+        Some(dir) if dir.is_dir() => Ok(path.to_path_buf()),
+        _ => Err("Destination directory does not exist".into()),
+    }
+}
+
+/// Write `bytes` to `path` through the fs plugin, replacing any existing file.
+pub(crate) fn write_file(app: &AppHandle, path: PathBuf, bytes: &[u8]) -> Result<(), String> {
+    let mut opts = OpenOptions::new();
+    opts.write(true).create(true).truncate(true);
+
+    let mut file = app.fs().open(path, opts).map_err(|e| e.to_string())?;
+    file.write_all(bytes).map_err(|e| e.to_string())
+}
+
+/// Read a user-chosen file through the fs plugin as UTF-8 text.
+pub(crate) fn read_file(app: &AppHandle, path: PathBuf) -> Result<String, String> {
+    let mut opts = OpenOptions::new();
+    opts.read(true);
+
+    let mut file = app.fs().open(path, opts).map_err(|e| e.to_string())?;
+    let mut text = String::new();
+    file.read_to_string(&mut text).map_err(|e| e.to_string())?;
+    Ok(text)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Save a chart canvas exported with `canvas.toDataURL("image/png")`.
+#[tauri::command]
+pub fn save_image(app: AppHandle, data_url: String, dest: String) -> Result<(), String> {
+    logged!("save_image", [dest], {
+        let encoded = data_url
+            .strip_prefix(PNG_DATA_URL_PREFIX)
+            .ok_or("Only PNG image data URLs can be saved")?;
+
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| format!("Image data is not valid base64: {}", e))?;
+        if !bytes.starts_with(PNG_MAGIC) {
+            return Err("Image data is not a PNG".into());
+        }
+
+        let path = validate_destination(&dest, "png")?;
+        write_file(&app, path, &bytes)
+    })
+}
@@ -0,0 +1,278 @@
+use tauri::AppHandle;
+
+use crate::{db, settings, symbols};
+
+/// Locales the formatter knows the conventions of.
+pub(crate) const LOCALES: &[&str] = &["en-US", "en-GB", "de-DE", "fr-FR", "es-ES", "ja-JP"];
+
+struct Conventions {
+    group: &'static str,
+    decimal: &'static str,
+    /// `1.234,50 €` rather than `€1,234.50`.
+    symbol_after: bool,
+    /// Space between a number and `%`.
+    percent_gap: bool,
+}
+
+fn conventions(locale: &str) -> Option<Conventions> {
+    let (group, decimal, symbol_after, percent_gap) = match locale {
+        "en-US" | "en-GB" | "ja-JP" => (",", ".", false, false),
+        "de-DE" | "es-ES" => (".", ",", true, true),
+        // Narrow no-break space, as French typography uses
+        "fr-FR" => ("\u{202f}", ",", true, true),
+        _ => return None,
+    };
+    Some(Conventions {
+        group,
+        decimal,
+        symbol_after,
+        percent_gap,
+    })
+}
+
+fn currency_symbol(code: &str) -> &str {
+    match code {
+        "USD" => "$",
+        "EUR" => "€",
+        "GBP" => "£",
+        "JPY" => "¥",
+        "CAD" => "CA$",
+        "AUD" => "A$",
+        _ => code,
+    }
+}
+
+/// Currencies without minor units.
+fn currency_decimals(code: &str) -> usize {
+    match code {
+        "JPY" | "KRW" => 0,
+        _ => 2,
+    }
+}
+
+/// Most places a share price is shown with; enough for crypto.
+pub(crate) const MAX_PRICE_DECIMALS: u32 = 8;
+
+/// Sub-dollar prices move in fractions of a cent, so they get four places;
+/// everything else is quoted to the cent.
+pub(crate) fn price_decimals(price: f64) -> usize {
+    if price.abs() < 1.0 {
+        4
+    } else {
+        2
+    }
+}
+
+/// Places for a share price: a fixed `precision` if known, else by
+/// magnitude.
+pub(crate) fn price_places(price: f64, precision: Option<u32>) -> usize {
+    precision.map_or_else(|| price_decimals(price), |places| places as usize)
+}
+
+fn grouped(value: f64, decimals: usize, c: &Conventions) -> String {
+    let fixed = format!("{:.*}", decimals, value.abs());
+    let (whole, fraction) = fixed.split_once('.').unwrap_or((&fixed, ""));
+
+    let mut out = String::new();
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            out.push_str(c.group);
+        }
+        out.push(digit);
+    }
+    if !fraction.is_empty() {
+        out.push_str(c.decimal);
+        out.push_str(fraction);
+    }
+    out
+}
+
+/// Format `value` as `currency`, a share `price` (currency with places from
+/// [`price_places`]), `percent` (already in percent, so 12.5 is 12.5%) or a
+/// plain `number`, following `locale`.
+pub(crate) fn format(
+    value: f64,
+    kind: &str,
+    currency: &str,
+    locale: &str,
+    price_precision: Option<u32>,
+) -> Result<String, String> {
+    if !value.is_finite() {
+        return Err("Value must be a finite number".into());
+    }
+    let c = conventions(locale).ok_or_else(|| {
+        format!(
+            "Unsupported locale '{}', expected one of: {}",
+            locale,
+            LOCALES.join(", ")
+        )
+    })?;
+
+    let decimals = match kind {
+        "currency" => currency_decimals(currency),
+        "price" => price_places(value, price_precision),
+        _ => 2,
+    };
+    // Don't print "-0.00" for values that round to zero
+    let rounds_to_zero = format!("{:.*}", decimals, value.abs())
+        .chars()
+        .all(|ch| ch == '0' || ch == '.');
+    let sign = if value < 0.0 && !rounds_to_zero {
+        "-"
+    } else {
+        ""
+    };
+    let digits = grouped(value, decimals, &c);
+
+    match kind {
+        "currency" | "price" => {
+            let symbol = currency_symbol(currency);
+            Ok(if c.symbol_after {
+                format!("{}{}\u{a0}{}", sign, digits, symbol)
+            } else {
+                format!("{}{}{}", sign, symbol, digits)
+            })
+        }
+        "percent" => {
+            let gap = if c.percent_gap { "\u{a0}" } else { "" };
+            Ok(format!("{}{}{}%", sign, digits, gap))
+        }
+        "number" => Ok(format!("{}{}", sign, digits)),
+        _ => Err(format!(
+            "Unknown format '{}', expected currency, price, percent or number",
+            kind
+        )),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Provider-reported places for `symbol`. Formatting must work before the
+/// frontend has loaded the database, so a failed lookup counts as none.
+async fn reported_precision(app: &AppHandle, symbol: &str) -> Option<u32> {
+    let pool = db::pool(app).await.ok()?;
+    symbols::price_precision(&pool, symbol).await.ok().flatten()
+}
+
+/// Format a value with the user's locale, in `currency` or else their base
+/// currency, so every screen and export writes numbers the same way. Prices
+/// for a `symbol` use its provider-reported precision when one can be read,
+/// then the user's fixed places, then the magnitude rule.
+#[tauri::command]
+pub async fn format_value(
+    app: AppHandle,
+    value: f64,
+    kind: String,
+    currency: Option<String>,
+    symbol: Option<String>,
+) -> Result<String, String> {
+    logged!("format_value", [value, kind, currency, symbol], async {
+        let settings = settings::load_settings(&app);
+        let currency = currency
+            .map(|code| code.trim().to_uppercase())
+            .unwrap_or(settings.base_currency);
+        let reported = match symbol.filter(|_| kind == "price") {
+            Some(symbol) => reported_precision(&app, &symbol).await,
+            None => None,
+        };
+        format(
+            value,
+            &kind,
+            &currency,
+            &settings.locale,
+            reported.or(settings.price_decimals),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn us_dollars_group_with_commas() {
+        assert_eq!(
+            format(1234.5, "currency", "USD", "en-US", None).unwrap(),
+            "$1,234.50"
+        );
+        assert_eq!(
+            format(-0.5, "currency", "USD", "en-US", None).unwrap(),
+            "-$0.50"
+        );
+        assert_eq!(
+            format(1_000_000.0, "number", "USD", "en-US", None).unwrap(),
+            "1,000,000.00"
+        );
+    }
+
+    #[test]
+    fn european_locales_swap_separators_and_trail_the_symbol() {
+        assert_eq!(
+            format(1234.5, "currency", "EUR", "de-DE", None).unwrap(),
+            "1.234,50\u{a0}€"
+        );
+        assert_eq!(
+            format(-1234.5, "currency", "EUR", "fr-FR", None).unwrap(),
+            "-1\u{202f}234,50\u{a0}€"
+        );
+        assert_eq!(
+            format(12.345, "percent", "EUR", "de-DE", None).unwrap(),
+            "12,35\u{a0}%"
+        );
+    }
+
+    #[test]
+    fn yen_has_no_minor_units() {
+        assert_eq!(
+            format(123456.7, "currency", "JPY", "ja-JP", None).unwrap(),
+            "¥123,457"
+        );
+        assert_eq!(
+            format(50.0, "currency", "CHF", "en-GB", None).unwrap(),
+            "CHF50.00"
+        );
+    }
+
+    #[test]
+    fn price_places_follow_magnitude() {
+        assert_eq!(price_decimals(0.0421), 4);
+        assert_eq!(price_decimals(0.9999), 4);
+        assert_eq!(price_decimals(1.0), 2);
+        assert_eq!(price_decimals(24.5), 2);
+        assert_eq!(price_decimals(412.37), 2);
+        assert_eq!(price_decimals(5_123.0), 2);
+    }
+
+    #[test]
+    fn prices_use_magnitude_places_unless_overridden() {
+        assert_eq!(
+            format(0.04217, "price", "USD", "en-US", None).unwrap(),
+            "$0.0422"
+        );
+        assert_eq!(
+            format(412.371, "price", "USD", "en-US", None).unwrap(),
+            "$412.37"
+        );
+        assert_eq!(
+            format(412.371, "price", "USD", "en-US", Some(3)).unwrap(),
+            "$412.371"
+        );
+    }
+
+    #[test]
+    fn tiny_negatives_are_not_negative_zero() {
+        assert_eq!(
+            format(-0.001, "percent", "USD", "en-US", None).unwrap(),
+            "0.00%"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_kind_and_locale() {
+        assert!(format(1.0, "ratio", "USD", "en-US", None).is_err());
+        assert!(format(1.0, "number", "USD", "xx-XX", None).is_err());
+        assert!(format(f64::NAN, "number", "USD", "en-US", None).is_err());
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use chrono::{NaiveDate, Utc};
+use chrono_tz::America::New_York;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::monitors::MonitorState;
+use crate::settings::{self, SettingsPatch};
+use crate::{db, market, session};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct SessionOpen {
+    open: f64,
+    prev_close: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Gap {
+    symbol: String,
+    open: f64,
+    prev_close: f64,
+    /// Positive for a gap up.
+    gap_pct: f64,
+    /// `up` or `down`.
+    direction: &'static str,
+}
+
+/// Symbols already alerted in the current session.
+#[derive(Default)]
+pub struct GapGuard(Mutex<AlertedToday>);
+
+#[derive(Default)]
+pub(crate) struct AlertedToday {
+    day: Option<NaiveDate>,
+    symbols: HashSet<String>,
+}
+
+impl AlertedToday {
+    /// Records `symbol` for `day`, returning `false` if it was already
+    /// alerted that day.
+    fn first_today(&mut self, day: NaiveDate, symbol: &str) -> bool {
+        if self.day != Some(day) {
+            self.day = Some(day);
+            self.symbols.clear();
+        }
+        self.symbols.insert(symbol.to_string())
+    }
+}
+
+/// Gaps of at least `threshold_pct` either way among `held` symbols, each
+/// reported once per `day`. Symbols without session data are skipped.
+pub(crate) fn detect(
+    held: &[String],
+    sessions: &HashMap<String, SessionOpen>,
+    threshold_pct: f64,
+    alerted: &mut AlertedToday,
+    day: NaiveDate,
+) -> Vec<Gap> {
+    held.iter()
+        .filter_map(|symbol| {
+            let session = sessions.get(symbol)?;
+            if session.prev_close <= 0.0 || !session.open.is_finite() {
+                return None;
+            }
+            let gap_pct = (session.open - session.prev_close) / session.prev_close * 100.0;
+            if gap_pct.abs() < threshold_pct || !alerted.first_today(day, symbol) {
+                return None;
+            }
+            Some(Gap {
+                symbol: symbol.clone(),
+                open: session.open,
+                prev_close: session.prev_close,
+                gap_pct,
+                direction: if gap_pct > 0.0 { "up" } else { "down" },
+            })
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Compare each held position's opening price with its prior close and
+/// emit `gap-alert` for gaps past the configured threshold, once per
+/// symbol per session. `sessions` maps symbols to today's open and the
+/// prior close, which the frontend already has from its quotes. Does
+/// nothing before the open.
+#[tauri::command]
+pub async fn check_gaps(
+    app: AppHandle,
+    monitors: State<'_, MonitorState>,
+    guard: State<'_, GapGuard>,
+    sessions: HashMap<String, SessionOpen>,
+) -> Result<Vec<Gap>, String> {
+    logged!("check_gaps", [], async {
+        let now = Utc::now();
+        if monitors.paused() || !market::market_is_open(now) {
+            return Ok(Vec::new());
+        }
+
+        let pool = db::pool(&app).await?;
+        let mut held: Vec<String> = db::load_trades(&pool)
+            .await?
+            .into_iter()
+            .filter(|t| t.is_open())
+            .map(|t| t.symbol)
+            .collect();
+        held.sort();
+        held.dedup();
+
+        let sessions: HashMap<String, SessionOpen> = sessions
+            .into_iter()
+            .map(|(symbol, session)| Ok((db::clean_symbol(&symbol)?, session)))
+            .collect::<Result<_, String>>()?;
+        let threshold = settings::load_settings(&app).gap_threshold_pct;
+        let today = now.with_timezone(&New_York).date_naive();
+        let gaps = {
+            let mut alerted = guard.0.lock().unwrap_or_else(|e| e.into_inner());
+            detect(&held, &sessions, threshold, &mut alerted, today)
+        };
+        session::alerts_fired(gaps.len());
+        for gap in &gaps {
+            let _ = app.emit("gap-alert", gap);
+        }
+        Ok(gaps)
+    })
+}
+
+/// Smallest overnight move, in percent either way, that `check_gaps`
+/// reports.
+#[tauri::command]
+pub fn set_gap_threshold(app: AppHandle, pct: f64) -> Result<(), String> {
+    logged!("set_gap_threshold", [pct], {
+        let patch = SettingsPatch {
+            gap_threshold_pct: Some(pct),
+            ..Default::default()
+        };
+        settings::update_settings(&app, patch).map(|_| ())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sessions(rows: &[(&str, f64, f64)]) -> HashMap<String, SessionOpen> {
+        rows.iter()
+            .map(|(symbol, open, prev_close)| {
+                (
+                    symbol.to_string(),
+                    SessionOpen {
+                        open: *open,
+                        prev_close: *prev_close,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, d).unwrap()
+    }
+
+    #[test]
+    fn flags_gaps_both_ways_past_the_threshold() {
+        let held = vec!["AAPL".to_string(), "MSFT".to_string(), "NVDA".to_string()];
+        let data = sessions(&[
+            ("AAPL", 105.0, 100.0),
+            ("MSFT", 388.0, 400.0),
+            ("NVDA", 101.0, 100.0),
+            ("TSLA", 150.0, 100.0),
+        ]);
+
+        let gaps = detect(&held, &data, 2.0, &mut AlertedToday::default(), day(13));
+        let found: Vec<(&str, &str)> = gaps
+            .iter()
+            .map(|g| (g.symbol.as_str(), g.direction))
+            .collect();
+        assert_eq!(found, [("AAPL", "up"), ("MSFT", "down")]);
+        assert_eq!(gaps[0].gap_pct, 5.0);
+        assert_eq!(gaps[1].gap_pct, -3.0);
+    }
+
+    #[test]
+    fn each_symbol_alerts_once_per_day() {
+        let held = vec!["AAPL".to_string()];
+        let data = sessions(&[("AAPL", 95.0, 100.0)]);
+        let mut alerted = AlertedToday::default();
+
+        assert_eq!(detect(&held, &data, 2.0, &mut alerted, day(13)).len(), 1);
+        assert!(detect(&held, &data, 2.0, &mut alerted, day(13)).is_empty());
+        assert_eq!(detect(&held, &data, 2.0, &mut alerted, day(14)).len(), 1);
+    }
+}
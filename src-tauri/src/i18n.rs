@@ -0,0 +1,296 @@
+use tauri::{AppHandle, Runtime};
+
+use crate::settings::{self, SettingsPatch};
+
+type Messages = &'static [(&'static str, &'static str)];
+
+/// English is the reference table; every other language falls back to it.
+const EN: Messages = &[
+    ("notify.test", "Stock Advisors test notification"),
+    ("notify.analysis_complete", "Analysis complete: {symbol}"),
+    (
+        "notify.held_back",
+        "{count} notifications held back, open to review",
+    ),
+    ("notify.volume_spike", "Unusual volume: {symbol}"),
+    (
+        "notify.volume_spike_pace",
+        "On pace for {multiple}x its average volume",
+    ),
+    (
+        "notify.allocation_drift",
+        "{symbol} is {weight}% of your portfolio",
+    ),
+    (
+        "notify.allocation_trim",
+        "That's over your {max}% limit; consider trimming",
+    ),
+    (
+        "notify.allocation_target",
+        "{name} is at {weight}%, off its target",
+    ),
+    (
+        "notify.allocation_rebalance",
+        "Your target is {target}%; consider rebalancing",
+    ),
+    ("notify.alert_triggered", "Alert triggered: {symbol}"),
+    ("notify.alert_price", "{symbol} is at {price}"),
+    ("greeting.morning", "Good morning, {name}"),
+    ("greeting.afternoon", "Good afternoon, {name}"),
+    ("greeting.evening", "Good evening, {name}"),
+    ("market.opens_in", "US markets open in {time}"),
+    ("market.closes_in", "US markets close in {time}"),
+];
+
+const DE: Messages = &[
+    ("notify.test", "Testbenachrichtigung von Stock Advisors"),
+    (
+        "notify.analysis_complete",
+        "Analyse abgeschlossen: {symbol}",
+    ),
+    (
+        "notify.held_back",
+        "{count} Benachrichtigungen zurückgehalten, zum Ansehen öffnen",
+    ),
+    ("notify.volume_spike", "Ungewöhnliches Volumen: {symbol}"),
+    (
+        "notify.volume_spike_pace",
+        "Auf Kurs für das {multiple}-Fache des Durchschnittsvolumens",
+    ),
+    (
+        "notify.allocation_drift",
+        "{symbol} macht {weight} % Ihres Portfolios aus",
+    ),
+    (
+        "notify.allocation_trim",
+        "Das liegt über Ihrem Limit von {max} %; erwägen Sie eine Reduzierung",
+    ),
+    (
+        "notify.allocation_target",
+        "{name} liegt bei {weight} %, abseits des Ziels",
+    ),
+    (
+        "notify.allocation_rebalance",
+        "Ihr Ziel sind {target} %; erwägen Sie ein Rebalancing",
+    ),
+    ("notify.alert_triggered", "Alarm ausgelöst: {symbol}"),
+    ("notify.alert_price", "{symbol} steht bei {price}"),
+    ("greeting.morning", "Guten Morgen, {name}"),
+    ("greeting.afternoon", "Guten Tag, {name}"),
+    ("greeting.evening", "Guten Abend, {name}"),
+    ("market.opens_in", "US-Märkte öffnen in {time}"),
+    ("market.closes_in", "US-Märkte schließen in {time}"),
+];
+
+const FR: Messages = &[
+    ("notify.test", "Notification de test de Stock Advisors"),
+    ("notify.analysis_complete", "Analyse terminée : {symbol}"),
+    (
+        "notify.held_back",
+        "{count} notifications en attente, ouvrez pour les consulter",
+    ),
+    ("notify.volume_spike", "Volume inhabituel : {symbol}"),
+    (
+        "notify.volume_spike_pace",
+        "En voie d'atteindre {multiple} fois le volume moyen",
+    ),
+    (
+        "notify.allocation_drift",
+        "{symbol} représente {weight} % de votre portefeuille",
+    ),
+    (
+        "notify.allocation_trim",
+        "C'est au-delà de votre limite de {max} % ; envisagez d'alléger",
+    ),
+    (
+        "notify.allocation_target",
+        "{name} est à {weight} %, loin de son objectif",
+    ),
+    (
+        "notify.allocation_rebalance",
+        "Votre objectif est de {target} % ; envisagez un rééquilibrage",
+    ),
+    ("notify.alert_triggered", "Alerte déclenchée : {symbol}"),
+    ("notify.alert_price", "{symbol} est à {price}"),
+    ("greeting.morning", "Bonjour, {name}"),
+    ("greeting.afternoon", "Bon après-midi, {name}"),
+    ("greeting.evening", "Bonsoir, {name}"),
+    (
+        "market.opens_in",
+        "Les marchés américains ouvrent dans {time}",
+    ),
+    (
+        "market.closes_in",
+        "Les marchés américains ferment dans {time}",
+    ),
+];
+
+const ES: Messages = &[
+    ("notify.test", "Notificación de prueba de Stock Advisors"),
+    ("notify.analysis_complete", "Análisis completado: {symbol}"),
+    (
+        "notify.held_back",
+        "{count} notificaciones retenidas, abre para revisarlas",
+    ),
+    ("notify.volume_spike", "Volumen inusual: {symbol}"),
+    (
+        "notify.volume_spike_pace",
+        "En camino a {multiple} veces su volumen medio",
+    ),
+    (
+        "notify.allocation_drift",
+        "{symbol} es el {weight} % de tu cartera",
+    ),
+    (
+        "notify.allocation_trim",
+        "Supera tu límite del {max} %; considera reducir la posición",
+    ),
+    (
+        "notify.allocation_target",
+        "{name} está en el {weight} %, lejos de su objetivo",
+    ),
+    (
+        "notify.allocation_rebalance",
+        "Tu objetivo es el {target} %; considera reequilibrar",
+    ),
+    ("notify.alert_triggered", "Alerta activada: {symbol}"),
+    ("notify.alert_price", "{symbol} está en {price}"),
+    ("greeting.morning", "Buenos días, {name}"),
+    ("greeting.afternoon", "Buenas tardes, {name}"),
+    ("greeting.evening", "Buenas noches, {name}"),
+    ("market.opens_in", "Los mercados de EE. UU. abren en {time}"),
+    (
+        "market.closes_in",
+        "Los mercados de EE. UU. cierran en {time}",
+    ),
+];
+
+const JA: Messages = &[
+    ("notify.test", "Stock Advisors のテスト通知"),
+    ("notify.analysis_complete", "分析完了: {symbol}"),
+    (
+        "notify.held_back",
+        "{count} 件の通知を保留中です。開いて確認してください",
+    ),
+    ("notify.volume_spike", "異常な出来高: {symbol}"),
+    (
+        "notify.volume_spike_pace",
+        "平均出来高の {multiple} 倍のペースです",
+    ),
+    (
+        "notify.allocation_drift",
+        "{symbol} がポートフォリオの {weight}% を占めています",
+    ),
+    (
+        "notify.allocation_trim",
+        "上限の {max}% を超えています。一部売却を検討してください",
+    ),
+    (
+        "notify.allocation_target",
+        "{name} は {weight}% で、目標から外れています",
+    ),
+    (
+        "notify.allocation_rebalance",
+        "目標は {target}% です。リバランスを検討してください",
+    ),
+    ("notify.alert_triggered", "アラート発動: {symbol}"),
+    ("notify.alert_price", "{symbol} は {price} です"),
+    ("greeting.morning", "おはようございます、{name}さん"),
+    ("greeting.afternoon", "こんにちは、{name}さん"),
+    ("greeting.evening", "こんばんは、{name}さん"),
+    ("market.opens_in", "米国市場は {time} 後に開きます"),
+    ("market.closes_in", "米国市場は {time} 後に閉まります"),
+];
+
+/// Table for the language part of a locale tag such as `de-DE`.
+fn messages(locale: &str) -> Messages {
+    match locale.split('-').next().unwrap_or_default() {
+        "de" => DE,
+        "fr" => FR,
+        "es" => ES,
+        "ja" => JA,
+        _ => EN,
+    }
+}
+
+fn lookup(table: Messages, key: &str) -> Option<&'static str> {
+    table.iter().find(|(k, _)| *k == key).map(|(_, text)| *text)
+}
+
+/// The message for `key` in `locale` with `{placeholders}` filled from
+/// `args`. Falls back to English, then to the key itself.
+pub(crate) fn text(locale: &str, key: &str, args: &[(&str, &str)]) -> String {
+    let template = lookup(messages(locale), key)
+        .or_else(|| lookup(EN, key))
+        .unwrap_or(key);
+    args.iter()
+        .fold(template.to_string(), |out, (name, value)| {
+            out.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+/// [`text`] in the user's locale.
+pub(crate) fn user_text<R: Runtime>(
+    app: &AppHandle<R>,
+    key: &str,
+    args: &[(&str, &str)],
+) -> String {
+    text(&settings::load_settings(app).locale, key, args)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Language for backend-generated text such as notifications and the
+/// welcome message. Also used for number formatting.
+#[tauri::command]
+pub fn set_locale(app: AppHandle, locale: String) -> Result<(), String> {
+    logged!("set_locale", [locale], {
+        let patch = SettingsPatch {
+            locale: Some(locale),
+            ..Default::default()
+        };
+        settings::update_settings(&app, patch).map(|_| ())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switching_locale_changes_the_message() {
+        let args = [("name", "Ada")];
+        assert_eq!(
+            text("en-US", "greeting.morning", &args),
+            "Good morning, Ada"
+        );
+        assert_eq!(
+            text("de-DE", "greeting.morning", &args),
+            "Guten Morgen, Ada"
+        );
+        assert_eq!(
+            text("ja-JP", "greeting.morning", &args),
+            "おはようございます、Adaさん"
+        );
+    }
+
+    #[test]
+    fn unknown_locales_and_keys_fall_back() {
+        assert_eq!(
+            text("pt-BR", "notify.analysis_complete", &[("symbol", "AAPL")]),
+            "Analysis complete: AAPL"
+        );
+        assert_eq!(text("fr-FR", "no.such.key", &[]), "no.such.key");
+    }
+
+    #[test]
+    fn every_table_covers_the_english_keys() {
+        for table in [DE, FR, ES, JA] {
+            for (key, _) in EN {
+                assert!(lookup(table, key).is_some(), "missing {}", key);
+            }
+        }
+    }
+}
@@ -0,0 +1,401 @@
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_BOLLINGER_PERIOD: usize = 20;
+const DEFAULT_BOLLINGER_STD: f64 = 2.0;
+
+fn default_bollinger_period() -> usize {
+    DEFAULT_BOLLINGER_PERIOD
+}
+
+fn default_bollinger_std() -> f64 {
+    DEFAULT_BOLLINGER_STD
+}
+
+/// One OHLC bar.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Candle {
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+pub(crate) fn validate_candles(candles: &[Candle]) -> Result<(), String> {
+    for (i, c) in candles.iter().enumerate() {
+        let finite = [c.open, c.high, c.low, c.close]
+            .iter()
+            .all(|v| v.is_finite());
+        if !finite || c.high < c.low || c.open > c.high || c.open < c.low {
+            return Err(format!("Candle {} has an invalid high/low range", i));
+        }
+        if c.close > c.high || c.close < c.low {
+            return Err(format!("Candle {} closes outside its high/low range", i));
+        }
+    }
+    Ok(())
+}
+
+/// An indicator to compute, as sent by the charting code.
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IndicatorSpec {
+    Sma {
+        period: usize,
+    },
+    Bollinger {
+        #[serde(default = "default_bollinger_period")]
+        period: usize,
+        #[serde(default = "default_bollinger_std")]
+        num_std: f64,
+    },
+}
+
+/// Bands aligned with the input prices; `None` during the warm-up period.
+#[derive(Debug, Serialize)]
+pub struct BollingerSeries {
+    upper: Vec<Option<f64>>,
+    middle: Vec<Option<f64>>,
+    lower: Vec<Option<f64>>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IndicatorSeries {
+    Sma { values: Vec<Option<f64>> },
+    Bollinger(BollingerSeries),
+}
+
+fn check_period(period: usize) -> Result<(), String> {
+    if period == 0 {
+        Err("Period must be at least 1".into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Simple moving average over `period` prices.
+pub(crate) fn sma(prices: &[f64], period: usize) -> Vec<Option<f64>> {
+    (0..prices.len())
+        .map(|i| {
+            (period > 0 && i + 1 >= period)
+                .then(|| prices[i + 1 - period..=i].iter().sum::<f64>() / period as f64)
+        })
+        .collect()
+}
+
+/// SMA with bands `num_std` population standard deviations either side.
+pub(crate) fn bollinger(prices: &[f64], period: usize, num_std: f64) -> BollingerSeries {
+    let middle = sma(prices, period);
+    let mut upper = Vec::with_capacity(prices.len());
+    let mut lower = Vec::with_capacity(prices.len());
+
+    for (i, mean) in middle.iter().enumerate() {
+        let band = mean.map(|mean| {
+            let window = &prices[i + 1 - period..=i];
+            let variance = window.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / period as f64;
+            (mean, num_std * variance.sqrt())
+        });
+        upper.push(band.map(|(mean, width)| mean + width));
+        lower.push(band.map(|(mean, width)| mean - width));
+    }
+
+    BollingerSeries {
+        upper,
+        middle,
+        lower,
+    }
+}
+
+/// Largest of the bar's range and its gaps from the previous close.
+fn true_range(candle: &Candle, prev_close: Option<f64>) -> f64 {
+    let range = candle.high - candle.low;
+    match prev_close {
+        Some(prev) => range
+            .max((candle.high - prev).abs())
+            .max((candle.low - prev).abs()),
+        None => range,
+    }
+}
+
+/// Average True Range with Wilder's smoothing, seeded by the mean of the
+/// first `period` true ranges. All `None` if there are fewer candles.
+pub(crate) fn atr(candles: &[Candle], period: usize) -> Vec<Option<f64>> {
+    let mut values = vec![None; candles.len()];
+    if period == 0 || candles.len() < period {
+        return values;
+    }
+
+    let ranges: Vec<f64> = candles
+        .iter()
+        .enumerate()
+        .map(|(i, c)| true_range(c, i.checked_sub(1).map(|p| candles[p].close)))
+        .collect();
+
+    let mut current = ranges[..period].iter().sum::<f64>() / period as f64;
+    values[period - 1] = Some(current);
+    for i in period..candles.len() {
+        current = (current * (period - 1) as f64 + ranges[i]) / period as f64;
+        values[i] = Some(current);
+    }
+    values
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PivotMethod {
+    Standard,
+    Fibonacci,
+    Camarilla,
+}
+
+impl PivotMethod {
+    fn parse(method: &str) -> Result<Self, String> {
+        match method {
+            "standard" => Ok(Self::Standard),
+            "fibonacci" => Ok(Self::Fibonacci),
+            "camarilla" => Ok(Self::Camarilla),
+            _ => Err(format!(
+                "Unknown pivot method '{}', expected one of: standard, fibonacci, camarilla",
+                method
+            )),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PivotLevels {
+    pivot: f64,
+    r1: f64,
+    r2: f64,
+    r3: f64,
+    s1: f64,
+    s2: f64,
+    s3: f64,
+}
+
+/// Intraday levels from the previous session's bar.
+pub(crate) fn pivot_points(prev: Candle, method: PivotMethod) -> PivotLevels {
+    let Candle {
+        high, low, close, ..
+    } = prev;
+    let pivot = (high + low + close) / 3.0;
+    let range = high - low;
+
+    match method {
+        PivotMethod::Standard => PivotLevels {
+            pivot,
+            r1: 2.0 * pivot - low,
+            r2: pivot + range,
+            r3: high + 2.0 * (pivot - low),
+            s1: 2.0 * pivot - high,
+            s2: pivot - range,
+            s3: low - 2.0 * (high - pivot),
+        },
+        PivotMethod::Fibonacci => PivotLevels {
+            pivot,
+            r1: pivot + 0.382 * range,
+            r2: pivot + 0.618 * range,
+            r3: pivot + range,
+            s1: pivot - 0.382 * range,
+            s2: pivot - 0.618 * range,
+            s3: pivot - range,
+        },
+        PivotMethod::Camarilla => PivotLevels {
+            pivot,
+            r1: close + range * 1.1 / 12.0,
+            r2: close + range * 1.1 / 6.0,
+            r3: close + range * 1.1 / 4.0,
+            s1: close - range * 1.1 / 12.0,
+            s2: close - range * 1.1 / 6.0,
+            s3: close - range * 1.1 / 4.0,
+        },
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Compute each requested indicator over `prices`, in request order.
+#[tauri::command]
+pub fn compute_indicators(
+    prices: Vec<f64>,
+    specs: Vec<IndicatorSpec>,
+) -> Result<Vec<IndicatorSeries>, String> {
+    logged!("compute_indicators", [], {
+        if prices.iter().any(|p| !p.is_finite()) {
+            return Err("Prices must be finite numbers".into());
+        }
+
+        specs
+            .into_iter()
+            .map(|spec| match spec {
+                IndicatorSpec::Sma { period } => {
+                    check_period(period)?;
+                    Ok(IndicatorSeries::Sma {
+                        values: sma(&prices, period),
+                    })
+                }
+                IndicatorSpec::Bollinger { period, num_std } => {
+                    check_period(period)?;
+                    if !num_std.is_finite() || num_std < 0.0 {
+                        return Err("Band width must be a non-negative number".into());
+                    }
+                    Ok(IndicatorSeries::Bollinger(bollinger(
+                        &prices, period, num_std,
+                    )))
+                }
+            })
+            .collect()
+    })
+}
+
+#[tauri::command]
+pub fn compute_atr(candles: Vec<Candle>, period: usize) -> Result<Vec<Option<f64>>, String> {
+    logged!("compute_atr", [period], {
+        check_period(period)?;
+        validate_candles(&candles)?;
+        Ok(atr(&candles, period))
+    })
+}
+
+#[tauri::command]
+pub fn compute_pivots(
+    prev_high: f64,
+    prev_low: f64,
+    prev_close: f64,
+    method: String,
+) -> Result<PivotLevels, String> {
+    logged!(
+        "compute_pivots",
+        [prev_high, prev_low, prev_close, method],
+        {
+            let method = PivotMethod::parse(&method)?;
+            let prev = Candle {
+                open: prev_close,
+                high: prev_high,
+                low: prev_low,
+                close: prev_close,
+            };
+            validate_candles(&[prev])?;
+            Ok(pivot_points(prev, method))
+        }
+    )
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::Candle;
+
+    pub(crate) fn candle(open: f64, high: f64, low: f64, close: f64) -> Candle {
+        Candle {
+            open,
+            high,
+            low,
+            close,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::candle;
+    use super::*;
+
+    #[test]
+    fn sma_warms_up_with_none() {
+        assert_eq!(
+            sma(&[1.0, 2.0, 3.0, 4.0], 3),
+            vec![None, None, Some(2.0), Some(3.0)]
+        );
+    }
+
+    #[test]
+    fn bollinger_middle_is_sma_and_bands_are_symmetric() {
+        let prices = [10.0, 11.0, 12.0, 11.0, 10.0, 9.0, 10.0, 12.0];
+        let bands = bollinger(&prices, 4, 2.0);
+
+        assert_eq!(bands.middle, sma(&prices, 4));
+        assert!(bands.upper[2].is_none() && bands.lower[2].is_none());
+        for i in 3..prices.len() {
+            let (upper, middle, lower) = (
+                bands.upper[i].unwrap(),
+                bands.middle[i].unwrap(),
+                bands.lower[i].unwrap(),
+            );
+            assert!(upper > middle);
+            assert!(((upper - middle) - (middle - lower)).abs() < 1e-9);
+        }
+
+        // First full window: mean 11, population std sqrt(0.5)
+        let width = bands.upper[3].unwrap() - bands.middle[3].unwrap();
+        assert!((width - 2.0 * 0.5f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn atr_matches_hand_computed_series() {
+        let candles = [
+            candle(10.0, 11.0, 9.0, 10.0),  // TR 2
+            candle(10.0, 12.0, 10.0, 11.0), // TR 2
+            candle(12.0, 13.0, 12.0, 12.5), // TR max(1, 2, 1) = 2
+            candle(10.0, 10.5, 9.5, 10.0),  // TR max(1, 2, 3) = 3
+            candle(10.0, 10.0, 9.0, 9.5),   // TR 1
+        ];
+
+        let values = atr(&candles, 3);
+        assert_eq!(values[..2], [None, None]);
+        assert_eq!(values[2], Some(2.0));
+        assert!((values[3].unwrap() - 7.0 / 3.0).abs() < 1e-9);
+        assert!((values[4].unwrap() - (14.0 / 3.0 + 1.0) / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn atr_needs_a_full_period() {
+        let candles = [candle(10.0, 11.0, 9.0, 10.0)];
+        assert_eq!(atr(&candles, 3), vec![None]);
+        assert!(validate_candles(&[candle(10.0, 9.0, 11.0, 10.0)]).is_err());
+    }
+
+    #[test]
+    fn standard_pivots_match_known_example() {
+        let levels = pivot_points(candle(104.0, 110.0, 100.0, 105.0), PivotMethod::Standard);
+        assert_eq!(
+            levels,
+            PivotLevels {
+                pivot: 105.0,
+                r1: 110.0,
+                r2: 115.0,
+                r3: 120.0,
+                s1: 100.0,
+                s2: 95.0,
+                s3: 90.0,
+            }
+        );
+    }
+
+    #[test]
+    fn fibonacci_and_camarilla_are_symmetric() {
+        let prev = candle(104.0, 110.0, 100.0, 105.0);
+        let fib = pivot_points(prev, PivotMethod::Fibonacci);
+        assert!((fib.r1 - 108.82).abs() < 1e-9);
+        assert!((fib.r1 - fib.pivot + fib.s1 - fib.pivot).abs() < 1e-9);
+
+        let cam = pivot_points(prev, PivotMethod::Camarilla);
+        assert!((cam.r3 - 107.75).abs() < 1e-9);
+        assert!((cam.s3 - 102.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn pivot_method_must_be_known() {
+        assert_eq!(PivotMethod::parse("fibonacci"), Ok(PivotMethod::Fibonacci));
+        assert!(PivotMethod::parse("woodie").is_err());
+    }
+
+    #[test]
+    fn bollinger_spec_defaults_to_20_and_2() {
+        let spec: IndicatorSpec = serde_json::from_str(r#"{ "kind": "bollinger" }"#).unwrap();
+        assert!(matches!(
+            spec,
+            IndicatorSpec::Bollinger { period: 20, num_std } if num_std == 2.0
+        ));
+    }
+}
@@ -0,0 +1,69 @@
+//! Backend-side logging for commands that fail.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Record the command's timing, log it to stderr if it failed, and pass
+/// the result through unchanged.
+pub(crate) fn log_result<T>(
+    command: &'static str,
+    args: &[(&str, String)],
+    started: std::time::Instant,
+    result: Result<T, String>,
+) -> Result<T, String> {
+    crate::metrics::record(command, started.elapsed());
+    crate::session::command_invoked();
+    if let Err(e) = &result {
+        let args: Vec<String> = args.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        let line = format!("{}({}) failed: {}", command, args.join(", "), e);
+        eprintln!("[ipc] {}", line);
+        remember(format!("{} {}", chrono::Utc::now().to_rfc3339(), line));
+    }
+    result
+}
+
+/// Run a command body and log its `Err` before it goes back to the renderer.
+///
+/// Only the arguments named in the brackets make it into the log line, so
+/// leave out anything user-authored (note text, draft JSON, image data).
+/// Async commands pass `async { ... }` as the body; sync ones a plain block.
+macro_rules! logged {
+    ($command:literal, [$($arg:ident),*], async $body:block) => {{
+        let started = std::time::Instant::now();
+        let args = [$((stringify!($arg), format!("{:?}", $arg))),*];
+        let result: Result<_, String> = async move $body.await;
+        $crate::ipc::log_result($command, &args, started, result)
+    }};
+    ($command:literal, [$($arg:ident),*], $body:block) => {{
+        let started = std::time::Instant::now();
+        let args = [$((stringify!($arg), format!("{:?}", $arg))),*];
+        // The closure gives `?` in the body something to return from
+        #[allow(clippy::redundant_closure_call)]
+        let result = (|| -> Result<_, String> { $body })();
+        $crate::ipc::log_result($command, &args, started, result)
+    }};
+}
+
+// ---------------------------------------------------------------------------
+// Recent errors
+// ---------------------------------------------------------------------------
+
+const MAX_RECENT_ERRORS: usize = 200;
+
+/// The last few failure lines, kept in memory for diagnostics bundles since
+/// stderr isn't captured anywhere in release builds.
+static RECENT_ERRORS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+fn remember(line: String) {
+    let mut recent = RECENT_ERRORS.lock().unwrap_or_else(|e| e.into_inner());
+    if recent.len() == MAX_RECENT_ERRORS {
+        recent.pop_front();
+    }
+    recent.push_back(line);
+}
+
+/// Logged failures since launch, oldest first.
+pub(crate) fn recent_errors() -> Vec<String> {
+    let recent = RECENT_ERRORS.lock().unwrap_or_else(|e| e.into_inner());
+    recent.iter().cloned().collect()
+}
@@ -0,0 +1,192 @@
+use std::path::Path;
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+
+use crate::actions::{self, Restore};
+use crate::{db, export};
+
+const MAX_ENTRY_CHARS: usize = 10_000;
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct JournalEntry {
+    id: i64,
+    trade_id: i64,
+    symbol: String,
+    text: String,
+    /// A screenshot saved with `save_image`.
+    image_path: Option<String>,
+    created_at: String,
+}
+
+// ---------------------------------------------------------------------------
+// Queries
+// ---------------------------------------------------------------------------
+
+/// Add an entry for `trade_id`, taking the symbol from the trade so the two
+/// can't disagree.
+pub(crate) async fn add_entry(
+    pool: &SqlitePool,
+    trade_id: i64,
+    text: &str,
+    image_path: Option<&str>,
+) -> Result<JournalEntry, String> {
+    let text = text.trim();
+    if text.is_empty() && image_path.is_none() {
+        return Err("Journal entry needs text or a screenshot".into());
+    }
+    if text.chars().count() > MAX_ENTRY_CHARS {
+        return Err(format!("Entry exceeds {} characters", MAX_ENTRY_CHARS));
+    }
+    if let Some(path) = image_path {
+        export::validate_destination(path, "png")?;
+        if !Path::new(path).is_file() {
+            return Err("Screenshot file does not exist".into());
+        }
+    }
+
+    let symbol: String = sqlx::query_scalar("SELECT symbol FROM trades WHERE id = ?")
+        .bind(trade_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No trade with id {}", trade_id))?;
+
+    sqlx::query_as(
+        "INSERT INTO journal_entries (trade_id, symbol, text, image_path) VALUES (?, ?, ?, ?) \
+         RETURNING id, trade_id, symbol, text, image_path, created_at",
+    )
+    .bind(trade_id)
+    .bind(&symbol)
+    .bind(text)
+    .bind(image_path)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Entries for `symbol`, newest first.
+pub(crate) async fn entries_for(
+    pool: &SqlitePool,
+    symbol: &str,
+) -> Result<Vec<JournalEntry>, String> {
+    let symbol = db::clean_symbol(symbol)?;
+    sqlx::query_as(
+        "SELECT id, trade_id, symbol, text, image_path, created_at FROM journal_entries \
+         WHERE symbol = ? ORDER BY created_at DESC, id DESC",
+    )
+    .bind(&symbol)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Delete an entry, keeping it for undo. The screenshot file is left where
+/// the user saved it.
+pub(crate) async fn delete_entry(pool: &SqlitePool, id: i64) -> Result<(), String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let deleted_row = Restore::capture(&mut tx, "journal_entries", vec![id.into()]).await?;
+    let deleted = sqlx::query("DELETE FROM journal_entries WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .rows_affected();
+    if deleted == 0 {
+        return Err(format!("No journal entry with id {}", id));
+    }
+    actions::record(&mut tx, "Deleted a journal entry", Some(vec![deleted_row])).await?;
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Journal a trade. Save a screenshot first with `save_image` and pass the
+/// same path as `image_path`.
+#[tauri::command]
+pub async fn add_journal_entry(
+    app: AppHandle,
+    trade_id: i64,
+    text: String,
+    image_path: Option<String>,
+) -> Result<JournalEntry, String> {
+    logged!("add_journal_entry", [trade_id], async {
+        let pool = db::pool(&app).await?;
+        add_entry(&pool, trade_id, &text, image_path.as_deref()).await
+    })
+}
+
+#[tauri::command]
+pub async fn list_journal_entries(
+    app: AppHandle,
+    symbol: String,
+) -> Result<Vec<JournalEntry>, String> {
+    logged!("list_journal_entries", [symbol], async {
+        let pool = db::pool(&app).await?;
+        entries_for(&pool, &symbol).await
+    })
+}
+
+#[tauri::command]
+pub async fn delete_journal_entry(app: AppHandle, id: i64) -> Result<(), String> {
+    logged!("delete_journal_entry", [id], async {
+        let pool = db::pool(&app).await?;
+        delete_entry(&pool, id).await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing;
+
+    #[tokio::test]
+    async fn entries_link_to_their_trade() {
+        let pool = testing::pool().await;
+        testing::insert_open(&pool, "AAPL", 10, 150.0).await;
+        testing::insert_open(&pool, "MSFT", 5, 300.0).await;
+
+        let entry = add_entry(&pool, 1, "  entered on the breakout ", None)
+            .await
+            .unwrap();
+        assert_eq!(entry.symbol, "AAPL");
+        assert_eq!(entry.text, "entered on the breakout");
+        add_entry(&pool, 2, "earnings play", None).await.unwrap();
+
+        let entries = entries_for(&pool, "aapl").await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].trade_id, 1);
+
+        assert!(add_entry(&pool, 99, "no such trade", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn delete_removes_only_that_entry() {
+        let pool = testing::pool().await;
+        testing::insert_open(&pool, "AAPL", 10, 150.0).await;
+        let first = add_entry(&pool, 1, "first", None).await.unwrap();
+        add_entry(&pool, 1, "second", None).await.unwrap();
+
+        delete_entry(&pool, first.id).await.unwrap();
+        let entries = entries_for(&pool, "AAPL").await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].text, "second");
+        assert!(delete_entry(&pool, first.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_empty_entries_and_missing_screenshots() {
+        let pool = testing::pool().await;
+        testing::insert_open(&pool, "AAPL", 10, 150.0).await;
+
+        assert!(add_entry(&pool, 1, "   ", None).await.is_err());
+        let missing = std::env::temp_dir().join("sa-journal-missing.png");
+        let err = add_entry(&pool, 1, "chart", missing.to_str())
+            .await
+            .unwrap_err();
+        assert!(err.contains("does not exist"));
+    }
+}
@@ -0,0 +1,138 @@
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::indicators::{validate_candles, Candle};
+use crate::{db, patterns, targets};
+
+/// Swings within 1% of each other count as one support/resistance level.
+const LEVEL_SENSITIVITY: f64 = 0.01;
+
+#[derive(Debug, Serialize)]
+pub struct Level {
+    /// `cost_basis`, `target`, `session_open`, `session_high`,
+    /// `session_low`, `support`, `resistance` or `support_resistance`.
+    kind: &'static str,
+    price: f64,
+    /// Percent from the current price; positive when the level is above it.
+    distance_pct: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PriceLadder {
+    symbol: String,
+    price: f64,
+    /// Highest first, like a ladder read top to bottom.
+    levels: Vec<Level>,
+}
+
+/// Every known level for one symbol. `candles` are oldest first, and the
+/// last one is taken as the current session.
+pub(crate) fn build_levels(
+    price: f64,
+    cost_basis: Option<f64>,
+    target: Option<f64>,
+    candles: &[Candle],
+) -> Vec<Level> {
+    let mut found: Vec<(&'static str, f64)> = Vec::new();
+    found.extend(cost_basis.map(|p| ("cost_basis", p)));
+    found.extend(target.map(|p| ("target", p)));
+    if let Some(session) = candles.last() {
+        found.push(("session_open", session.open));
+        found.push(("session_high", session.high));
+        found.push(("session_low", session.low));
+    }
+    for level in patterns::find_levels(candles, LEVEL_SENSITIVITY) {
+        let kind = match level.kind {
+            "both" => "support_resistance",
+            kind => kind,
+        };
+        found.push((kind, level.price));
+    }
+
+    let mut levels: Vec<Level> = found
+        .into_iter()
+        .map(|(kind, level)| Level {
+            kind,
+            price: level,
+            distance_pct: (level - price) / price * 100.0,
+        })
+        .collect();
+    levels.sort_by(|a, b| b.price.total_cmp(&a.price));
+    levels
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Cost basis, price target, session range and support/resistance for
+/// `symbol`, relative to `price`. Quotes and bars come from the frontend;
+/// without `candles` only the stored levels are returned. Price alerts
+/// aren't stored by the backend, so they aren't on the ladder.
+#[tauri::command]
+pub async fn price_ladder(
+    app: AppHandle,
+    symbol: String,
+    price: f64,
+    candles: Option<Vec<Candle>>,
+) -> Result<PriceLadder, String> {
+    logged!("price_ladder", [symbol, price], async {
+        if !price.is_finite() || price <= 0.0 {
+            return Err("Price must be positive".into());
+        }
+        let candles = candles.unwrap_or_default();
+        validate_candles(&candles)?;
+
+        let symbol = db::clean_symbol(&symbol)?;
+        let pool = db::pool(&app).await?;
+        let cost_basis = targets::cost_basis(&pool, &symbol).await?;
+        let target = targets::read_target(&pool, &symbol, None)
+            .await?
+            .map(|t| t.target);
+
+        Ok(PriceLadder {
+            levels: build_levels(price, cost_basis, target, &candles),
+            symbol,
+            price,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::candle;
+
+    #[test]
+    fn levels_are_tagged_and_sorted_high_to_low() {
+        let levels = build_levels(
+            100.0,
+            Some(90.0),
+            Some(120.0),
+            &[candle(98.0, 104.0, 97.0, 100.0)],
+        );
+
+        let kinds: Vec<&str> = levels.iter().map(|l| l.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                "target",
+                "session_high",
+                "session_open",
+                "session_low",
+                "cost_basis"
+            ]
+        );
+        assert_eq!(levels[0].distance_pct, 20.0);
+        assert_eq!(levels[4].distance_pct, -10.0);
+    }
+
+    #[test]
+    fn missing_levels_are_left_out() {
+        assert!(build_levels(100.0, None, None, &[]).is_empty());
+
+        let levels = build_levels(100.0, Some(95.0), None, &[]);
+        assert_eq!(levels.len(), 1);
+        assert_eq!(levels[0].kind, "cost_basis");
+    }
+}
@@ -1,13 +1,96 @@
+use chrono::{DateTime, Timelike, Utc};
+use chrono_tz::Tz;
 use serde::Serialize;
 use tauri::{Emitter, Manager};
 
+#[macro_use]
+mod ipc;
+
+mod actions;
+mod alerts;
+mod allocation;
+mod analytics;
+mod charts;
+mod config;
+mod db;
+mod dedupe;
+mod delivery;
+mod diagnostics;
+mod drafts;
+mod export;
+mod format;
+mod gaps;
+mod i18n;
+mod indicators;
+mod journal;
+mod ladder;
+mod lifecycle;
+mod market;
+mod metrics;
+mod monitors;
+mod network;
+mod notes;
+mod notifications;
+mod paper;
+mod patterns;
+mod planning;
+mod popover;
+mod portfolio;
+mod power;
+mod privacy;
+mod projections;
+mod providers;
+mod query;
+mod report;
+mod risk;
+mod schedule;
+mod session;
+mod settings;
+mod snapshots;
+mod stress;
+mod symbols;
+mod tags;
+mod targets;
+mod tax;
+mod timezone;
+mod tray;
+mod volume;
+mod window;
+
+/// Store file shared by every backend-persisted preference.
+pub(crate) const STORE_FILE: &str = "settings.json";
+
+const TRAY_TOOLTIP: &str = "Stock Advisors";
+
 // ---------------------------------------------------------------------------
 // Tauri commands
 // ---------------------------------------------------------------------------
 
+/// "Good morning, Ada" for the hour at `now`, with the market status on
+/// a second line.
+fn welcome_message(name: &str, now: DateTime<Tz>, locale: &str) -> String {
+    let key = match now.hour() {
+        5..=11 => "greeting.morning",
+        12..=17 => "greeting.afternoon",
+        _ => "greeting.evening",
+    };
+    format!(
+        "{}\n{}",
+        i18n::text(locale, key, &[("name", name.trim())]),
+        market::status_line(now.with_timezone(&Utc), locale)
+    )
+}
+
 #[tauri::command]
-fn greet(name: &str) -> String {
-    format!("Hello, {}! Welcome to Stock Advisors.", name)
+fn get_welcome_message(app: tauri::AppHandle, name: String) -> String {
+    let now = Utc::now().with_timezone(&timezone::user_timezone(&app));
+    welcome_message(&name, now, &settings::load_settings(&app).locale)
+}
+
+/// Kept for callers of the original template command.
+#[tauri::command]
+fn greet(app: tauri::AppHandle, name: String) -> String {
+    get_welcome_message(app, name)
 }
 
 #[tauri::command]
@@ -23,8 +106,31 @@ fn show_main_window(app: tauri::AppHandle) {
     }
 }
 
+/// Current tray state, so the UI and automated tests can check the menu
+/// is in sync with the monitors.
+#[tauri::command]
+fn get_tray_state(
+    app: tauri::AppHandle,
+    monitors: tauri::State<'_, monitors::MonitorState>,
+    mute: tauri::State<'_, alerts::AlertMute>,
+) -> TrayState {
+    let always_on_top = app
+        .get_webview_window("main")
+        .and_then(|w| w.is_always_on_top().ok())
+        .unwrap_or(false);
+
+    TrayState {
+        position_monitor: monitors.position(),
+        news_monitor: monitors.news(),
+        monitors_paused: monitors.paused(),
+        alerts_muted: mute.muted_at(chrono::Utc::now().timestamp()),
+        always_on_top,
+        tooltip: tray::tooltip(monitors.paused()),
+    }
+}
+
 // ---------------------------------------------------------------------------
-// Tray event payload
+// Tray payloads
 // ---------------------------------------------------------------------------
 
 #[derive(Clone, Serialize)]
@@ -33,6 +139,16 @@ struct TrayEventPayload {
     value: Option<bool>,
 }
 
+#[derive(Serialize)]
+struct TrayState {
+    position_monitor: bool,
+    news_monitor: bool,
+    monitors_paused: bool,
+    alerts_muted: bool,
+    always_on_top: bool,
+    tooltip: String,
+}
+
 // ---------------------------------------------------------------------------
 // Entry point
 // ---------------------------------------------------------------------------
@@ -42,10 +158,39 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_fs::init())
-        .plugin(tauri_plugin_sql::Builder::new().build())
+        .plugin(
+            tauri_plugin_sql::Builder::new()
+                .add_migrations(db::DB_URL, db::migrations())
+                .build(),
+        )
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_store::Builder::new().build())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_positioner::init())
+        .manage(monitors::MonitorState::default())
+        .manage(network::NetworkState::default())
+        .manage(notifications::NotificationLimiter::default())
+        .manage(volume::VolumeCache::default())
+        .manage(gaps::GapGuard::default())
+        .manage(portfolio::AllocationState::default())
+        .manage(alerts::AlertQuotes::default())
+        .manage(alerts::AlertMute::default())
+        .manage(alerts::LastFocus::default())
+        .manage(session::SessionStart::default())
         .setup(|app| {
+            // ---------------------------------------------------------------
+            // Settings store
+            // ---------------------------------------------------------------
+            if let Err(e) = config::migrate_store(app.handle()) {
+                eprintln!("[config] Failed to migrate settings store: {}", e);
+            }
+            app.state::<notifications::NotificationLimiter>()
+                .set_rate(settings::load_settings(app.handle()).notifications_per_minute);
+
+            if let Err(e) = window::apply_min_size(app.handle()) {
+                eprintln!("[window] Failed to apply the minimum size: {}", e);
+            }
+
             // ---------------------------------------------------------------
             // Window vibrancy (macOS only)
             // ---------------------------------------------------------------
@@ -82,6 +227,10 @@ pub fn run() {
                 tauri::menu::MenuItemBuilder::with_id("news_monitor", "News Monitor: ON")
                     .build(app)?;
 
+            let alerts_muted =
+                tauri::menu::MenuItemBuilder::with_id("alerts_muted", tray::alerts_muted_label(false))
+                    .build(app)?;
+
             let quit =
                 tauri::menu::MenuItemBuilder::with_id("quit", "Quit")
                     .build(app)?;
@@ -95,14 +244,36 @@ pub fn run() {
                 .item(&sep1)
                 .item(&position_monitor)
                 .item(&news_monitor)
+                .item(&alerts_muted)
                 .item(&sep2)
                 .item(&quit)
                 .build()?;
 
-            let _tray = tauri::tray::TrayIconBuilder::new()
-                .tooltip("Stock Advisors")
+            // Enabled states are refreshed by the frontend once the DB is up
+            app.manage(tray::TrayMenu {
+                run_analysis: run_analysis.clone(),
+                alerts_muted: alerts_muted.clone(),
+            });
+
+            let left_click_menu = tray::menu_on_left_click(&settings::load_settings(app.handle()));
+
+            let _tray = tauri::tray::TrayIconBuilder::with_id(tray::TRAY_ID)
+                .tooltip(TRAY_TOOLTIP)
                 .menu(&menu)
-                .show_menu_on_left_click(false)
+                .show_menu_on_left_click(left_click_menu)
+                .on_tray_icon_event(|tray, event| {
+                    // Keeps the positioner's idea of where the tray icon is current
+                    tauri_plugin_positioner::on_tray_event(tray.app_handle(), &event);
+
+                    if let tauri::tray::TrayIconEvent::Click {
+                        button: tauri::tray::MouseButton::Left,
+                        button_state: tauri::tray::MouseButtonState::Up,
+                        ..
+                    } = event
+                    {
+                        tray::on_left_click(tray.app_handle());
+                    }
+                })
                 .on_menu_event(move |app_handle, event| {
                     let id = event.id().as_ref();
                     match id {
@@ -123,14 +294,12 @@ pub fn run() {
                         }
                         "position_monitor" => {
                             // Toggle label between ON / OFF and notify the frontend
-                            let current_text = position_monitor.text().unwrap_or_default();
-                            let turning_on = current_text.contains("OFF");
-                            let new_label = if turning_on {
-                                "Position Monitor: ON"
-                            } else {
-                                "Position Monitor: OFF"
-                            };
-                            let _ = position_monitor.set_text(new_label);
+                            let monitor_state = app_handle.state::<monitors::MonitorState>();
+                            let (turning_on, state) =
+                                monitors::toggle_monitor(monitor_state.position());
+                            let _ = position_monitor
+                                .set_text(format!("Position Monitor: {}", state));
+                            monitor_state.set_position(turning_on);
                             let _ = app_handle.emit(
                                 "tray-event",
                                 TrayEventPayload {
@@ -140,14 +309,11 @@ pub fn run() {
                             );
                         }
                         "news_monitor" => {
-                            let current_text = news_monitor.text().unwrap_or_default();
-                            let turning_on = current_text.contains("OFF");
-                            let new_label = if turning_on {
-                                "News Monitor: ON"
-                            } else {
-                                "News Monitor: OFF"
-                            };
-                            let _ = news_monitor.set_text(new_label);
+                            let monitor_state = app_handle.state::<monitors::MonitorState>();
+                            let (turning_on, state) =
+                                monitors::toggle_monitor(monitor_state.news());
+                            let _ = news_monitor.set_text(format!("News Monitor: {}", state));
+                            monitor_state.set_news(turning_on);
                             let _ = app_handle.emit(
                                 "tray-event",
                                 TrayEventPayload {
@@ -156,6 +322,13 @@ pub fn run() {
                                 },
                             );
                         }
+                        "alerts_muted" => {
+                            let mute = app_handle.state::<alerts::AlertMute>();
+                            let muted = !mute.muted_at(chrono::Utc::now().timestamp());
+                            mute.set(muted, None);
+                            tray::sync_alerts_muted(app_handle, muted);
+                            let _ = app_handle.emit("alerts-muted", muted);
+                        }
                         "quit" => {
                             app_handle.exit(0);
                         }
@@ -164,20 +337,183 @@ pub fn run() {
                 })
                 .build(app)?;
 
+            // ---------------------------------------------------------------
+            // Background tasks
+            // ---------------------------------------------------------------
+            network::spawn_monitor(app.handle().clone());
+            power::spawn_sleep_watch(app.handle().clone());
+            schedule::spawn_scheduler(app.handle().clone());
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             greet,
+            get_welcome_message,
             get_app_version,
             show_main_window,
+            charts::get_chart_ranges,
+            charts::set_default_chart_range,
+            window::set_min_window_size,
+            get_tray_state,
+            session::session_stats,
+            session::count_quotes_fetched,
+            metrics::command_metrics,
+            metrics::reset_command_metrics,
+            providers::provider_capabilities,
+            monitors::pause_all_monitors,
+            monitors::resume_all_monitors,
+            tray::refresh_tray_menu,
+            drafts::save_draft,
+            drafts::clear_draft,
+            actions::undo_last_action,
+            alerts::parse_alert,
+            alerts::validate_condition,
+            alerts::create_alert,
+            alerts::batch_create_alerts,
+            alerts::list_alerts,
+            alerts::delete_alert,
+            alerts::bulk_delete_alerts,
+            alerts::position_alerts_summary,
+            alerts::get_alert_history,
+            alerts::set_alerts_muted,
+            alerts::check_alerts,
+            alerts::export_alerts_config,
+            alerts::import_alerts_config,
+            delivery::set_alert_webhook,
+            analytics::returns_distribution,
+            analytics::drawdown_periods,
+            analytics::rolling_correlation,
+            db::integrity_check,
+            db::get_schema,
+            dedupe::dedupe_trades,
+            diagnostics::export_diagnostics,
+            diagnostics::get_disk_usage,
+            export::save_image,
+            format::format_value,
+            gaps::check_gaps,
+            gaps::set_gap_threshold,
+            i18n::set_locale,
+            indicators::compute_indicators,
+            indicators::compute_atr,
+            indicators::compute_pivots,
+            journal::add_journal_entry,
+            journal::list_journal_entries,
+            journal::delete_journal_entry,
+            ladder::price_ladder,
+            paper::validate_order,
+            paper::paper_execute,
+            paper::check_paper_orders,
+            paper::paper_orders,
+            paper::cancel_paper_order,
+            patterns::detect_candlestick_patterns,
+            patterns::support_resistance,
+            portfolio::concentration,
+            portfolio::reconcile,
+            portfolio::stale_positions,
+            portfolio::check_allocation,
+            portfolio::set_max_position_weight,
+            portfolio::set_allocation_drift_band,
+            allocation::set_allocation_targets,
+            allocation::get_allocation_targets,
+            power::set_refresh_on_resume,
+            projections::monte_carlo,
+            query::run_query,
+            report::generate_report,
+            schedule::create_scheduled_job,
+            schedule::list_scheduled_jobs,
+            schedule::delete_scheduled_job,
+            planning::position_size,
+            planning::position_size_short,
+            planning::dca_schedule,
+            planning::dca_backtest,
+            notes::set_symbol_note,
+            notes::get_symbol_note,
+            notifications::test_notification,
+            notifications::notify_analysis_complete,
+            notifications::set_notification_rate,
+            notifications::request_notification_permission,
+            notifications::notification_permission_state,
+            tags::add_tag,
+            tags::remove_tag,
+            tags::list_tags,
+            tags::positions_by_tag,
+            targets::set_price_target,
+            targets::get_price_target,
+            tax::wash_sale_check,
+            tax::tax_harvest_candidates,
+            tax::export_tax_form,
+            network::get_network_status,
+            lifecycle::relaunch,
+            privacy::delete_all_data,
+            settings::complete_onboarding,
+            settings::get_settings,
+            settings::settings_schema,
+            settings::set_setting,
+            settings::set_startup_view,
+            settings::get_startup_view,
+            settings::set_fractional_shares,
+            settings::get_fractional_shares,
+            settings::save_last_view,
+            settings::reset_settings,
+            snapshots::take_snapshot,
+            snapshots::compare_snapshots,
+            stress::stress_test,
+            symbols::normalize_symbol,
+            symbols::canonicalize_symbol,
+            symbols::set_symbol_price_precision,
+            timezone::get_timezone,
+            timezone::set_timezone_override,
+            volume::check_volume,
+            volume::set_volume_spike_multiplier,
         ])
-        .on_window_event(|window, event| {
+        .on_page_load(|webview, payload| {
+            if webview.label() == "main"
+                && matches!(payload.event(), tauri::webview::PageLoadEvent::Finished)
+            {
+                drafts::emit_restore_draft(webview.app_handle());
+                settings::emit_onboarding(webview.app_handle());
+                settings::emit_app_ready(webview.app_handle());
+            }
+        })
+        .on_window_event(|window, event| match event {
             // Minimize to tray instead of closing
-            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+            tauri::WindowEvent::CloseRequested { api, .. } => {
+                // Give the frontend a chance to persist any in-progress draft
+                let _ = window.emit("flush-draft", ());
                 let _ = window.hide();
                 api.prevent_close();
             }
+            // Showing the window focuses it too, so this covers both
+            tauri::WindowEvent::Focused(focused) if window.label() == "main" => {
+                alerts::main_window_focused(window.app_handle(), *focused);
+            }
+            _ => {}
         })
         .run(tauri::generate_context!())
         .expect("error while running Stock Advisors");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Tz> {
+        // A Wednesday, in New York so market times are easy to read
+        chrono_tz::America::New_York
+            .with_ymd_and_hms(2024, 3, 13, hour, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn welcome_follows_the_time_of_day() {
+        assert_eq!(
+            welcome_message("Ada", at(9), "en-US"),
+            "Good morning, Ada\nUS markets open in 30m"
+        );
+        assert!(welcome_message("Ada", at(14), "en-US").starts_with("Good afternoon, Ada\n"));
+        assert!(welcome_message("Ada", at(20), "en-US").starts_with("Good evening, Ada\n"));
+        assert!(welcome_message("Ada", at(2), "en-US").starts_with("Good evening"));
+    }
+}
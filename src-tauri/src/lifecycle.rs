@@ -0,0 +1,66 @@
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_sql::{DbInstances, DbPool};
+use tauri_plugin_store::StoreExt;
+
+use crate::monitors::MonitorState;
+use crate::STORE_FILE;
+
+/// Flush anything that would otherwise be lost when the process goes away
+/// without a normal exit: debounced store writes and open SQL connections.
+pub(crate) async fn drain(app: &AppHandle) {
+    if let Ok(store) = app.store(STORE_FILE) {
+        let _ = store.save();
+    }
+
+    let instances = app.state::<DbInstances>();
+    let instances = instances.0.read().await;
+    for db in instances.values() {
+        match db {
+            DbPool::Sqlite(pool) => pool.close().await,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Restart the app so settings that can't be hot-applied take effect.
+///
+/// Returns `false` if the user declined because monitors are running;
+/// otherwise the process restarts and the call never resolves.
+#[tauri::command]
+pub async fn relaunch(app: AppHandle) -> Result<bool, String> {
+    logged!("relaunch", [], async {
+        if app.state::<MonitorState>().any_active() {
+            let mut dialog = app
+                .dialog()
+                .message("Monitors are running and will restart along with the app.")
+                .title("Relaunch Stock Advisors?")
+                .kind(MessageDialogKind::Warning)
+                .buttons(MessageDialogButtons::OkCancelCustom(
+                    "Relaunch".into(),
+                    "Cancel".into(),
+                ));
+
+            // Attach to the window only when it's on screen; a hidden parent
+            // would hide the dialog too
+            if let Some(window) = app.get_webview_window("main") {
+                if window.is_visible().unwrap_or(false) {
+                    dialog = dialog.parent(&window);
+                }
+            }
+
+            let confirmed = tauri::async_runtime::spawn_blocking(move || dialog.blocking_show())
+                .await
+                .map_err(|e| e.to_string())?;
+            if !confirmed {
+                return Ok(false);
+            }
+        }
+
+        drain(&app).await;
+        app.restart()
+    })
+}
@@ -0,0 +1,103 @@
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::America::New_York;
+
+use crate::i18n;
+
+/// Regular NYSE/Nasdaq session, in exchange time. Holidays and early
+/// closes aren't modelled.
+pub(crate) const OPEN: NaiveTime = NaiveTime::from_hms_opt(9, 30, 0).unwrap();
+const CLOSE: NaiveTime = NaiveTime::from_hms_opt(16, 0, 0).unwrap();
+
+fn is_trading_day(day: Weekday) -> bool {
+    !matches!(day, Weekday::Sat | Weekday::Sun)
+}
+
+/// Whether the US regular session is in progress at `now`.
+pub(crate) fn market_is_open(now: DateTime<Utc>) -> bool {
+    let local = now.with_timezone(&New_York);
+    let time = local.time();
+    is_trading_day(local.weekday()) && time >= OPEN && time < CLOSE
+}
+
+/// The next session open strictly after `now`.
+fn next_open(now: DateTime<Utc>) -> DateTime<Utc> {
+    let mut day = now.with_timezone(&New_York).date_naive();
+    loop {
+        if is_trading_day(day.weekday()) {
+            let open = New_York
+                .from_local_datetime(&day.and_time(OPEN))
+                .single()
+                .map(|t| t.with_timezone(&Utc));
+            if let Some(open) = open.filter(|open| *open > now) {
+                return open;
+            }
+        }
+        day = day.succ_opt().expect("date in range");
+    }
+}
+
+/// `45m`, `2h 15m` or `2d 17h`, rounded up to the minute.
+fn until(duration: Duration) -> String {
+    let minutes = (duration.num_seconds() + 59) / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h", days, hours),
+    }
+}
+
+/// One-line summary such as `US markets open in 45m`, in `locale`.
+pub(crate) fn status_line(now: DateTime<Utc>, locale: &str) -> String {
+    if market_is_open(now) {
+        let close = New_York
+            .from_local_datetime(&now.with_timezone(&New_York).date_naive().and_time(CLOSE))
+            .single()
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or(now);
+        let time = until(close - now);
+        i18n::text(locale, "market.closes_in", &[("time", &time)])
+    } else {
+        let time = until(next_open(now) - now);
+        i18n::text(locale, "market.opens_in", &[("time", &time)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A New York wall-clock time as UTC.
+    fn ny(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        New_York
+            .with_ymd_and_hms(y, m, d, h, min, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn open_only_during_the_weekday_session() {
+        // 2024-03-13 is a Wednesday
+        assert!(!market_is_open(ny(2024, 3, 13, 9, 29)));
+        assert!(market_is_open(ny(2024, 3, 13, 9, 30)));
+        assert!(!market_is_open(ny(2024, 3, 13, 16, 0)));
+        assert!(!market_is_open(ny(2024, 3, 16, 12, 0)));
+    }
+
+    #[test]
+    fn status_counts_down_to_the_next_transition() {
+        assert_eq!(
+            status_line(ny(2024, 3, 13, 8, 45), "en-US"),
+            "US markets open in 45m"
+        );
+        assert_eq!(
+            status_line(ny(2024, 3, 13, 13, 45), "en-US"),
+            "US markets close in 2h 15m"
+        );
+        // Friday after the close waits for Monday
+        assert_eq!(
+            status_line(ny(2024, 3, 15, 16, 30), "en-US"),
+            "US markets open in 2d 17h"
+        );
+    }
+}
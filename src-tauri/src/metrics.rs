@@ -0,0 +1,170 @@
+//! Per-command latency, recorded by `logged!` around every fallible
+//! command.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Sub-buckets per power of two, so a percentile is within 1/8 (12.5%) of
+/// the true latency.
+const SUB_BUCKET_BITS: u32 = 3;
+const SUB_BUCKETS: usize = 1 << SUB_BUCKET_BITS;
+/// Top power of two tracked, in microseconds (about 9.5 hours); anything
+/// slower lands in the last bucket.
+const MAX_EXPONENT: u32 = 35;
+const BUCKETS: usize = (MAX_EXPONENT - SUB_BUCKET_BITS + 2) as usize * SUB_BUCKETS;
+
+/// Log-linear buckets of microseconds, as in HDR histograms: exact below 8µs,
+/// then 8 equal buckets per power of two.
+pub(crate) struct Histogram {
+    counts: Box<[u64; BUCKETS]>,
+    total: u64,
+    max_micros: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            counts: Box::new([0; BUCKETS]),
+            total: 0,
+            max_micros: 0,
+        }
+    }
+}
+
+fn bucket(micros: u64) -> usize {
+    if micros < SUB_BUCKETS as u64 {
+        return micros as usize;
+    }
+    if micros >> (MAX_EXPONENT + 1) != 0 {
+        return BUCKETS - 1;
+    }
+    let exponent = 63 - micros.leading_zeros();
+    let sub = (micros >> (exponent - SUB_BUCKET_BITS)) as usize & (SUB_BUCKETS - 1);
+    (exponent - SUB_BUCKET_BITS + 1) as usize * SUB_BUCKETS + sub
+}
+
+/// Midpoint of a bucket, in microseconds.
+fn bucket_value(index: usize) -> u64 {
+    if index < SUB_BUCKETS {
+        return index as u64;
+    }
+    let exponent = (index / SUB_BUCKETS) as u32 + SUB_BUCKET_BITS - 1;
+    let width = 1u64 << (exponent - SUB_BUCKET_BITS);
+    let low = (1u64 << exponent) + (index % SUB_BUCKETS) as u64 * width;
+    low + width / 2
+}
+
+impl Histogram {
+    pub(crate) fn record(&mut self, elapsed: Duration) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        self.counts[bucket(micros)] += 1;
+        self.total += 1;
+        self.max_micros = self.max_micros.max(micros);
+    }
+
+    /// Latency at or below which `pct` percent of calls finished, in
+    /// microseconds.
+    pub(crate) fn percentile(&self, pct: f64) -> u64 {
+        let rank = ((pct / 100.0 * self.total as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return bucket_value(index).min(self.max_micros);
+            }
+        }
+        self.max_micros
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommandMetric {
+    command: &'static str,
+    count: u64,
+    p50_ms: f64,
+    p95_ms: f64,
+    p99_ms: f64,
+    max_ms: f64,
+}
+
+/// A static so `logged!` can record without an app handle. The lock is
+/// held only for a bucket increment.
+static METRICS: Mutex<Option<HashMap<&'static str, Histogram>>> = Mutex::new(None);
+
+pub(crate) fn record(command: &'static str, elapsed: Duration) {
+    let mut metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    metrics
+        .get_or_insert_with(HashMap::new)
+        .entry(command)
+        .or_default()
+        .record(elapsed);
+}
+
+/// Timings per command since launch or the last reset, slowest p95 first.
+pub(crate) fn snapshot() -> Vec<CommandMetric> {
+    let metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    let ms = |micros: u64| micros as f64 / 1000.0;
+    let mut out: Vec<CommandMetric> = metrics
+        .iter()
+        .flatten()
+        .map(|(command, h)| CommandMetric {
+            command,
+            count: h.total,
+            p50_ms: ms(h.percentile(50.0)),
+            p95_ms: ms(h.percentile(95.0)),
+            p99_ms: ms(h.percentile(99.0)),
+            max_ms: ms(h.max_micros),
+        })
+        .collect();
+    out.sort_by(|a, b| b.p95_ms.total_cmp(&a.p95_ms).then(a.command.cmp(b.command)));
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn command_metrics() -> Vec<CommandMetric> {
+    snapshot()
+}
+
+#[tauri::command]
+pub fn reset_command_metrics() {
+    *METRICS.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_are_within_the_bucket_error() {
+        let mut h = Histogram::default();
+        for ms in 1..=100 {
+            h.record(Duration::from_millis(ms));
+        }
+        for (pct, expected) in [(50.0, 50_000.0), (95.0, 95_000.0), (99.0, 99_000.0)] {
+            let got = h.percentile(pct) as f64;
+            assert!(
+                (got - expected).abs() / expected <= 0.125,
+                "p{}: {}",
+                pct,
+                got
+            );
+        }
+        assert_eq!(h.percentile(100.0), 100_000);
+    }
+
+    #[test]
+    fn buckets_cover_the_whole_range() {
+        assert_eq!(bucket(0), 0);
+        assert_eq!(bucket(7), 7);
+        assert_eq!(bucket_value(bucket(7)), 7);
+        assert!(bucket(8) < bucket(9_000));
+        assert_eq!(bucket(u64::MAX), BUCKETS - 1);
+    }
+}
@@ -0,0 +1,128 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::tray;
+
+/// Backend mirror of the frontend monitors' ON/OFF state, kept in sync by
+/// the tray toggles. `paused` overrides both without touching their
+/// toggles, so resuming restores whatever was on before.
+pub struct MonitorState {
+    position: AtomicBool,
+    news: AtomicBool,
+    paused: AtomicBool,
+}
+
+impl Default for MonitorState {
+    fn default() -> Self {
+        // Both tray items start out "ON"
+        Self {
+            position: AtomicBool::new(true),
+            news: AtomicBool::new(true),
+            paused: AtomicBool::new(false),
+        }
+    }
+}
+
+impl MonitorState {
+    pub fn position(&self) -> bool {
+        self.position.load(Ordering::Relaxed)
+    }
+
+    pub fn news(&self) -> bool {
+        self.news.load(Ordering::Relaxed)
+    }
+
+    pub fn set_position(&self, on: bool) {
+        self.position.store(on, Ordering::Relaxed);
+    }
+
+    pub fn set_news(&self, on: bool) {
+        self.news.store(on, Ordering::Relaxed);
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Returns `false` if the flag was already set that way.
+    pub fn set_paused(&self, paused: bool) -> bool {
+        self.paused.swap(paused, Ordering::Relaxed) != paused
+    }
+
+    pub fn any_active(&self) -> bool {
+        !self.paused() && (self.position() || self.news())
+    }
+}
+
+/// Flip a monitor, returning its new state and the ON/OFF word for its
+/// tray label.
+pub(crate) fn toggle_monitor(current_on: bool) -> (bool, &'static str) {
+    if current_on {
+        (false, "OFF")
+    } else {
+        (true, "ON")
+    }
+}
+
+fn set_paused(app: &AppHandle, state: &MonitorState, paused: bool) {
+    if state.set_paused(paused) {
+        tray::sync_tooltip(app, paused);
+        let event = if paused {
+            "monitors-paused"
+        } else {
+            "monitors-resumed"
+        };
+        let _ = app.emit(event, ());
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Stop the position and news monitors and scheduled jobs at once. The
+/// frontend loops keep their alert state and just skip work while paused.
+#[tauri::command]
+pub fn pause_all_monitors(app: AppHandle, state: State<'_, MonitorState>) {
+    set_paused(&app, &state, true);
+}
+
+#[tauri::command]
+pub fn resume_all_monitors(app: AppHandle, state: State<'_, MonitorState>) {
+    set_paused(&app, &state, false);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_flips_state_and_label() {
+        assert_eq!(toggle_monitor(true), (false, "OFF"));
+        assert_eq!(toggle_monitor(false), (true, "ON"));
+    }
+
+    #[test]
+    fn monitors_start_on() {
+        let state = MonitorState::default();
+        assert!(state.position() && state.news());
+
+        state.set_position(false);
+        assert!(state.any_active());
+        state.set_news(false);
+        assert!(!state.any_active());
+    }
+
+    #[test]
+    fn pause_overrides_toggles_until_resumed() {
+        let state = MonitorState::default();
+        assert!(state.set_paused(true));
+        assert!(!state.set_paused(true));
+        assert!(!state.any_active());
+        assert!(state.position() && state.news());
+
+        assert!(state.set_paused(false));
+        assert!(state.any_active());
+    }
+}
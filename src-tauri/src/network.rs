@@ -0,0 +1,92 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Probe the market data host itself: reaching it is what the monitors
+/// actually need, regardless of what the OS reports.
+const PROBE_ADDR: &str = "www.alphavantage.co:443";
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+const ONLINE_INTERVAL: Duration = Duration::from_secs(30);
+const OFFLINE_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Serialize)]
+pub struct NetworkStatus {
+    online: bool,
+    last_online: Option<String>,
+}
+
+pub struct NetworkState {
+    online: AtomicBool,
+    last_online: Mutex<Option<String>>,
+}
+
+impl Default for NetworkState {
+    fn default() -> Self {
+        // Assume online until the first probe says otherwise
+        Self {
+            online: AtomicBool::new(true),
+            last_online: Mutex::new(None),
+        }
+    }
+}
+
+impl NetworkState {
+    pub fn is_online(&self) -> bool {
+        self.online.load(Ordering::Relaxed)
+    }
+
+    fn status(&self) -> NetworkStatus {
+        NetworkStatus {
+            online: self.is_online(),
+            last_online: self.last_online.lock().unwrap().clone(),
+        }
+    }
+
+    /// Record a probe result, returning true if the online flag flipped.
+    fn record(&self, online: bool) -> bool {
+        if online {
+            *self.last_online.lock().unwrap() = Some(chrono::Utc::now().to_rfc3339());
+        }
+        self.online.swap(online, Ordering::Relaxed) != online
+    }
+}
+
+async fn probe() -> bool {
+    matches!(
+        tokio::time::timeout(PROBE_TIMEOUT, tokio::net::TcpStream::connect(PROBE_ADDR)).await,
+        Ok(Ok(_))
+    )
+}
+
+/// Periodically probe connectivity and emit `network-status` on changes so
+/// the frontend monitors can pause while offline.
+pub fn spawn_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let online = probe().await;
+            let state = app.state::<NetworkState>();
+            if state.record(online) {
+                let _ = app.emit("network-status", state.status());
+            }
+
+            let interval = if online {
+                ONLINE_INTERVAL
+            } else {
+                OFFLINE_INTERVAL
+            };
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_network_status(state: State<'_, NetworkState>) -> NetworkStatus {
+    state.status()
+}
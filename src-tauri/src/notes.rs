@@ -0,0 +1,146 @@
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter};
+
+use crate::actions::{self, Restore};
+use crate::db;
+
+const MAX_NOTE_CHARS: usize = 10_000;
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct SymbolNote {
+    symbol: String,
+    note: String,
+    updated_at: String,
+}
+
+#[derive(Clone, Serialize)]
+struct NoteChangedPayload {
+    symbol: String,
+}
+
+// ---------------------------------------------------------------------------
+// Queries
+// ---------------------------------------------------------------------------
+
+/// Upsert the thesis note for `symbol`, or remove it if `note` is blank.
+/// Returns the normalized symbol. The previous note is kept for undo.
+pub(crate) async fn write_note(
+    pool: &SqlitePool,
+    symbol: &str,
+    note: &str,
+) -> Result<String, String> {
+    let symbol = db::clean_symbol(symbol)?;
+    let note = note.trim();
+    if note.chars().count() > MAX_NOTE_CHARS {
+        return Err(format!("Note exceeds {} characters", MAX_NOTE_CHARS));
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let previous = Restore::capture(&mut tx, "symbol_notes", vec![symbol.clone().into()]).await?;
+    if note.is_empty() {
+        sqlx::query("DELETE FROM symbol_notes WHERE symbol = ?")
+            .bind(&symbol)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    } else {
+        sqlx::query(
+            "INSERT INTO symbol_notes (symbol, note, updated_at) VALUES (?, ?, datetime('now')) \
+             ON CONFLICT(symbol) DO UPDATE SET note = excluded.note, updated_at = excluded.updated_at",
+        )
+        .bind(&symbol)
+        .bind(note)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+    let description = format!("Edited the note for {}", symbol);
+    actions::record(&mut tx, &description, Some(vec![previous])).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(symbol)
+}
+
+pub(crate) async fn read_note(
+    pool: &SqlitePool,
+    symbol: &str,
+) -> Result<Option<SymbolNote>, String> {
+    let symbol = db::clean_symbol(symbol)?;
+
+    sqlx::query_as("SELECT symbol, note, updated_at FROM symbol_notes WHERE symbol = ?")
+        .bind(&symbol)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Upsert the thesis note for `symbol`. An empty note removes it.
+#[tauri::command]
+pub async fn set_symbol_note(app: AppHandle, symbol: String, note: String) -> Result<(), String> {
+    logged!("set_symbol_note", [symbol], async {
+        let pool = db::pool(&app).await?;
+        let symbol = write_note(&pool, &symbol, &note).await?;
+
+        let _ = app.emit("note-changed", NoteChangedPayload { symbol });
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub async fn get_symbol_note(app: AppHandle, symbol: String) -> Result<Option<SymbolNote>, String> {
+    logged!("get_symbol_note", [symbol], async {
+        let pool = db::pool(&app).await?;
+        read_note(&pool, &symbol).await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing;
+
+    #[tokio::test]
+    async fn write_then_read_normalizes_symbol() {
+        let pool = testing::pool().await;
+
+        let symbol = write_note(&pool, " aapl ", "  services growth  ")
+            .await
+            .unwrap();
+        assert_eq!(symbol, "AAPL");
+
+        let note = read_note(&pool, "AAPL").await.unwrap().unwrap();
+        assert_eq!(note.note, "services growth");
+    }
+
+    #[tokio::test]
+    async fn write_replaces_existing_note() {
+        let pool = testing::pool().await;
+        write_note(&pool, "MSFT", "first").await.unwrap();
+        write_note(&pool, "MSFT", "second").await.unwrap();
+
+        let note = read_note(&pool, "msft").await.unwrap().unwrap();
+        assert_eq!(note.note, "second");
+    }
+
+    #[tokio::test]
+    async fn blank_note_deletes() {
+        let pool = testing::pool().await;
+        write_note(&pool, "NVDA", "thesis").await.unwrap();
+        write_note(&pool, "NVDA", "   ").await.unwrap();
+
+        assert!(read_note(&pool, "NVDA").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_note() {
+        let pool = testing::pool().await;
+        let note = "x".repeat(MAX_NOTE_CHARS + 1);
+
+        assert!(write_note(&pool, "AAPL", &note).await.is_err());
+        assert!(read_note(&pool, "AAPL").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,304 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::plugin::PermissionState;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::i18n;
+use crate::settings::{self, SettingsPatch};
+
+/// Web Notification API names, which the frontend already understands.
+/// Desktop platforms without a permission model report `granted`.
+fn permission_name(state: PermissionState) -> &'static str {
+    match state {
+        PermissionState::Granted => "granted",
+        PermissionState::Denied => "denied",
+        PermissionState::Prompt | PermissionState::PromptWithRationale => "default",
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Rate limiting
+// ---------------------------------------------------------------------------
+
+/// Token bucket refilled continuously at `per_minute`. Notifications turned
+/// away while it's empty are counted so they can be summarized later.
+pub(crate) struct RateLimiter {
+    per_minute: u32,
+    tokens: f64,
+    updated: Instant,
+    held_back: u32,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(per_minute: u32, now: Instant) -> Self {
+        Self {
+            per_minute,
+            tokens: per_minute as f64,
+            updated: now,
+            held_back: 0,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * self.per_minute as f64 / 60.0).min(self.per_minute as f64);
+        self.updated = now;
+    }
+
+    pub(crate) fn set_rate(&mut self, per_minute: u32, now: Instant) {
+        self.refill(now);
+        self.per_minute = per_minute;
+        self.tokens = self.tokens.min(per_minute as f64);
+    }
+
+    /// Take a token, or count the notification as held back.
+    pub(crate) fn try_acquire(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            self.held_back += 1;
+            false
+        }
+    }
+
+    /// Once a token is available, spend it on a summary of everything held
+    /// back and return how many that was.
+    pub(crate) fn flush(&mut self, now: Instant) -> Option<u32> {
+        self.refill(now);
+        if self.held_back == 0 || self.tokens < 1.0 {
+            return None;
+        }
+        self.tokens -= 1.0;
+        Some(std::mem::take(&mut self.held_back))
+    }
+
+    fn refill_interval(&self) -> Duration {
+        Duration::from_secs_f64(60.0 / self.per_minute as f64)
+    }
+}
+
+pub struct NotificationLimiter(Mutex<RateLimiter>);
+
+impl Default for NotificationLimiter {
+    fn default() -> Self {
+        let per_minute = settings::Settings::default().notifications_per_minute;
+        Self(Mutex::new(RateLimiter::new(per_minute, Instant::now())))
+    }
+}
+
+impl NotificationLimiter {
+    pub fn set_rate(&self, per_minute: u32) {
+        let mut limiter = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        limiter.set_rate(per_minute, Instant::now());
+    }
+}
+
+fn show(app: &AppHandle, title: &str, body: &str, icon: Option<String>) -> Result<(), String> {
+    let mut builder = app.notification().builder().title(title).body(body);
+    if let Some(icon) = icon {
+        builder = builder.icon(icon);
+    }
+    builder
+        .show()
+        .map_err(|e| format!("Failed to send notification: {}", e))
+}
+
+/// Send a notification unless the rate cap has been hit. The first one held
+/// back starts a task that sends a single roundup once the bucket refills.
+/// Returns whether this notification was shown.
+pub(crate) fn show_limited(app: &AppHandle, title: &str, body: &str) -> Result<bool, String> {
+    show_limited_with_icon(app, title, body, None)
+}
+
+/// [`show_limited`] with an icon file in place of the app's own, on the
+/// platforms that allow one. The rest ignore it.
+pub(crate) fn show_limited_with_icon(
+    app: &AppHandle,
+    title: &str,
+    body: &str,
+    icon: Option<String>,
+) -> Result<bool, String> {
+    let limiter = app.state::<NotificationLimiter>();
+    let (admitted, start_flush, wait) = {
+        let mut limiter = limiter.0.lock().unwrap_or_else(|e| e.into_inner());
+        let admitted = limiter.try_acquire(Instant::now());
+        (
+            admitted,
+            !admitted && limiter.held_back == 1,
+            limiter.refill_interval(),
+        )
+    };
+
+    if admitted {
+        show(app, title, body, icon)?;
+    } else if start_flush {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(wait).await;
+                let limiter = app.state::<NotificationLimiter>();
+                let flushed = limiter
+                    .0
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .flush(Instant::now());
+                if let Some(count) = flushed {
+                    let body =
+                        i18n::user_text(&app, "notify.held_back", &[("count", &count.to_string())]);
+                    let _ = show(&app, "Stock Advisors", &body, None);
+                    break;
+                }
+            }
+        });
+    }
+    Ok(admitted)
+}
+
+/// Analysis results only warrant a notification when the user opted in and
+/// isn't already looking at them.
+fn should_notify_analysis(opted_in: bool, window_focused: bool) -> bool {
+    opted_in && !window_focused
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Ask the OS for notification permission. Returns whether it was granted.
+#[tauri::command]
+pub async fn request_notification_permission(app: AppHandle) -> Result<bool, String> {
+    logged!("request_notification_permission", [], async {
+        let state = app
+            .notification()
+            .request_permission()
+            .map_err(|e| e.to_string())?;
+        Ok(state == PermissionState::Granted)
+    })
+}
+
+#[tauri::command]
+pub fn notification_permission_state(app: AppHandle) -> Result<String, String> {
+    logged!("notification_permission_state", [], {
+        let state = app
+            .notification()
+            .permission_state()
+            .map_err(|e| e.to_string())?;
+        Ok(permission_name(state).to_string())
+    })
+}
+
+/// Send a sample notification so the settings screen can confirm alerts
+/// will actually reach the user.
+#[tauri::command]
+pub fn test_notification(app: AppHandle) -> Result<(), String> {
+    logged!("test_notification", [], {
+        let notification = app.notification();
+
+        match notification.permission_state().map_err(|e| e.to_string())? {
+            PermissionState::Granted => {}
+            PermissionState::Denied => {
+                return Err(
+                    "Notifications are blocked for Stock Advisors in system settings".into(),
+                )
+            }
+            PermissionState::Prompt | PermissionState::PromptWithRationale => {
+                return Err("Notification permission hasn't been granted yet".into())
+            }
+        }
+
+        notification
+            .builder()
+            .title("Stock Advisors")
+            .body(i18n::user_text(&app, "notify.test", &[]))
+            .show()
+            .map_err(|e| format!("Failed to send notification: {}", e))
+    })
+}
+
+/// Tell the user an analysis run finished, unless they turned this off or
+/// the main window is focused. Returns whether a notification was sent.
+#[tauri::command]
+pub fn notify_analysis_complete(
+    app: AppHandle,
+    symbol: String,
+    summary: String,
+) -> Result<bool, String> {
+    logged!("notify_analysis_complete", [symbol], {
+        let focused = app
+            .get_webview_window("main")
+            .and_then(|w| w.is_focused().ok())
+            .unwrap_or(false);
+        let opted_in = settings::load_settings(&app).notify_on_analysis_complete;
+        if !should_notify_analysis(opted_in, focused) {
+            return Ok(false);
+        }
+
+        let title = i18n::user_text(&app, "notify.analysis_complete", &[("symbol", &symbol)]);
+        show_limited(&app, &title, &summary)
+    })
+}
+
+/// Cap how many notifications can be shown per minute. Anything over the
+/// cap is rolled into one summary notification.
+#[tauri::command]
+pub fn set_notification_rate(app: AppHandle, per_minute: u32) -> Result<(), String> {
+    logged!("set_notification_rate", [per_minute], {
+        let patch = SettingsPatch {
+            notifications_per_minute: Some(per_minute),
+            ..Default::default()
+        };
+        settings::update_settings(&app, patch).map(|_| ())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pending_prompts_report_default() {
+        assert_eq!(permission_name(PermissionState::Granted), "granted");
+        assert_eq!(permission_name(PermissionState::Denied), "denied");
+        assert_eq!(permission_name(PermissionState::Prompt), "default");
+        assert_eq!(
+            permission_name(PermissionState::PromptWithRationale),
+            "default"
+        );
+    }
+
+    #[test]
+    fn burst_past_cap_flushes_one_summary() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(10, start);
+
+        let admitted = (0..15).filter(|_| limiter.try_acquire(start)).count();
+        assert_eq!(admitted, 10);
+        assert_eq!(limiter.flush(start), None);
+
+        let later = start + Duration::from_secs(6);
+        assert_eq!(limiter.flush(later), Some(5));
+        assert_eq!(limiter.flush(later), None);
+    }
+
+    #[test]
+    fn bucket_refills_at_the_configured_rate() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(2, start);
+        assert!(limiter.try_acquire(start));
+        assert!(limiter.try_acquire(start));
+        assert!(!limiter.try_acquire(start));
+        assert!(limiter.try_acquire(start + Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn analysis_notification_skips_focused_window() {
+        assert!(should_notify_analysis(true, false));
+        assert!(!should_notify_analysis(true, true));
+        assert!(!should_notify_analysis(false, false));
+    }
+}
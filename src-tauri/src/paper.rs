@@ -0,0 +1,1086 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use tauri::{AppHandle, Emitter};
+
+use crate::risk::{self, RiskLimits};
+use crate::{db, settings};
+
+/// Reg NMS sub-penny rule: quotes of $1.00 and up trade in whole cents.
+const PENNY_TICK: f64 = 0.01;
+const SUB_PENNY_TICK: f64 = 0.0001;
+
+#[derive(Clone, Deserialize)]
+pub struct OrderTicket {
+    symbol: String,
+    /// `buy` or `sell`.
+    side: String,
+    quantity: i64,
+    /// `market`, `limit`, `stop` or `stop_limit`.
+    order_type: String,
+    limit_price: Option<f64>,
+    stop_price: Option<f64>,
+}
+
+#[derive(Deserialize)]
+pub struct AccountState {
+    pub(crate) cash: f64,
+    /// Shares held per symbol.
+    pub(crate) positions: HashMap<String, i64>,
+    /// Latest quote for the ticket's symbol. Not needed when checking
+    /// resting orders, which fill at the quotes they're checked against.
+    #[serde(default)]
+    pub(crate) market_price: f64,
+    /// Quotes for the other held symbols. Only needed for the position and
+    /// leverage risk limits.
+    #[serde(default)]
+    pub(crate) quotes: HashMap<String, f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidatedOrder {
+    pub(crate) symbol: String,
+    pub(crate) side: String,
+    pub(crate) quantity: i64,
+    pub(crate) order_type: String,
+    pub(crate) limit_price: Option<f64>,
+    pub(crate) stop_price: Option<f64>,
+    /// Worst-case cost of a buy, or proceeds of a sell at the expected price.
+    pub(crate) estimated_value: f64,
+}
+
+fn tick_size(price: f64) -> f64 {
+    if price >= 1.0 {
+        PENNY_TICK
+    } else {
+        SUB_PENNY_TICK
+    }
+}
+
+fn round_to_tick(price: f64) -> f64 {
+    let tick = tick_size(price);
+    // Divide by the reciprocal so whole ticks come out as exact decimals
+    (price / tick).round() / (1.0 / tick).round()
+}
+
+fn on_tick(price: f64) -> bool {
+    let ticks = price / tick_size(price);
+    (ticks - ticks.round()).abs() < 1e-6
+}
+
+/// Check a ticket against the account, collecting every problem rather than
+/// stopping at the first so the form can show them all.
+pub(crate) fn validate(
+    order: &OrderTicket,
+    account: &AccountState,
+) -> Result<ValidatedOrder, Vec<String>> {
+    let mut problems = Vec::new();
+
+    let symbol = db::clean_symbol(&order.symbol).unwrap_or_else(|e| {
+        problems.push(e);
+        String::new()
+    });
+    let buying = match order.side.as_str() {
+        "buy" => true,
+        "sell" => false,
+        other => {
+            problems.push(format!("Unknown side '{}', expected buy or sell", other));
+            true
+        }
+    };
+    if order.quantity <= 0 {
+        problems.push("Quantity must be at least 1 share".into());
+    }
+    if !account.market_price.is_finite() || account.market_price <= 0.0 {
+        problems.push("No valid market price for this symbol".into());
+    }
+
+    let (needs_limit, needs_stop) = match order.order_type.as_str() {
+        "market" => (false, false),
+        "limit" => (true, false),
+        "stop" => (false, true),
+        "stop_limit" => (true, true),
+        other => {
+            problems.push(format!(
+                "Unknown order type '{}', expected market, limit, stop or stop_limit",
+                other
+            ));
+            (false, false)
+        }
+    };
+    for (name, price, needed) in [
+        ("Limit", order.limit_price, needs_limit),
+        ("Stop", order.stop_price, needs_stop),
+    ] {
+        match (price, needed) {
+            (None, true) => problems.push(format!("{} price is required", name)),
+            (Some(_), false) => problems.push(format!(
+                "{} price doesn't apply to a {} order",
+                name, order.order_type
+            )),
+            (Some(p), true) if !p.is_finite() || p <= 0.0 => {
+                problems.push(format!("{} price must be positive", name))
+            }
+            (Some(p), true) if !on_tick(p) => problems.push(format!(
+                "{} price {} isn't a multiple of the ${} tick",
+                name,
+                p,
+                tick_size(p)
+            )),
+            _ => {}
+        }
+    }
+
+    let market = account.market_price;
+    if let Some(stop) = order.stop_price.filter(|_| needs_stop) {
+        if buying && stop <= market {
+            problems.push(format!(
+                "Buy stop {} must be above the market ({})",
+                stop, market
+            ));
+        }
+        if !buying && stop >= market {
+            problems.push(format!(
+                "Sell stop {} must be below the market ({})",
+                stop, market
+            ));
+        }
+        if let Some(limit) = order.limit_price.filter(|_| needs_limit) {
+            if buying && limit < stop {
+                problems.push("Buy stop-limit needs a limit at or above the stop".into());
+            }
+            if !buying && limit > stop {
+                problems.push("Sell stop-limit needs a limit at or below the stop".into());
+            }
+        }
+    }
+
+    let expected_price = order
+        .limit_price
+        .filter(|_| needs_limit)
+        .or(order.stop_price.filter(|_| needs_stop))
+        .unwrap_or(market);
+    let estimated_value = expected_price * order.quantity.max(0) as f64;
+
+    if buying && estimated_value > account.cash {
+        problems.push(format!(
+            "Not enough buying power: needs {:.2}, have {:.2}",
+            estimated_value, account.cash
+        ));
+    }
+    if !buying {
+        let held = account.positions.get(&symbol).copied().unwrap_or(0);
+        if order.quantity > held {
+            problems.push(format!(
+                "Can't sell {} shares of {}, only {} held",
+                order.quantity, symbol, held
+            ));
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+    Ok(ValidatedOrder {
+        symbol,
+        side: order.side.clone(),
+        quantity: order.quantity,
+        order_type: order.order_type.clone(),
+        limit_price: order.limit_price,
+        stop_price: order.stop_price,
+        estimated_value,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Fill engine
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Fill {
+    id: i64,
+    order_id: i64,
+    symbol: String,
+    side: String,
+    quantity: i64,
+    price: f64,
+    filled_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PaperExecution {
+    order_id: i64,
+    /// `filled` for market orders, `open` for orders left resting.
+    status: &'static str,
+    fill: Option<Fill>,
+}
+
+#[derive(sqlx::FromRow)]
+struct RestingOrder {
+    id: i64,
+    symbol: String,
+    side: String,
+    remaining: i64,
+    order_type: String,
+    limit_price: Option<f64>,
+    stop_price: Option<f64>,
+    triggered: bool,
+}
+
+/// Market fill price after slippage, which always goes against the trader.
+pub(crate) fn slipped(market: f64, slippage_bps: u32, buying: bool) -> f64 {
+    let slip = market * slippage_bps as f64 / 10_000.0;
+    round_to_tick(if buying { market + slip } else { market - slip })
+}
+
+/// What a resting order does at `market`: whether its stop has now been hit,
+/// and the fill price if it executes. Stops become market orders; limits
+/// fill at the market when it's at or through the limit.
+fn evaluate(order: &RestingOrder, market: f64, slippage_bps: u32) -> (bool, Option<f64>) {
+    let buying = order.side == "buy";
+    let limit_fill = |limit: f64| {
+        let through = if buying {
+            market <= limit
+        } else {
+            market >= limit
+        };
+        through.then_some(market)
+    };
+
+    let stop_hit = order.triggered
+        || order.stop_price.is_some_and(|stop| {
+            if buying {
+                market >= stop
+            } else {
+                market <= stop
+            }
+        });
+
+    match (order.order_type.as_str(), order.limit_price) {
+        ("limit", Some(limit)) => (false, limit_fill(limit)),
+        ("stop", _) => (
+            stop_hit,
+            stop_hit.then(|| slipped(market, slippage_bps, buying)),
+        ),
+        ("stop_limit", Some(limit)) => (stop_hit, limit_fill(limit).filter(|_| stop_hit)),
+        _ => (false, None),
+    }
+}
+
+async fn insert_order(
+    tx: &mut Transaction<'_, Sqlite>,
+    order: &ValidatedOrder,
+    status: &str,
+) -> Result<i64, String> {
+    sqlx::query_scalar(
+        "INSERT INTO paper_orders (symbol, side, quantity, order_type, limit_price, stop_price, status) \
+         VALUES (?, ?, ?, ?, ?, ?, ?) RETURNING id",
+    )
+    .bind(&order.symbol)
+    .bind(&order.side)
+    .bind(order.quantity)
+    .bind(&order.order_type)
+    .bind(order.limit_price)
+    .bind(order.stop_price)
+    .bind(status)
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Record a fill of `quantity`, marking the order filled once nothing is
+/// left of it.
+async fn record_fill(
+    tx: &mut Transaction<'_, Sqlite>,
+    order_id: i64,
+    symbol: &str,
+    side: &str,
+    quantity: i64,
+    price: f64,
+) -> Result<Fill, String> {
+    sqlx::query(
+        "UPDATE paper_orders SET filled_quantity = filled_quantity + ?1, \
+         status = CASE WHEN filled_quantity + ?1 >= quantity THEN 'filled' ELSE status END \
+         WHERE id = ?2",
+    )
+    .bind(quantity)
+    .bind(order_id)
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    sqlx::query_as(
+        "INSERT INTO paper_fills (order_id, symbol, side, quantity, price) VALUES (?, ?, ?, ?, ?) \
+         RETURNING id, order_id, symbol, side, quantity, price, filled_at",
+    )
+    .bind(order_id)
+    .bind(symbol)
+    .bind(side)
+    .bind(quantity)
+    .bind(price)
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Fill a market order now, or leave any other type resting. A market buy
+/// must be affordable at its slipped fill price, which `validate` can't
+/// know.
+pub(crate) async fn place(
+    pool: &SqlitePool,
+    order: &ValidatedOrder,
+    account: &AccountState,
+    slippage_bps: u32,
+) -> Result<PaperExecution, String> {
+    let market_fill = (order.order_type == "market")
+        .then(|| slipped(account.market_price, slippage_bps, order.side == "buy"));
+    if let Some(price) = market_fill.filter(|_| order.side == "buy") {
+        let cost = price * order.quantity as f64;
+        if cost > account.cash {
+            return Err(format!(
+                "Not enough buying power at the {:.2} fill price: needs {:.2}, have {:.2}",
+                price, cost, account.cash
+            ));
+        }
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let execution = if let Some(price) = market_fill {
+        let order_id = insert_order(&mut tx, order, "open").await?;
+        let fill = record_fill(
+            &mut tx,
+            order_id,
+            &order.symbol,
+            &order.side,
+            order.quantity,
+            price,
+        )
+        .await?;
+        PaperExecution {
+            order_id,
+            status: "filled",
+            fill: Some(fill),
+        }
+    } else {
+        PaperExecution {
+            order_id: insert_order(&mut tx, order, "open").await?,
+            status: "open",
+            fill: None,
+        }
+    };
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(execution)
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RejectedOrder {
+    order_id: i64,
+    problems: Vec<String>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Matched {
+    pub(crate) fills: Vec<Fill>,
+    /// Buys cancelled instead of filled because they broke a risk limit or
+    /// the cash ran out.
+    pub(crate) rejected: Vec<RejectedOrder>,
+}
+
+/// What resting buys are checked against as they fill. The account moves
+/// with each fill, so later orders in a pass see the earlier ones.
+pub(crate) struct RestingRisk {
+    pub(crate) limits: RiskLimits,
+    pub(crate) account: AccountState,
+    pub(crate) loss_today: f64,
+}
+
+impl RestingRisk {
+    fn review(
+        &mut self,
+        order: &RestingOrder,
+        quantity: i64,
+        price: f64,
+        market: f64,
+    ) -> Vec<String> {
+        let cost = price * quantity as f64;
+        let mut problems = Vec::new();
+        if cost > self.account.cash {
+            problems.push(format!(
+                "Not enough buying power: needs {:.2}, have {:.2}",
+                cost, self.account.cash
+            ));
+        }
+        let fill = ValidatedOrder {
+            symbol: order.symbol.clone(),
+            side: order.side.clone(),
+            quantity,
+            order_type: order.order_type.clone(),
+            limit_price: order.limit_price,
+            stop_price: order.stop_price,
+            estimated_value: cost,
+        };
+        self.account.market_price = market;
+        problems.extend(risk::check(
+            &self.limits,
+            &fill,
+            &self.account,
+            self.loss_today,
+        ));
+        problems
+    }
+
+    fn record(&mut self, fill: &Fill) {
+        let value = fill.quantity as f64 * fill.price;
+        let held = self
+            .account
+            .positions
+            .entry(fill.symbol.clone())
+            .or_default();
+        if fill.side == "buy" {
+            *held += fill.quantity;
+            self.account.cash -= value;
+        } else {
+            *held -= fill.quantity;
+            self.account.cash += value;
+        }
+    }
+}
+
+/// Check every open order against `quotes` and fill the ones whose
+/// conditions are met. Symbols without a quote are left alone.
+///
+/// `sizes` is the share count available at each quote. Orders filling at
+/// their limit take no more than what is left of it, oldest first, and stay
+/// open for the rest; without a size they fill in full. Triggered stops
+/// are market orders and always fill in full.
+///
+/// Each buy is checked against `risk` before it fills, like a new ticket
+/// would be. One that fails is cancelled rather than left to retry.
+pub(crate) async fn match_resting(
+    pool: &SqlitePool,
+    quotes: &HashMap<String, f64>,
+    sizes: &HashMap<String, i64>,
+    slippage_bps: u32,
+    risk: &mut RestingRisk,
+) -> Result<Matched, String> {
+    risk.account.quotes.extend(quotes.clone());
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let orders: Vec<RestingOrder> = sqlx::query_as(
+        "SELECT id, symbol, side, quantity - filled_quantity AS remaining, order_type, limit_price, stop_price, triggered \
+         FROM paper_orders WHERE status = 'open' ORDER BY id",
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut available = sizes.clone();
+    let mut fills = Vec::new();
+    let mut rejected = Vec::new();
+    for order in orders {
+        let Some(&market) = quotes.get(&order.symbol) else {
+            continue;
+        };
+        let (triggered, price) = evaluate(&order, market, slippage_bps);
+
+        if let Some(price) = price {
+            let size = available
+                .get_mut(&order.symbol)
+                .filter(|_| order.limit_price.is_some());
+            let quantity = size
+                .as_deref()
+                .map_or(order.remaining, |size| order.remaining.min(*size));
+            if quantity > 0 {
+                if order.side == "buy" {
+                    let problems = risk.review(&order, quantity, price, market);
+                    if !problems.is_empty() {
+                        sqlx::query("UPDATE paper_orders SET status = 'cancelled' WHERE id = ?")
+                            .bind(order.id)
+                            .execute(&mut *tx)
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        rejected.push(RejectedOrder {
+                            order_id: order.id,
+                            problems,
+                        });
+                        continue;
+                    }
+                }
+                if let Some(size) = size {
+                    *size -= quantity;
+                }
+                let fill = record_fill(
+                    &mut tx,
+                    order.id,
+                    &order.symbol,
+                    &order.side,
+                    quantity,
+                    price,
+                )
+                .await?;
+                risk.record(&fill);
+                fills.push(fill);
+            }
+        }
+        if triggered && !order.triggered {
+            sqlx::query("UPDATE paper_orders SET triggered = 1 WHERE id = ?")
+                .bind(order.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(Matched { fills, rejected })
+}
+
+// ---------------------------------------------------------------------------
+// Order book
+// ---------------------------------------------------------------------------
+
+const ORDER_STATUSES: &[&str] = &["open", "filled", "cancelled"];
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct PaperOrder {
+    id: i64,
+    symbol: String,
+    side: String,
+    quantity: i64,
+    order_type: String,
+    limit_price: Option<f64>,
+    stop_price: Option<f64>,
+    status: String,
+    filled_quantity: i64,
+    /// Whether a stop or stop-limit has seen its stop price.
+    triggered: bool,
+    created_at: String,
+    #[sqlx(skip)]
+    fills: Vec<Fill>,
+}
+
+/// Paper orders newest first, each with its fills.
+pub(crate) async fn load_orders(
+    pool: &SqlitePool,
+    status: Option<&str>,
+) -> Result<Vec<PaperOrder>, String> {
+    if let Some(status) = status {
+        if !ORDER_STATUSES.contains(&status) {
+            return Err(format!(
+                "Status must be one of: {}",
+                ORDER_STATUSES.join(", ")
+            ));
+        }
+    }
+
+    let mut orders: Vec<PaperOrder> = sqlx::query_as(
+        "SELECT id, symbol, side, quantity, order_type, limit_price, stop_price, status, \
+         filled_quantity, triggered, created_at \
+         FROM paper_orders WHERE ?1 IS NULL OR status = ?1 ORDER BY id DESC",
+    )
+    .bind(status)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let fills: Vec<Fill> = sqlx::query_as(
+        "SELECT id, order_id, symbol, side, quantity, price, filled_at FROM paper_fills ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut by_order: HashMap<i64, Vec<Fill>> = HashMap::new();
+    for fill in fills {
+        by_order.entry(fill.order_id).or_default().push(fill);
+    }
+    for order in &mut orders {
+        order.fills = by_order.remove(&order.id).unwrap_or_default();
+    }
+    Ok(orders)
+}
+
+/// Cancel an open order. Anything already filled stays filled; only the
+/// unfilled remainder stops working.
+pub(crate) async fn cancel(pool: &SqlitePool, id: i64) -> Result<(), String> {
+    let updated = sqlx::query(
+        "UPDATE paper_orders SET status = 'cancelled' WHERE id = ? AND status = 'open'",
+    )
+    .bind(id)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .rows_affected();
+    if updated > 0 {
+        return Ok(());
+    }
+
+    let status: Option<String> = sqlx::query_scalar("SELECT status FROM paper_orders WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    match status {
+        Some(status) => Err(format!("Order {} is already {}", id, status)),
+        None => Err(format!("No paper order with id {}", id)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Validate a paper-trading order ticket, including the configured risk
+/// limits. Rejections are expected user input, so they go back as a list of
+/// problems without being logged.
+#[tauri::command]
+pub async fn validate_order(
+    app: AppHandle,
+    order: OrderTicket,
+    account: AccountState,
+) -> Result<ValidatedOrder, Vec<String>> {
+    let validated = validate(&order, &account)?;
+    risk::review(&app, &validated, &account).await?;
+    Ok(validated)
+}
+
+/// Validate and place a paper order. Market orders fill immediately at the
+/// quote plus the configured slippage; others rest until
+/// `check_paper_orders` sees their conditions met. Fills live in
+/// `paper_fills`, apart from the real trade history.
+#[tauri::command]
+pub async fn paper_execute(
+    app: AppHandle,
+    order: OrderTicket,
+    account: AccountState,
+) -> Result<PaperExecution, String> {
+    logged!("paper_execute", [], async {
+        let validated = validate(&order, &account).map_err(|problems| problems.join("; "))?;
+        risk::review(&app, &validated, &account)
+            .await
+            .map_err(|problems| problems.join("; "))?;
+        let slippage = settings::load_settings(&app).paper_slippage_bps;
+
+        let pool = db::pool(&app).await?;
+        let loss_before = risk::daily_loss(&pool).await?;
+        let execution = place(&pool, &validated, &account, slippage).await?;
+        if let Some(fill) = &execution.fill {
+            let _ = app.emit("paper-fill", fill);
+            // The fill is committed, so a failed check mustn't report an error
+            if let Err(e) = risk::notify_breach(&app, &pool, loss_before).await {
+                eprintln!("[paper] Failed to check the daily loss cap: {}", e);
+            }
+        }
+        Ok(execution)
+    })
+}
+
+/// Fill resting paper orders against the latest quotes, and optionally the
+/// size available at each. The frontend's monitor calls this after each
+/// price refresh, with the account as it stands. Buys that would break a
+/// risk limit are cancelled and reported in `paper-order-rejected`.
+#[tauri::command]
+pub async fn check_paper_orders(
+    app: AppHandle,
+    quotes: HashMap<String, f64>,
+    sizes: Option<HashMap<String, i64>>,
+    account: AccountState,
+) -> Result<Vec<Fill>, String> {
+    logged!("check_paper_orders", [], async {
+        let settings = settings::load_settings(&app);
+        let pool = db::pool(&app).await?;
+        let loss_before = risk::daily_loss(&pool).await?;
+        let mut risk = RestingRisk {
+            limits: settings.risk_limits,
+            account,
+            loss_today: loss_before,
+        };
+        let matched = match_resting(
+            &pool,
+            &quotes,
+            &sizes.unwrap_or_default(),
+            settings.paper_slippage_bps,
+            &mut risk,
+        )
+        .await?;
+        for rejection in &matched.rejected {
+            let _ = app.emit("paper-order-rejected", rejection);
+        }
+        let fills = matched.fills;
+        for fill in &fills {
+            let _ = app.emit("paper-fill", fill);
+        }
+        if !fills.is_empty() {
+            if let Err(e) = risk::notify_breach(&app, &pool, loss_before).await {
+                eprintln!("[paper] Failed to check the daily loss cap: {}", e);
+            }
+        }
+        Ok(fills)
+    })
+}
+
+/// Paper orders with their trigger conditions and fills, optionally only
+/// those with the given status.
+#[tauri::command]
+pub async fn paper_orders(
+    app: AppHandle,
+    status: Option<String>,
+) -> Result<Vec<PaperOrder>, String> {
+    logged!("paper_orders", [status], async {
+        let pool = db::pool(&app).await?;
+        load_orders(&pool, status.as_deref()).await
+    })
+}
+
+#[tauri::command]
+pub async fn cancel_paper_order(app: AppHandle, id: i64) -> Result<(), String> {
+    logged!("cancel_paper_order", [id], async {
+        let pool = db::pool(&app).await?;
+        cancel(&pool, id).await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing;
+
+    fn ticket(side: &str, quantity: i64, order_type: &str) -> OrderTicket {
+        OrderTicket {
+            symbol: "aapl".into(),
+            side: side.into(),
+            quantity,
+            order_type: order_type.into(),
+            limit_price: None,
+            stop_price: None,
+        }
+    }
+
+    fn account(cash: f64, held: i64) -> AccountState {
+        AccountState {
+            cash,
+            positions: HashMap::from([("AAPL".to_string(), held)]),
+            market_price: 100.0,
+            quotes: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn market_buy_within_buying_power_passes() {
+        let order = validate(&ticket("buy", 10, "market"), &account(1_000.0, 0)).unwrap();
+        assert_eq!(order.symbol, "AAPL");
+        assert_eq!(order.estimated_value, 1_000.0);
+    }
+
+    #[test]
+    fn buy_over_buying_power_is_rejected() {
+        let problems = validate(&ticket("buy", 11, "market"), &account(1_000.0, 0)).unwrap_err();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("buying power"));
+    }
+
+    #[test]
+    fn sells_need_shares() {
+        assert!(validate(&ticket("sell", 5, "market"), &account(0.0, 5)).is_ok());
+        let problems = validate(&ticket("sell", 6, "market"), &account(0.0, 5)).unwrap_err();
+        assert!(problems[0].contains("only 5 held"));
+    }
+
+    #[test]
+    fn stops_must_be_on_the_right_side_of_market() {
+        let mut buy_stop = ticket("buy", 1, "stop");
+        buy_stop.stop_price = Some(95.0);
+        assert!(validate(&buy_stop, &account(1_000.0, 0)).is_err());
+        buy_stop.stop_price = Some(105.0);
+        assert!(validate(&buy_stop, &account(1_000.0, 0)).is_ok());
+
+        let mut sell_stop = ticket("sell", 1, "stop_limit");
+        sell_stop.stop_price = Some(95.0);
+        sell_stop.limit_price = Some(96.0);
+        let problems = validate(&sell_stop, &account(0.0, 1)).unwrap_err();
+        assert!(problems[0].contains("at or below the stop"));
+    }
+
+    #[test]
+    fn prices_must_fit_the_tick_and_the_order_type() {
+        let mut limit = ticket("buy", 1, "limit");
+        limit.limit_price = Some(99.995);
+        assert!(validate(&limit, &account(1_000.0, 0)).unwrap_err()[0].contains("tick"));
+        limit.limit_price = Some(99.99);
+        assert!(validate(&limit, &account(1_000.0, 0)).is_ok());
+
+        let mut market = ticket("buy", 1, "market");
+        market.limit_price = Some(99.0);
+        assert!(validate(&market, &account(1_000.0, 0)).is_err());
+        assert!(on_tick(0.5012) && !on_tick(0.50125));
+    }
+
+    #[test]
+    fn collects_every_problem() {
+        let order = ticket("hold", 0, "trailing");
+        assert_eq!(validate(&order, &account(0.0, 0)).unwrap_err().len(), 3);
+    }
+
+    fn validated(
+        side: &str,
+        order_type: &str,
+        limit: Option<f64>,
+        stop: Option<f64>,
+    ) -> ValidatedOrder {
+        let mut order = ticket(side, 10, order_type);
+        order.limit_price = limit;
+        order.stop_price = stop;
+        validate(&order, &account(10_000.0, 10)).unwrap()
+    }
+
+    fn unlimited() -> RestingRisk {
+        RestingRisk {
+            limits: RiskLimits::default(),
+            account: account(10_000.0, 10),
+            loss_today: 0.0,
+        }
+    }
+
+    #[test]
+    fn slippage_goes_against_the_trader() {
+        assert_eq!(slipped(100.0, 10, true), 100.1);
+        assert_eq!(slipped(100.0, 10, false), 99.9);
+        assert_eq!(slipped(100.0, 0, true), 100.0);
+    }
+
+    #[tokio::test]
+    async fn market_order_fills_immediately_with_slippage() {
+        let pool = testing::pool().await;
+        let execution = place(
+            &pool,
+            &validated("buy", "market", None, None),
+            &account(10_000.0, 10),
+            5,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(execution.status, "filled");
+        let fill = execution.fill.unwrap();
+        assert_eq!(fill.quantity, 10);
+        assert_eq!(fill.price, 100.05);
+    }
+
+    #[tokio::test]
+    async fn market_buy_must_afford_the_slipped_price() {
+        let pool = testing::pool().await;
+        let funds = account(1_000.0, 0);
+        let order = validate(&ticket("buy", 10, "market"), &funds).unwrap();
+
+        let err = place(&pool, &order, &funds, 5).await.unwrap_err();
+        assert!(err.contains("buying power"));
+        assert!(load_orders(&pool, None).await.unwrap().is_empty());
+        assert!(place(&pool, &order, &funds, 0).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn limit_order_rests_until_price_is_reached() {
+        let pool = testing::pool().await;
+        let execution = place(
+            &pool,
+            &validated("buy", "limit", Some(95.0), None),
+            &account(10_000.0, 10),
+            5,
+        )
+        .await
+        .unwrap();
+        assert_eq!(execution.status, "open");
+
+        let quotes = |price: f64| HashMap::from([("AAPL".to_string(), price)]);
+        assert!(
+            match_resting(&pool, &quotes(96.0), &HashMap::new(), 5, &mut unlimited())
+                .await
+                .unwrap()
+                .fills
+                .is_empty()
+        );
+
+        let fills = match_resting(&pool, &quotes(94.5), &HashMap::new(), 5, &mut unlimited())
+            .await
+            .unwrap()
+            .fills;
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].price, 94.5);
+        assert!(
+            match_resting(&pool, &quotes(90.0), &HashMap::new(), 5, &mut unlimited())
+                .await
+                .unwrap()
+                .fills
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn resting_buys_over_a_risk_limit_are_cancelled() {
+        let pool = testing::pool().await;
+        let funds = account(1_000.0, 0);
+        for quantity in [10, 5] {
+            let mut order = ticket("buy", quantity, "limit");
+            order.limit_price = Some(95.0);
+            let order = validate(&order, &funds).unwrap();
+            place(&pool, &order, &funds, 0).await.unwrap();
+        }
+        let mut risk = RestingRisk {
+            limits: RiskLimits {
+                max_position_pct: Some(50.0),
+                ..RiskLimits::default()
+            },
+            account: funds,
+            loss_today: 0.0,
+        };
+
+        let quotes = HashMap::from([("AAPL".to_string(), 94.5)]);
+        let matched = match_resting(&pool, &quotes, &HashMap::new(), 0, &mut risk)
+            .await
+            .unwrap();
+        assert_eq!(matched.fills.len(), 1);
+        assert_eq!(matched.fills[0].quantity, 5);
+        assert_eq!(matched.rejected.len(), 1);
+        assert!(matched.rejected[0].problems[0].starts_with("max_position_pct"));
+        assert_eq!(risk.account.positions["AAPL"], 5);
+
+        let cancelled = load_orders(&pool, Some("cancelled")).await.unwrap();
+        assert_eq!(cancelled[0].id, matched.rejected[0].order_id);
+        assert!(cancelled[0].fills.is_empty());
+    }
+
+    #[tokio::test]
+    async fn stop_limit_remembers_its_trigger() {
+        let pool = testing::pool().await;
+        place(
+            &pool,
+            &validated("sell", "stop_limit", Some(94.0), Some(95.0)),
+            &account(10_000.0, 10),
+            5,
+        )
+        .await
+        .unwrap();
+        let quotes = |price: f64| HashMap::from([("AAPL".to_string(), price)]);
+
+        // Gaps through the stop and the limit; triggered but unfilled
+        assert!(
+            match_resting(&pool, &quotes(93.0), &HashMap::new(), 5, &mut unlimited())
+                .await
+                .unwrap()
+                .fills
+                .is_empty()
+        );
+        let fills = match_resting(&pool, &quotes(94.2), &HashMap::new(), 5, &mut unlimited())
+            .await
+            .unwrap()
+            .fills;
+        assert_eq!(fills[0].price, 94.2);
+    }
+
+    #[tokio::test]
+    async fn order_book_filters_by_status_and_carries_fills() {
+        let pool = testing::pool().await;
+        place(
+            &pool,
+            &validated("buy", "market", None, None),
+            &account(10_000.0, 10),
+            0,
+        )
+        .await
+        .unwrap();
+        place(
+            &pool,
+            &validated("buy", "limit", Some(95.0), None),
+            &account(10_000.0, 10),
+            0,
+        )
+        .await
+        .unwrap();
+
+        let all = load_orders(&pool, None).await.unwrap();
+        assert_eq!(all.len(), 2);
+        let open = load_orders(&pool, Some("open")).await.unwrap();
+        assert_eq!(open.len(), 1);
+        assert!(open[0].fills.is_empty());
+
+        let filled = load_orders(&pool, Some("filled")).await.unwrap();
+        assert_eq!(filled[0].fills.len(), 1);
+        assert_eq!(filled[0].fills[0].price, 100.0);
+        assert!(load_orders(&pool, Some("pending")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn cancelling_keeps_fills_and_rejects_finished_orders() {
+        let pool = testing::pool().await;
+        let market = place(
+            &pool,
+            &validated("buy", "market", None, None),
+            &account(10_000.0, 10),
+            0,
+        )
+        .await
+        .unwrap();
+        let limit = place(
+            &pool,
+            &validated("buy", "limit", Some(95.0), None),
+            &account(10_000.0, 10),
+            0,
+        )
+        .await
+        .unwrap();
+
+        cancel(&pool, limit.order_id).await.unwrap();
+        let quotes = HashMap::from([("AAPL".to_string(), 90.0)]);
+        assert!(
+            match_resting(&pool, &quotes, &HashMap::new(), 0, &mut unlimited())
+                .await
+                .unwrap()
+                .fills
+                .is_empty()
+        );
+
+        let err = cancel(&pool, market.order_id).await.unwrap_err();
+        assert!(err.contains("already filled"));
+        let filled = load_orders(&pool, Some("filled")).await.unwrap();
+        assert_eq!(filled[0].filled_quantity, 10);
+        assert_eq!(filled[0].fills.len(), 1);
+        assert!(cancel(&pool, 99).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn cancelling_a_partial_fill_keeps_the_filled_shares() {
+        let pool = testing::pool().await;
+        let limit = place(
+            &pool,
+            &validated("buy", "limit", Some(95.0), None),
+            &account(10_000.0, 10),
+            0,
+        )
+        .await
+        .unwrap();
+        let quotes = HashMap::from([("AAPL".to_string(), 94.5)]);
+        let sizes = HashMap::from([("AAPL".to_string(), 4)]);
+
+        let fills = match_resting(&pool, &quotes, &sizes, 0, &mut unlimited())
+            .await
+            .unwrap()
+            .fills;
+        assert_eq!(fills[0].quantity, 4);
+        let open = load_orders(&pool, Some("open")).await.unwrap();
+        assert_eq!(open[0].filled_quantity, 4);
+
+        cancel(&pool, limit.order_id).await.unwrap();
+        assert!(
+            match_resting(&pool, &quotes, &HashMap::new(), 0, &mut unlimited())
+                .await
+                .unwrap()
+                .fills
+                .is_empty()
+        );
+        let cancelled = load_orders(&pool, Some("cancelled")).await.unwrap();
+        assert_eq!(cancelled[0].filled_quantity, 4);
+        assert_eq!(cancelled[0].fills.len(), 1);
+    }
+}
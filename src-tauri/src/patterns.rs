@@ -0,0 +1,303 @@
+use serde::Serialize;
+
+use crate::indicators::{validate_candles, Candle};
+
+/// A doji's body is at most this share of its range.
+const DOJI_BODY_RATIO: f64 = 0.1;
+/// Hammers and shooting stars have a small body...
+const SMALL_BODY_RATIO: f64 = 1.0 / 3.0;
+/// ...a long shadow at least this many bodies long...
+const LONG_SHADOW_BODIES: f64 = 2.0;
+/// ...and almost nothing on the other side.
+const SHORT_SHADOW_RATIO: f64 = 0.1;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PatternHit {
+    index: usize,
+    pattern: &'static str,
+    direction: &'static str,
+}
+
+fn body(c: &Candle) -> f64 {
+    (c.close - c.open).abs()
+}
+
+fn range(c: &Candle) -> f64 {
+    c.high - c.low
+}
+
+fn upper_shadow(c: &Candle) -> f64 {
+    c.high - c.open.max(c.close)
+}
+
+fn lower_shadow(c: &Candle) -> f64 {
+    c.open.min(c.close) - c.low
+}
+
+pub(crate) fn is_doji(c: &Candle) -> bool {
+    range(c) > 0.0 && body(c) <= DOJI_BODY_RATIO * range(c)
+}
+
+pub(crate) fn is_hammer(c: &Candle) -> bool {
+    range(c) > 0.0
+        && body(c) <= SMALL_BODY_RATIO * range(c)
+        && lower_shadow(c) >= LONG_SHADOW_BODIES * body(c)
+        && upper_shadow(c) <= SHORT_SHADOW_RATIO * range(c)
+}
+
+pub(crate) fn is_shooting_star(c: &Candle) -> bool {
+    range(c) > 0.0
+        && body(c) <= SMALL_BODY_RATIO * range(c)
+        && upper_shadow(c) >= LONG_SHADOW_BODIES * body(c)
+        && lower_shadow(c) <= SHORT_SHADOW_RATIO * range(c)
+}
+
+/// A rising bar whose body covers the whole body of a falling one before it.
+pub(crate) fn is_bullish_engulfing(prev: &Candle, c: &Candle) -> bool {
+    prev.close < prev.open
+        && c.close > c.open
+        && c.open <= prev.close
+        && c.close >= prev.open
+        && body(c) > body(prev)
+}
+
+pub(crate) fn is_bearish_engulfing(prev: &Candle, c: &Candle) -> bool {
+    prev.close > prev.open
+        && c.close < c.open
+        && c.open >= prev.close
+        && c.close <= prev.open
+        && body(c) > body(prev)
+}
+
+/// Every pattern found, in candle order. A doji isn't also reported as a
+/// hammer or shooting star.
+pub(crate) fn detect_patterns(candles: &[Candle]) -> Vec<PatternHit> {
+    let mut hits = Vec::new();
+    let mut hit = |index, pattern, direction| {
+        hits.push(PatternHit {
+            index,
+            pattern,
+            direction,
+        })
+    };
+
+    for (index, c) in candles.iter().enumerate() {
+        if is_doji(c) {
+            hit(index, "doji", "neutral");
+        } else if is_hammer(c) {
+            hit(index, "hammer", "bullish");
+        } else if is_shooting_star(c) {
+            hit(index, "shooting_star", "bearish");
+        }
+
+        if let Some(prev) = index.checked_sub(1).map(|i| &candles[i]) {
+            if is_bullish_engulfing(prev, c) {
+                hit(index, "bullish_engulfing", "bullish");
+            } else if is_bearish_engulfing(prev, c) {
+                hit(index, "bearish_engulfing", "bearish");
+            }
+        }
+    }
+    hits
+}
+
+// ---------------------------------------------------------------------------
+// Support and resistance
+// ---------------------------------------------------------------------------
+
+/// Bars either side a swing high or low has to beat.
+const SWING_LOOKBACK: usize = 2;
+
+#[derive(Debug, Serialize)]
+pub struct PriceLevel {
+    pub(crate) price: f64,
+    /// Swing highs and lows that landed on this level.
+    touches: usize,
+    pub(crate) kind: &'static str,
+}
+
+struct Swing {
+    price: f64,
+    high: bool,
+}
+
+fn swings(candles: &[Candle]) -> Vec<Swing> {
+    let mut found = Vec::new();
+    if candles.len() <= 2 * SWING_LOOKBACK {
+        return found;
+    }
+
+    for i in SWING_LOOKBACK..candles.len() - SWING_LOOKBACK {
+        let neighbours = || {
+            (i - SWING_LOOKBACK..=i + SWING_LOOKBACK)
+                .filter(move |&j| j != i)
+                .map(|j| &candles[j])
+        };
+        if neighbours().all(|c| c.high < candles[i].high) {
+            found.push(Swing {
+                price: candles[i].high,
+                high: true,
+            });
+        }
+        if neighbours().all(|c| c.low > candles[i].low) {
+            found.push(Swing {
+                price: candles[i].low,
+                high: false,
+            });
+        }
+    }
+    found
+}
+
+/// Cluster swing highs and lows into horizontal levels, merging swings within
+/// `sensitivity` (a fraction of price, 0.01 for 1%) of a level's average.
+/// Strongest levels first.
+pub(crate) fn find_levels(candles: &[Candle], sensitivity: f64) -> Vec<PriceLevel> {
+    let mut points = swings(candles);
+    points.sort_by(|a, b| a.price.total_cmp(&b.price));
+
+    // (sum of prices, touches, highs)
+    let mut clusters: Vec<(f64, usize, usize)> = Vec::new();
+    for point in points {
+        match clusters.last_mut() {
+            Some((sum, touches, highs))
+                if (point.price - *sum / *touches as f64).abs() <= sensitivity * point.price =>
+            {
+                *sum += point.price;
+                *touches += 1;
+                *highs += point.high as usize;
+            }
+            _ => clusters.push((point.price, 1, point.high as usize)),
+        }
+    }
+
+    let mut levels: Vec<PriceLevel> = clusters
+        .into_iter()
+        .map(|(sum, touches, highs)| PriceLevel {
+            price: sum / touches as f64,
+            touches,
+            kind: match highs {
+                0 => "support",
+                h if h == touches => "resistance",
+                _ => "both",
+            },
+        })
+        .collect();
+    levels.sort_by(|a, b| b.touches.cmp(&a.touches).then(a.price.total_cmp(&b.price)));
+    levels
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn detect_candlestick_patterns(candles: Vec<Candle>) -> Result<Vec<PatternHit>, String> {
+    logged!("detect_candlestick_patterns", [], {
+        validate_candles(&candles)?;
+        Ok(detect_patterns(&candles))
+    })
+}
+
+#[tauri::command]
+pub fn support_resistance(
+    candles: Vec<Candle>,
+    sensitivity: f64,
+) -> Result<Vec<PriceLevel>, String> {
+    logged!("support_resistance", [sensitivity], {
+        if !sensitivity.is_finite() || !(0.0..=0.5).contains(&sensitivity) {
+            return Err("Sensitivity must be between 0 and 0.5".into());
+        }
+        validate_candles(&candles)?;
+        Ok(find_levels(&candles, sensitivity))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::candle;
+
+    #[test]
+    fn doji_has_a_tiny_body() {
+        assert!(is_doji(&candle(10.0, 11.0, 9.0, 10.05)));
+        assert!(!is_doji(&candle(10.0, 11.0, 9.0, 10.5)));
+        assert!(!is_doji(&candle(10.0, 10.0, 10.0, 10.0)));
+    }
+
+    #[test]
+    fn hammer_and_shooting_star_mirror_each_other() {
+        let hammer = candle(10.5, 11.0, 8.0, 11.0);
+        assert!(is_hammer(&hammer));
+        assert!(!is_shooting_star(&hammer));
+
+        let star = candle(8.5, 11.0, 8.0, 8.0);
+        assert!(is_shooting_star(&star));
+        assert!(!is_hammer(&star));
+    }
+
+    #[test]
+    fn engulfing_needs_the_whole_prior_body() {
+        let falling = candle(11.0, 11.5, 9.5, 10.0);
+        assert!(is_bullish_engulfing(
+            &falling,
+            &candle(9.8, 11.8, 9.6, 11.5)
+        ));
+        assert!(!is_bullish_engulfing(
+            &falling,
+            &candle(10.2, 11.8, 10.0, 11.5)
+        ));
+
+        let rising = candle(10.0, 11.5, 9.5, 11.0);
+        assert!(is_bearish_engulfing(&rising, &candle(11.2, 11.4, 9.4, 9.8)));
+    }
+
+    #[test]
+    fn detect_reports_index_and_direction() {
+        let candles = [
+            candle(11.0, 11.5, 9.5, 10.0),
+            candle(9.8, 11.8, 9.6, 11.5),
+            candle(11.5, 12.0, 11.0, 11.52),
+        ];
+
+        assert_eq!(
+            detect_patterns(&candles),
+            vec![
+                PatternHit {
+                    index: 1,
+                    pattern: "bullish_engulfing",
+                    direction: "bullish",
+                },
+                PatternHit {
+                    index: 2,
+                    pattern: "doji",
+                    direction: "neutral",
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_top_becomes_the_strongest_level() {
+        // Three rallies stall near 110, with lows drifting in between
+        let closes = [
+            100.0, 104.0, 110.0, 104.0, 100.0, 103.0, 109.8, 103.0, 98.0, 104.0, 110.2, 104.0,
+            101.0,
+        ];
+        let candles: Vec<Candle> = closes
+            .iter()
+            .map(|&c| candle(c, c + 0.5, c - 0.5, c))
+            .collect();
+
+        let levels = find_levels(&candles, 0.01);
+        assert_eq!(levels[0].touches, 3);
+        assert_eq!(levels[0].kind, "resistance");
+        assert!((levels[0].price - 110.5).abs() < 0.5);
+    }
+
+    #[test]
+    fn too_few_candles_have_no_levels() {
+        let candles = [candle(10.0, 11.0, 9.0, 10.0); 4];
+        assert!(find_levels(&candles, 0.01).is_empty());
+    }
+}
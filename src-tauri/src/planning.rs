@@ -0,0 +1,286 @@
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::settings;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PositionSizeResult {
+    shares: f64,
+    dollars: f64,
+    risk_amount: f64,
+}
+
+/// Fixed-fractional sizing: risk `risk_pct` percent of the account between
+/// `entry` and `stop`. Longs need the stop below entry, shorts above it.
+/// The position is capped at the account value, so no leverage is assumed,
+/// and rounded down to whole shares unless `fractional`.
+pub(crate) fn fixed_fractional(
+    account_value: f64,
+    risk_pct: f64,
+    entry: f64,
+    stop: f64,
+    short: bool,
+    fractional: bool,
+) -> Result<PositionSizeResult, String> {
+    for (name, value) in [
+        ("Account value", account_value),
+        ("Entry price", entry),
+        ("Stop price", stop),
+    ] {
+        if !value.is_finite() || value <= 0.0 {
+            return Err(format!("{} must be positive", name));
+        }
+    }
+    if !risk_pct.is_finite() || risk_pct <= 0.0 || risk_pct > 100.0 {
+        return Err("Risk must be between 0 and 100 percent".into());
+    }
+
+    let per_share_risk = if short { stop - entry } else { entry - stop };
+    if per_share_risk <= 0.0 {
+        return Err(if short {
+            "Stop must be above entry for a short".into()
+        } else {
+            "Stop must be below entry for a long".into()
+        });
+    }
+
+    let budget = account_value * risk_pct / 100.0;
+    let mut shares = (budget / per_share_risk).min(account_value / entry);
+    if !fractional {
+        shares = shares.floor();
+    }
+
+    Ok(PositionSizeResult {
+        shares,
+        dollars: shares * entry,
+        risk_amount: shares * per_share_risk,
+    })
+}
+
+/// Fifty years of monthly buys is plenty for a planning tool.
+const MAX_DCA_MONTHS: usize = 600;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DcaMonth {
+    month: u32,
+    price: f64,
+    shares_bought: f64,
+    cumulative_shares: f64,
+    cumulative_invested: f64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DcaResult {
+    months: Vec<DcaMonth>,
+    average_cost: Option<f64>,
+    /// Cash not spent because only whole shares could be bought.
+    uninvested: f64,
+}
+
+/// Invest `monthly_amount` at each price in turn. Without fractional shares,
+/// whatever doesn't buy a whole share carries over to the next month.
+pub(crate) fn dca(
+    monthly_amount: f64,
+    prices: &[f64],
+    fractional: bool,
+) -> Result<DcaResult, String> {
+    if !monthly_amount.is_finite() || monthly_amount <= 0.0 {
+        return Err("Monthly amount must be positive".into());
+    }
+    if prices.is_empty() || prices.len() > MAX_DCA_MONTHS {
+        return Err(format!(
+            "Schedule must cover 1 to {} months",
+            MAX_DCA_MONTHS
+        ));
+    }
+    if prices.iter().any(|p| !p.is_finite() || *p <= 0.0) {
+        return Err("Prices must be positive".into());
+    }
+
+    let mut cash = 0.0;
+    let mut shares = 0.0;
+    let mut invested = 0.0;
+    let mut months = Vec::with_capacity(prices.len());
+
+    for (i, &price) in prices.iter().enumerate() {
+        cash += monthly_amount;
+        let bought = if fractional {
+            cash / price
+        } else {
+            (cash / price).floor()
+        };
+        cash -= bought * price;
+        shares += bought;
+        invested += bought * price;
+
+        months.push(DcaMonth {
+            month: i as u32 + 1,
+            price,
+            shares_bought: bought,
+            cumulative_shares: shares,
+            cumulative_invested: invested,
+        });
+    }
+
+    Ok(DcaResult {
+        months,
+        average_cost: (shares > 0.0).then(|| invested / shares),
+        uninvested: cash,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Whether the user's broker supports fractional shares.
+fn fractional_allowed(app: &AppHandle) -> bool {
+    settings::load_settings(app).fractional_shares_allowed
+}
+
+#[tauri::command]
+pub fn position_size(
+    app: AppHandle,
+    account_value: f64,
+    risk_pct: f64,
+    entry: f64,
+    stop: f64,
+) -> Result<PositionSizeResult, String> {
+    logged!("position_size", [account_value, risk_pct, entry, stop], {
+        fixed_fractional(
+            account_value,
+            risk_pct,
+            entry,
+            stop,
+            false,
+            fractional_allowed(&app),
+        )
+    })
+}
+
+#[tauri::command]
+pub fn position_size_short(
+    app: AppHandle,
+    account_value: f64,
+    risk_pct: f64,
+    entry: f64,
+    stop: f64,
+) -> Result<PositionSizeResult, String> {
+    logged!(
+        "position_size_short",
+        [account_value, risk_pct, entry, stop],
+        {
+            fixed_fractional(
+                account_value,
+                risk_pct,
+                entry,
+                stop,
+                true,
+                fractional_allowed(&app),
+            )
+        }
+    )
+}
+
+/// Project `months` of buys at a constant `symbol_price`.
+#[tauri::command]
+pub fn dca_schedule(
+    app: AppHandle,
+    monthly_amount: f64,
+    symbol_price: f64,
+    months: u32,
+) -> Result<DcaResult, String> {
+    logged!("dca_schedule", [monthly_amount, symbol_price, months], {
+        let prices = vec![symbol_price; (months as usize).min(MAX_DCA_MONTHS + 1)];
+        dca(monthly_amount, &prices, fractional_allowed(&app))
+    })
+}
+
+/// Replay monthly buys against a historical price series, one price per month.
+#[tauri::command]
+pub fn dca_backtest(
+    app: AppHandle,
+    monthly_amount: f64,
+    prices: Vec<f64>,
+) -> Result<DcaResult, String> {
+    logged!("dca_backtest", [monthly_amount], {
+        dca(monthly_amount, &prices, fractional_allowed(&app))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_risks_the_requested_fraction() {
+        let size = fixed_fractional(100_000.0, 1.0, 50.0, 45.0, false, true).unwrap();
+        assert_eq!(size.shares, 200.0);
+        assert_eq!(size.dollars, 10_000.0);
+        assert_eq!(size.risk_amount, 1_000.0);
+    }
+
+    #[test]
+    fn short_measures_risk_above_entry() {
+        let size = fixed_fractional(100_000.0, 1.0, 50.0, 55.0, true, true).unwrap();
+        assert_eq!(size.shares, 200.0);
+        assert_eq!(size.risk_amount, 1_000.0);
+    }
+
+    #[test]
+    fn tight_stop_is_capped_at_account_value() {
+        let size = fixed_fractional(10_000.0, 2.0, 100.0, 99.9, false, true).unwrap();
+        assert_eq!(size.shares, 100.0);
+        assert_eq!(size.dollars, 10_000.0);
+    }
+
+    #[test]
+    fn whole_shares_round_down_when_fractional_is_off() {
+        let whole = fixed_fractional(10_000.0, 1.0, 30.0, 27.0, false, false).unwrap();
+        assert_eq!(whole.shares, 33.0);
+        assert_eq!(whole.dollars, 990.0);
+
+        let fractional = fixed_fractional(10_000.0, 1.0, 30.0, 27.0, false, true).unwrap();
+        assert!((fractional.shares - 100.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rejects_stop_on_wrong_side() {
+        assert!(fixed_fractional(10_000.0, 1.0, 50.0, 55.0, false, true).is_err());
+        assert!(fixed_fractional(10_000.0, 1.0, 50.0, 45.0, true, true).is_err());
+        assert!(fixed_fractional(10_000.0, 0.0, 50.0, 45.0, false, true).is_err());
+        assert!(fixed_fractional(-1.0, 1.0, 50.0, 45.0, false, true).is_err());
+    }
+
+    #[test]
+    fn dca_at_constant_price_accumulates_linearly() {
+        let result = dca(500.0, &[50.0; 3], true).unwrap();
+        let last = result.months.last().unwrap();
+        assert_eq!(last.cumulative_shares, 30.0);
+        assert_eq!(last.cumulative_invested, 1_500.0);
+        assert_eq!(result.average_cost, Some(50.0));
+    }
+
+    #[test]
+    fn whole_shares_carry_leftover_cash() {
+        let result = dca(100.0, &[30.0, 30.0], false).unwrap();
+        assert_eq!(result.months[0].shares_bought, 3.0);
+        assert_eq!(result.months[1].shares_bought, 3.0);
+        assert_eq!(result.uninvested, 20.0);
+    }
+
+    #[test]
+    fn dca_buys_more_when_prices_fall() {
+        let result = dca(100.0, &[100.0, 50.0], true).unwrap();
+        assert_eq!(result.months[1].shares_bought, 2.0);
+        assert_eq!(result.average_cost, Some(200.0 / 3.0));
+    }
+
+    #[test]
+    fn dca_rejects_bad_input() {
+        assert!(dca(0.0, &[10.0], true).is_err());
+        assert!(dca(100.0, &[], true).is_err());
+        assert!(dca(100.0, &[10.0, -1.0], true).is_err());
+        assert!(dca(100.0, &[10.0; MAX_DCA_MONTHS + 1], true).is_err());
+    }
+}
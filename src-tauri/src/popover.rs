@@ -0,0 +1,56 @@
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder, WindowEvent};
+use tauri_plugin_positioner::{Position, WindowExt};
+
+pub(crate) const POPOVER_LABEL: &str = "tray-popover";
+
+const POPOVER_WIDTH: f64 = 320.0;
+const POPOVER_HEIGHT: f64 = 360.0;
+
+/// The macOS menu bar is at the top of the screen, so the popover drops
+/// below the icon; Windows and most Linux panels sit at the bottom, so it
+/// opens upward from the tray corner instead.
+#[cfg(target_os = "macos")]
+const POPOVER_ANCHOR: Position = Position::TrayCenter;
+#[cfg(not(target_os = "macos"))]
+const POPOVER_ANCHOR: Position = Position::TrayBottomRight;
+
+fn build_popover(app: &AppHandle) -> tauri::Result<WebviewWindow> {
+    let window =
+        WebviewWindowBuilder::new(app, POPOVER_LABEL, WebviewUrl::App("tray-popover".into()))
+            .title("Stock Advisors")
+            .inner_size(POPOVER_WIDTH, POPOVER_HEIGHT)
+            .resizable(false)
+            .decorations(false)
+            .skip_taskbar(true)
+            .always_on_top(true)
+            .visible(false)
+            .build()?;
+
+    // Behave like a menu: clicking anywhere else dismisses it
+    let popover = window.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Focused(false) = event {
+            let _ = popover.hide();
+        }
+    });
+
+    Ok(window)
+}
+
+/// Show the popover next to the tray icon, creating it on first use.
+pub(crate) fn show_popover(app: &AppHandle) {
+    let window = match app.get_webview_window(POPOVER_LABEL) {
+        Some(window) => window,
+        None => match build_popover(app) {
+            Ok(window) => window,
+            Err(e) => {
+                eprintln!("[popover] Failed to create tray popover: {}", e);
+                return;
+            }
+        },
+    };
+
+    let _ = window.move_window(POPOVER_ANCHOR);
+    let _ = window.show();
+    let _ = window.set_focus();
+}
@@ -0,0 +1,628 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::allocation::{self, TargetDrift};
+use crate::db::{self, TradeRow};
+use crate::settings::{self, SettingsPatch};
+use crate::{i18n, notifications, session, tax};
+
+/// HHI bands, after the thresholds antitrust regulators use for markets.
+const DIVERSIFIED_BELOW: f64 = 0.15;
+const CONCENTRATED_BELOW: f64 = 0.25;
+
+const TOP_N: usize = 5;
+
+/// Positions worth less than this are leftovers rather than holdings.
+const STALE_MAX_VALUE: f64 = 100.0;
+
+#[derive(Debug, Serialize)]
+pub struct ConcentrationReport {
+    hhi: f64,
+    top5_pct: f64,
+    label: &'static str,
+    positions: usize,
+}
+
+/// Exposure per symbol across open trades. Shorts count at their absolute
+/// value since they concentrate risk just the same. Symbols without a price
+/// in `prices` fall back to their entry price.
+pub(crate) fn symbol_exposures(
+    trades: &[TradeRow],
+    prices: &HashMap<String, f64>,
+) -> Vec<(String, f64)> {
+    let mut by_symbol: BTreeMap<&str, f64> = BTreeMap::new();
+    for trade in trades.iter().filter(|t| t.is_open()) {
+        let price = prices
+            .get(&trade.symbol)
+            .copied()
+            .unwrap_or(trade.entry_price);
+        *by_symbol.entry(&trade.symbol).or_default() += trade.quantity as f64 * price;
+    }
+    by_symbol
+        .into_iter()
+        .map(|(symbol, value)| (symbol.to_string(), value.abs()))
+        .filter(|(_, value)| *value > 0.0)
+        .collect()
+}
+
+pub(crate) fn exposures(trades: &[TradeRow], prices: &HashMap<String, f64>) -> Vec<f64> {
+    symbol_exposures(trades, prices)
+        .into_iter()
+        .map(|(_, value)| value)
+        .collect()
+}
+
+/// Herfindahl-Hirschman Index of the weights, top-5 share and a risk label.
+/// An empty portfolio reports zero concentration.
+pub(crate) fn concentration_of(values: &[f64]) -> ConcentrationReport {
+    let total: f64 = values.iter().sum();
+    if total <= 0.0 {
+        return ConcentrationReport {
+            hhi: 0.0,
+            top5_pct: 0.0,
+            label: "diversified",
+            positions: 0,
+        };
+    }
+
+    let mut weights: Vec<f64> = values.iter().map(|v| v / total).collect();
+    weights.sort_by(|a, b| b.total_cmp(a));
+
+    let hhi = weights.iter().map(|w| w * w).sum();
+    let label = if hhi < DIVERSIFIED_BELOW {
+        "diversified"
+    } else if hhi < CONCENTRATED_BELOW {
+        "concentrated"
+    } else {
+        "very concentrated"
+    };
+
+    ConcentrationReport {
+        hhi,
+        top5_pct: weights.iter().take(TOP_N).sum::<f64>() * 100.0,
+        label,
+        positions: weights.len(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Broker reconciliation
+// ---------------------------------------------------------------------------
+
+/// A holding as reported by the broker. Shorts have negative quantities.
+#[derive(Deserialize)]
+pub struct BrokerPosition {
+    symbol: String,
+    quantity: i64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Discrepancy {
+    symbol: String,
+    tracked_qty: i64,
+    broker_qty: i64,
+    diff: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReconcileReport {
+    in_sync: bool,
+    discrepancies: Vec<Discrepancy>,
+}
+
+/// Compare tracked open quantities with the broker's, symbol by symbol.
+/// `diff` is what the tracked position would need to change by to match.
+pub(crate) fn reconcile_positions(
+    trades: &[TradeRow],
+    broker: &[BrokerPosition],
+) -> Result<ReconcileReport, String> {
+    let mut quantities: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+    for trade in trades.iter().filter(|t| t.is_open()) {
+        let signed = if trade.is_long() {
+            trade.quantity
+        } else {
+            -trade.quantity
+        };
+        quantities.entry(trade.symbol.clone()).or_default().0 += signed;
+    }
+    for position in broker {
+        let symbol = db::clean_symbol(&position.symbol)?;
+        quantities.entry(symbol).or_default().1 += position.quantity;
+    }
+
+    let discrepancies: Vec<Discrepancy> = quantities
+        .into_iter()
+        .filter(|(_, (tracked, broker))| tracked != broker)
+        .map(|(symbol, (tracked_qty, broker_qty))| Discrepancy {
+            symbol,
+            tracked_qty,
+            broker_qty,
+            diff: broker_qty - tracked_qty,
+        })
+        .collect();
+
+    Ok(ReconcileReport {
+        in_sync: discrepancies.is_empty(),
+        discrepancies,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Stale positions
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Serialize)]
+pub struct StalePosition {
+    symbol: String,
+    quantity: i64,
+    market_value: f64,
+    last_activity: String,
+    days_dormant: i64,
+}
+
+/// Small open positions with no entry or exit on the symbol for at least
+/// `days` days. Symbols without a price in `prices` are valued at cost.
+pub(crate) fn find_stale(
+    trades: &[TradeRow],
+    prices: &HashMap<String, f64>,
+    today: NaiveDate,
+    days: u32,
+) -> Vec<StalePosition> {
+    // (signed open quantity, open value, last activity)
+    let mut by_symbol: BTreeMap<&str, (i64, f64, Option<NaiveDate>)> = BTreeMap::new();
+    for trade in trades {
+        let entry = by_symbol.entry(&trade.symbol).or_default();
+        let dates = [Some(trade.entry_date.as_str()), trade.exit_date.as_deref()];
+        for date in dates.into_iter().flatten().filter_map(tax::trade_date) {
+            entry.2 = entry.2.max(Some(date));
+        }
+
+        if trade.is_open() {
+            let signed = if trade.is_long() {
+                trade.quantity
+            } else {
+                -trade.quantity
+            };
+            let price = prices
+                .get(&trade.symbol)
+                .copied()
+                .unwrap_or(trade.entry_price);
+            entry.0 += signed;
+            entry.1 += signed as f64 * price;
+        }
+    }
+
+    by_symbol
+        .into_iter()
+        .filter_map(|(symbol, (quantity, value, last))| {
+            let last = last?;
+            let dormant = (today - last).num_days();
+            (quantity != 0 && value.abs() < STALE_MAX_VALUE && dormant >= days as i64).then(|| {
+                StalePosition {
+                    symbol: symbol.to_string(),
+                    quantity,
+                    market_value: value,
+                    last_activity: last.to_string(),
+                    days_dormant: dormant,
+                }
+            })
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Allocation drift
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AllocationAlert {
+    symbol: String,
+    weight_pct: f64,
+    max_pct: f64,
+}
+
+/// Either kind of drift, as sent with `allocation-drift`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AllocationDrift {
+    MaxWeight(AllocationAlert),
+    Target(TargetDrift),
+}
+
+/// Symbols over the max weight and targets outside their band, so each
+/// crossing alerts once.
+#[derive(Default)]
+pub struct AllocationState(Mutex<Crossed>);
+
+#[derive(Default)]
+pub(crate) struct Crossed {
+    over: HashSet<String>,
+    drifted: HashSet<(String, String)>,
+}
+
+/// Positions whose share of gross exposure has just risen past `max_pct`.
+/// `over` carries which symbols were already over between checks; a
+/// symbol that falls back under can alert again on its next crossing.
+pub(crate) fn allocation_crossings(
+    exposures: &[(String, f64)],
+    max_pct: f64,
+    over: &mut HashSet<String>,
+) -> Vec<AllocationAlert> {
+    let total: f64 = exposures.iter().map(|(_, v)| v).sum();
+    let mut alerts = Vec::new();
+    let mut now_over = HashSet::new();
+    for (symbol, value) in exposures {
+        let weight_pct = if total > 0.0 {
+            value / total * 100.0
+        } else {
+            0.0
+        };
+        if weight_pct <= max_pct {
+            continue;
+        }
+        if !over.contains(symbol) {
+            alerts.push(AllocationAlert {
+                symbol: symbol.clone(),
+                weight_pct,
+                max_pct,
+            });
+        }
+        now_over.insert(symbol.clone());
+    }
+    *over = now_over;
+    alerts
+}
+
+/// Notification title and body for a drift.
+fn drift_text(app: &AppHandle, alert: &AllocationDrift) -> (String, String) {
+    match alert {
+        AllocationDrift::MaxWeight(alert) => {
+            let weight = format!("{:.0}", alert.weight_pct);
+            let max = format!("{:.0}", alert.max_pct);
+            (
+                i18n::user_text(
+                    app,
+                    "notify.allocation_drift",
+                    &[("symbol", &alert.symbol), ("weight", &weight)],
+                ),
+                i18n::user_text(app, "notify.allocation_trim", &[("max", &max)]),
+            )
+        }
+        AllocationDrift::Target(drift) => {
+            let weight = format!("{:.0}", drift.weight_pct);
+            let target = format!("{:.0}", drift.target_pct);
+            (
+                i18n::user_text(
+                    app,
+                    "notify.allocation_target",
+                    &[("name", &drift.name), ("weight", &weight)],
+                ),
+                i18n::user_text(app, "notify.allocation_rebalance", &[("target", &target)]),
+            )
+        }
+    }
+}
+
+/// Notify for each drift. A failed notification is logged and the rest
+/// still go out: the crossings are already recorded, so returning early
+/// would lose them for good.
+fn notify_each(
+    alerts: &[AllocationDrift],
+    mut notify: impl FnMut(&AllocationDrift) -> Result<(), String>,
+) {
+    for alert in alerts {
+        if let Err(e) = notify(alert) {
+            eprintln!("[allocation] Failed to notify: {}", e);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Concentration of open positions. `prices` maps symbols to their latest
+/// quote; positions without one are valued at cost.
+#[tauri::command]
+pub async fn concentration(
+    app: AppHandle,
+    prices: Option<HashMap<String, f64>>,
+) -> Result<ConcentrationReport, String> {
+    logged!("concentration", [], async {
+        let pool = db::pool(&app).await?;
+        let trades = db::load_trades(&pool).await?;
+        Ok(concentration_of(&exposures(
+            &trades,
+            &prices.unwrap_or_default(),
+        )))
+    })
+}
+
+/// Report where tracked positions drift from a broker export. This only
+/// reads; adopting the broker's numbers goes through TradeMemory in the
+/// frontend, which owns the trades table.
+#[tauri::command]
+pub async fn reconcile(
+    app: AppHandle,
+    broker_positions: Vec<BrokerPosition>,
+) -> Result<ReconcileReport, String> {
+    logged!("reconcile", [], async {
+        let pool = db::pool(&app).await?;
+        let trades = db::load_trades(&pool).await?;
+        reconcile_positions(&trades, &broker_positions)
+    })
+}
+
+/// Leftover positions worth cleaning up: under the value threshold and
+/// untouched for `days` days.
+#[tauri::command]
+pub async fn stale_positions(
+    app: AppHandle,
+    days: u32,
+    prices: Option<HashMap<String, f64>>,
+) -> Result<Vec<StalePosition>, String> {
+    logged!("stale_positions", [days], async {
+        if days == 0 {
+            return Err("Days must be at least 1".into());
+        }
+        let pool = db::pool(&app).await?;
+        let trades = db::load_trades(&pool).await?;
+        Ok(find_stale(
+            &trades,
+            &prices.unwrap_or_default(),
+            Local::now().date_naive(),
+            days,
+        ))
+    })
+}
+
+/// Check position weights against the configured max, and against any
+/// allocation targets, after a price refresh. Emits `allocation-drift` once
+/// per crossing and notifies unless turned off. `prices` works as in
+/// `concentration`; `sectors` maps symbols to sectors for sector targets.
+#[tauri::command]
+pub async fn check_allocation(
+    app: AppHandle,
+    state: State<'_, AllocationState>,
+    prices: Option<HashMap<String, f64>>,
+    sectors: Option<HashMap<String, String>>,
+) -> Result<Vec<AllocationDrift>, String> {
+    logged!("check_allocation", [], async {
+        let pool = db::pool(&app).await?;
+        let trades = db::load_trades(&pool).await?;
+        let targets = allocation::load_targets(&pool).await?;
+        let exposures = symbol_exposures(&trades, &prices.unwrap_or_default());
+        let weights = allocation::group_weights(&exposures, &sectors.unwrap_or_default());
+        let settings = settings::load_settings(&app);
+        let alerts: Vec<AllocationDrift> = {
+            let mut crossed = state.0.lock().unwrap_or_else(|e| e.into_inner());
+            let over = allocation_crossings(
+                &exposures,
+                settings.max_position_weight_pct,
+                &mut crossed.over,
+            );
+            let drifted = allocation::target_drifts(
+                &weights,
+                &targets,
+                settings.allocation_drift_band_pct,
+                &mut crossed.drifted,
+            );
+            over.into_iter()
+                .map(AllocationDrift::MaxWeight)
+                .chain(drifted.into_iter().map(AllocationDrift::Target))
+                .collect()
+        };
+
+        session::alerts_fired(alerts.len());
+        for alert in &alerts {
+            let _ = app.emit("allocation-drift", alert);
+        }
+        if settings.notify_on_allocation_drift {
+            notify_each(&alerts, |alert| {
+                let (title, body) = drift_text(&app, alert);
+                notifications::show_limited(&app, &title, &body).map(|_| ())
+            });
+        }
+        Ok(alerts)
+    })
+}
+
+/// How far, in percentage points either way, a holding may drift from its
+/// allocation target before `check_allocation` flags it.
+#[tauri::command]
+pub fn set_allocation_drift_band(app: AppHandle, pct: f64) -> Result<(), String> {
+    logged!("set_allocation_drift_band", [pct], {
+        let patch = SettingsPatch {
+            allocation_drift_band_pct: Some(pct),
+            ..Default::default()
+        };
+        settings::update_settings(&app, patch).map(|_| ())
+    })
+}
+
+/// Largest share of the portfolio, in percent, one position may reach
+/// before `check_allocation` flags it.
+#[tauri::command]
+pub fn set_max_position_weight(app: AppHandle, pct: f64) -> Result<(), String> {
+    logged!("set_max_position_weight", [pct], {
+        let patch = SettingsPatch {
+            max_position_weight_pct: Some(pct),
+            ..Default::default()
+        };
+        settings::update_settings(&app, patch).map(|_| ())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing::{closed, open};
+
+    #[test]
+    fn allocation_alerts_once_per_crossing() {
+        let mut over = HashSet::new();
+        let at = |aapl: f64| {
+            vec![
+                ("AAPL".to_string(), aapl),
+                ("GOOG".to_string(), 3_000.0),
+                ("MSFT".to_string(), 3_000.0),
+                ("NVDA".to_string(), 3_000.0),
+            ]
+        };
+
+        assert!(allocation_crossings(&at(1_000.0), 40.0, &mut over).is_empty());
+        // AAPL's gain takes it past 40% of the portfolio
+        let alerts = allocation_crossings(&at(9_000.0), 40.0, &mut over);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].symbol, "AAPL");
+        assert_eq!(alerts[0].weight_pct, 50.0);
+        assert!(allocation_crossings(&at(10_000.0), 40.0, &mut over).is_empty());
+
+        allocation_crossings(&at(2_000.0), 40.0, &mut over);
+        assert_eq!(allocation_crossings(&at(9_000.0), 40.0, &mut over).len(), 1);
+    }
+
+    /// AAPL and MSFT both drifting 20 points off an even split.
+    async fn drifting_targets(drifted: &mut HashSet<(String, String)>) -> Vec<AllocationDrift> {
+        let pool = crate::db::testing::pool().await;
+        let split = HashMap::from([("AAPL".to_string(), 50.0), ("MSFT".to_string(), 50.0)]);
+        allocation::save_targets(&pool, "symbol", &split)
+            .await
+            .unwrap();
+        let targets = allocation::load_targets(&pool).await.unwrap();
+        let exposures = vec![("AAPL".to_string(), 70.0), ("MSFT".to_string(), 30.0)];
+        let weights = allocation::group_weights(&exposures, &HashMap::new());
+        allocation::target_drifts(&weights, &targets, 5.0, drifted)
+            .into_iter()
+            .map(AllocationDrift::Target)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn a_failed_notification_does_not_drop_the_rest() {
+        let exposures = vec![("AAPL".to_string(), 60.0), ("MSFT".to_string(), 40.0)];
+        let max_weight: Vec<AllocationDrift> =
+            allocation_crossings(&exposures, 30.0, &mut HashSet::new())
+                .into_iter()
+                .map(AllocationDrift::MaxWeight)
+                .collect();
+        let target = drifting_targets(&mut HashSet::new()).await;
+
+        for (kind, alerts) in [("max weight", max_weight), ("target", target)] {
+            assert_eq!(alerts.len(), 2, "{}", kind);
+            let mut attempted = 0;
+            notify_each(&alerts, |_| {
+                attempted += 1;
+                Err("rate limited".to_string())
+            });
+            assert_eq!(attempted, 2, "{}", kind);
+        }
+    }
+
+    #[tokio::test]
+    async fn two_targets_crossing_in_one_pass_are_both_recorded() {
+        let mut drifted = HashSet::new();
+        assert_eq!(drifting_targets(&mut drifted).await.len(), 2);
+        assert_eq!(drifted.len(), 2);
+        assert!(drifting_targets(&mut drifted).await.is_empty());
+    }
+
+    #[test]
+    fn known_weights_give_known_hhi() {
+        let report = concentration_of(&[50.0, 30.0, 20.0]);
+        assert!((report.hhi - 0.38).abs() < 1e-9);
+        assert_eq!(report.label, "very concentrated");
+        assert!((report.top5_pct - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn single_position_is_fully_concentrated() {
+        let report = concentration_of(&[1_234.0]);
+        assert_eq!(report.hhi, 1.0);
+        assert_eq!(report.positions, 1);
+    }
+
+    #[test]
+    fn equal_weights_are_diversified() {
+        let report = concentration_of(&[10.0; 10]);
+        assert!((report.hhi - 0.1).abs() < 1e-9);
+        assert_eq!(report.label, "diversified");
+        assert!((report.top5_pct - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn exposures_merge_lots_and_skip_closed() {
+        let trades = vec![
+            open(1, "AAPL", 10, "2024-01-02"),
+            open(2, "AAPL", 10, "2024-02-02"),
+            open(3, "MSFT", 5, "2024-01-02"),
+            closed(4, "NVDA", 10, "2024-01-02", "2024-03-01", 50.0),
+        ];
+        let prices = HashMap::from([("MSFT".to_string(), 300.0)]);
+
+        assert_eq!(exposures(&trades, &prices), vec![2_000.0, 1_500.0]);
+    }
+
+    fn broker(symbol: &str, quantity: i64) -> BrokerPosition {
+        BrokerPosition {
+            symbol: symbol.into(),
+            quantity,
+        }
+    }
+
+    #[test]
+    fn matching_positions_are_in_sync() {
+        let trades = vec![
+            open(1, "AAPL", 10, "2024-01-02"),
+            open(2, "AAPL", 5, "2024-02-02"),
+        ];
+
+        let report = reconcile_positions(&trades, &[broker("aapl", 15)]).unwrap();
+        assert!(report.in_sync);
+    }
+
+    #[test]
+    fn reports_extra_missing_and_mismatched() {
+        let trades = vec![
+            open(1, "AAPL", 10, "2024-01-02"),
+            open(2, "MSFT", 5, "2024-01-02"),
+            closed(3, "TSLA", 10, "2024-01-02", "2024-03-01", 50.0),
+        ];
+        let positions = [broker("AAPL", 12), broker("NVDA", 3), broker("TSLA", 0)];
+
+        let report = reconcile_positions(&trades, &positions).unwrap();
+        assert!(!report.in_sync);
+        let diffs: Vec<(&str, i64)> = report
+            .discrepancies
+            .iter()
+            .map(|d| (d.symbol.as_str(), d.diff))
+            .collect();
+        assert_eq!(diffs, vec![("AAPL", 2), ("MSFT", -5), ("NVDA", 3)]);
+    }
+
+    #[test]
+    fn only_small_dormant_positions_are_stale() {
+        let trades = vec![
+            // Dormant leftover: one share worth 100 at cost, 50 now
+            open(1, "AAPL", 1, "2024-01-02"),
+            // Same size, but a sale last week counts as activity
+            open(2, "MSFT", 1, "2024-01-02"),
+            closed(3, "MSFT", 10, "2024-01-02", "2024-06-24", 20.0),
+            // Dormant but too valuable to be a leftover
+            open(4, "NVDA", 10, "2024-01-02"),
+        ];
+        let prices = HashMap::from([("AAPL".to_string(), 50.0)]);
+        let today = NaiveDate::from_ymd_opt(2024, 7, 1).unwrap();
+
+        let stale = find_stale(&trades, &prices, today, 30);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].symbol, "AAPL");
+        assert_eq!(stale[0].market_value, 50.0);
+        assert_eq!(stale[0].last_activity, "2024-01-02");
+        assert_eq!(stale[0].days_dormant, 181);
+    }
+}
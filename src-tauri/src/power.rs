@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::settings::{self, SettingsPatch};
+
+/// Tauri doesn't surface OS power events, so sleep is inferred: the tokio
+/// timer stops while the machine sleeps but the wall clock doesn't.
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A late wake-up shorter than this is just a busy machine.
+const SLEEP_GAP: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Serialize)]
+struct ResumedPayload {
+    slept_secs: u64,
+}
+
+/// How long the machine was asleep, judging by how much more wall-clock
+/// time passed between two checks than the timer waited.
+pub(crate) fn slept_for(
+    before: DateTime<Utc>,
+    after: DateTime<Utc>,
+    waited: Duration,
+) -> Option<Duration> {
+    let elapsed = (after - before).to_std().ok()?;
+    let extra = elapsed.checked_sub(waited)?;
+    (extra >= SLEEP_GAP).then_some(extra)
+}
+
+/// Emit `resumed-from-sleep` after the machine wakes so the frontend
+/// monitors refresh right away instead of showing stale quotes until their
+/// next poll. The scheduler reads the wall clock on every tick, so it needs
+/// no re-sync of its own.
+pub fn spawn_sleep_watch(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last = Utc::now();
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let now = Utc::now();
+            if let Some(slept) = slept_for(last, now, CHECK_INTERVAL) {
+                if settings::load_settings(&app).refresh_on_resume {
+                    let payload = ResumedPayload {
+                        slept_secs: slept.as_secs(),
+                    };
+                    let _ = app.emit("resumed-from-sleep", payload);
+                }
+            }
+            last = now;
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Whether waking from sleep triggers an immediate refresh.
+#[tauri::command]
+pub fn set_refresh_on_resume(app: AppHandle, enabled: bool) -> Result<(), String> {
+    logged!("set_refresh_on_resume", [enabled], {
+        let patch = SettingsPatch {
+            refresh_on_resume: Some(enabled),
+            ..Default::default()
+        };
+        settings::update_settings(&app, patch).map(|_| ())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_710_000_000 + secs, 0).unwrap()
+    }
+
+    #[test]
+    fn gaps_beyond_the_threshold_count_as_sleep() {
+        let waited = Duration::from_secs(15);
+        assert_eq!(slept_for(at(0), at(15), waited), None);
+        // a sluggish timer isn't sleep
+        assert_eq!(slept_for(at(0), at(45), waited), None);
+        assert_eq!(
+            slept_for(at(0), at(3_615), waited),
+            Some(Duration::from_secs(3_600))
+        );
+    }
+
+    #[test]
+    fn clock_going_backwards_is_ignored() {
+        assert_eq!(slept_for(at(100), at(0), Duration::from_secs(15)), None);
+    }
+}
@@ -0,0 +1,109 @@
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+
+use crate::{config, db, settings, STORE_FILE};
+
+const CONFIRMATION_PHRASE: &str = "DELETE ALL MY DATA";
+
+fn check_confirmation(phrase: &str) -> Result<(), String> {
+    if phrase == CONFIRMATION_PHRASE {
+        Ok(())
+    } else {
+        Err(format!(
+            "Type \"{}\" exactly to confirm",
+            CONFIRMATION_PHRASE
+        ))
+    }
+}
+
+/// Delete every row from every table in one transaction. The schema and
+/// migration history stay so nothing has to be recreated. `trades` belongs
+/// to the frontend's TradeMemory, which clears it on `data-wiped`.
+pub(crate) async fn wipe_tables(pool: &SqlitePool) -> Result<(), String> {
+    let tables: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' \
+         AND name NOT LIKE 'sqlite_%' AND name NOT IN ('_sqlx_migrations', 'trades')",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    for table in &tables {
+        sqlx::query(&format!("DELETE FROM \"{}\"", table.replace('"', "\"\"")))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    // Restart AUTOINCREMENT ids along with the data
+    sqlx::query("DELETE FROM sqlite_sequence")
+        .execute(&mut *tx)
+        .await
+        .ok();
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Erase all trades, notes, tags and settings. Unlike `reset_settings` this
+/// also empties the database; trades go when the frontend handles
+/// `data-wiped`. Only whether onboarding was completed survives, so the user
+/// isn't walked through it again.
+#[tauri::command]
+pub async fn delete_all_data(app: AppHandle, confirmation_phrase: String) -> Result<(), String> {
+    logged!("delete_all_data", [], async {
+        check_confirmation(&confirmation_phrase)?;
+
+        let pool = db::pool(&app).await?;
+        wipe_tables(&pool).await?;
+
+        let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+        let onboarded = store.get(settings::ONBOARDED_KEY);
+        store.clear();
+        if let Some(onboarded) = onboarded {
+            store.set(settings::ONBOARDED_KEY, onboarded);
+        }
+        store.save().map_err(|e| e.to_string())?;
+        config::migrate_store(&app)?;
+
+        let _ = app.emit("data-wiped", ());
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing::{self, insert_open};
+    use crate::notes::{read_note, write_note};
+
+    #[test]
+    fn only_the_exact_phrase_confirms() {
+        assert!(check_confirmation("DELETE ALL MY DATA").is_ok());
+        assert!(check_confirmation("delete all my data").is_err());
+        assert!(check_confirmation("DELETE ALL MY DATA ").is_err());
+        assert!(check_confirmation("").is_err());
+    }
+
+    #[tokio::test]
+    async fn wipe_empties_backend_tables_but_keeps_schema() {
+        let pool = testing::pool().await;
+        insert_open(&pool, "AAPL", 10, 150.0).await;
+        write_note(&pool, "AAPL", "thesis").await.unwrap();
+
+        wipe_tables(&pool).await.unwrap();
+
+        let trades: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM trades")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        // Left for the frontend to clear
+        assert_eq!(trades, 1);
+        assert!(read_note(&pool, "AAPL").await.unwrap().is_none());
+
+        write_note(&pool, "MSFT", "still works").await.unwrap();
+    }
+}
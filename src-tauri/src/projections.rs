@@ -0,0 +1,156 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+
+/// Enough simulations for stable percentiles without pinning a core.
+const MAX_SIMS: u32 = 10_000;
+const MAX_YEARS: u32 = 50;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Percentiles {
+    p10: f64,
+    p50: f64,
+    p90: f64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct MonteCarloResult {
+    /// Distribution of the portfolio value after the final year.
+    ending: Percentiles,
+    /// Percentile bands at the end of each year, starting with year 1.
+    path: Vec<Percentiles>,
+    sims: u32,
+}
+
+/// Nearest-rank percentile of already sorted values.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank]
+}
+
+fn bands(values: &[f64]) -> Percentiles {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    Percentiles {
+        p10: percentile(&sorted, 0.10),
+        p50: percentile(&sorted, 0.50),
+        p90: percentile(&sorted, 0.90),
+    }
+}
+
+/// Standard normal draw via Box-Muller.
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    let u1: f64 = 1.0 - rng.random::<f64>();
+    let u2: f64 = rng.random();
+    (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+}
+
+/// Project `start_value` forward under geometric Brownian motion with yearly
+/// steps. Returns and volatility are annual fractions (0.07 for 7%).
+pub(crate) fn simulate(
+    start_value: f64,
+    annual_return: f64,
+    annual_vol: f64,
+    years: u32,
+    sims: u32,
+    seed: Option<u64>,
+) -> Result<MonteCarloResult, String> {
+    if !start_value.is_finite() || start_value <= 0.0 {
+        return Err("Start value must be positive".into());
+    }
+    if !annual_return.is_finite() || !annual_vol.is_finite() || annual_vol < 0.0 {
+        return Err("Return and volatility must be finite, volatility non-negative".into());
+    }
+    if years == 0 || years > MAX_YEARS {
+        return Err(format!("Years must be between 1 and {}", MAX_YEARS));
+    }
+    if sims == 0 {
+        return Err("At least one simulation is required".into());
+    }
+    let sims = sims.min(MAX_SIMS);
+
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    };
+    let drift = annual_return - annual_vol * annual_vol / 2.0;
+
+    let mut values = vec![start_value; sims as usize];
+    let mut path = Vec::with_capacity(years as usize);
+    for _ in 0..years {
+        for value in values.iter_mut() {
+            *value *= (drift + annual_vol * standard_normal(&mut rng)).exp();
+        }
+        path.push(bands(&values));
+    }
+
+    Ok(MonteCarloResult {
+        ending: bands(&values),
+        path,
+        sims,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn monte_carlo(
+    start_value: f64,
+    annual_return: f64,
+    annual_vol: f64,
+    years: u32,
+    sims: u32,
+    seed: Option<u64>,
+) -> Result<MonteCarloResult, String> {
+    logged!(
+        "monte_carlo",
+        [start_value, annual_return, annual_vol, years, sims, seed],
+        async {
+            tauri::async_runtime::spawn_blocking(move || {
+                simulate(start_value, annual_return, annual_vol, years, sims, seed)
+            })
+            .await
+            .map_err(|e| e.to_string())?
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_seed_is_reproducible() {
+        let a = simulate(10_000.0, 0.07, 0.15, 10, 500, Some(42)).unwrap();
+        let b = simulate(10_000.0, 0.07, 0.15, 10, 500, Some(42)).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.path.len(), 10);
+        assert!(a.ending.p10 < a.ending.p50 && a.ending.p50 < a.ending.p90);
+    }
+
+    #[test]
+    fn zero_volatility_compounds_deterministically() {
+        let result = simulate(1_000.0, 0.05, 0.0, 2, 10, Some(1)).unwrap();
+        let expected = 1_000.0 * (0.10f64).exp();
+        assert!((result.ending.p50 - expected).abs() < 1e-9);
+        assert_eq!(result.ending.p10, result.ending.p90);
+    }
+
+    #[test]
+    fn median_tracks_gbm_drift() {
+        let result = simulate(1_000.0, 0.08, 0.2, 5, 10_000, Some(7)).unwrap();
+        let expected = 1_000.0 * ((0.08 - 0.02) * 5.0f64).exp();
+        assert!((result.ending.p50 / expected - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn caps_sims_and_rejects_bad_input() {
+        let result = simulate(1_000.0, 0.05, 0.1, 1, MAX_SIMS + 1, Some(1)).unwrap();
+        assert_eq!(result.sims, MAX_SIMS);
+        assert!(simulate(0.0, 0.05, 0.1, 1, 10, None).is_err());
+        assert!(simulate(1_000.0, 0.05, -0.1, 1, 10, None).is_err());
+        assert!(simulate(1_000.0, 0.05, 0.1, 0, 10, None).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+//! What each market data provider can serve, so the frontend can hide
+//! features a provider lacks instead of surfacing its errors. Quotes are
+//! fetched by the frontend; each table mirrors the calls its client makes.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ProviderCapabilities {
+    quotes: bool,
+    /// Several symbols in one request.
+    batch_quotes: bool,
+    historical: bool,
+    news: bool,
+    dividends: bool,
+    earnings: bool,
+    /// Streaming quotes rather than polling.
+    websocket: bool,
+    /// Pre- and post-market prices.
+    extended_hours: bool,
+    search: bool,
+}
+
+/// Alpha Vantage as `AlphaVantageClient` uses it. Bulk quotes and the
+/// dividends endpoint are never called, and intraday series are read as
+/// regular-session data.
+const ALPHA_VANTAGE: ProviderCapabilities = ProviderCapabilities {
+    quotes: true,
+    batch_quotes: false,
+    historical: true,
+    news: true,
+    dividends: false,
+    earnings: true,
+    websocket: false,
+    extended_hours: false,
+    search: true,
+};
+
+/// Registered providers by id.
+pub(crate) const PROVIDERS: &[(&str, ProviderCapabilities)] = &[("alphavantage", ALPHA_VANTAGE)];
+
+pub(crate) fn capabilities(provider: &str) -> Result<ProviderCapabilities, String> {
+    let id = provider.trim().to_ascii_lowercase();
+    PROVIDERS
+        .iter()
+        .find(|(name, _)| *name == id)
+        .map(|(_, caps)| *caps)
+        .ok_or_else(|| {
+            let known: Vec<&str> = PROVIDERS.iter().map(|(name, _)| *name).collect();
+            format!(
+                "Unknown provider '{}', expected one of: {}",
+                provider,
+                known.join(", ")
+            )
+        })
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn provider_capabilities(provider: String) -> Result<ProviderCapabilities, String> {
+    logged!("provider_capabilities", [provider], {
+        capabilities(&provider)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_registered_provider_reports_capabilities() {
+        for (name, caps) in PROVIDERS {
+            assert_eq!(capabilities(name).unwrap(), *caps);
+            assert!(caps.quotes, "{} serves no quotes", name);
+        }
+        assert_eq!(
+            capabilities(" AlphaVantage ").unwrap(),
+            capabilities("alphavantage").unwrap()
+        );
+        assert!(capabilities("polygon")
+            .unwrap_err()
+            .contains("alphavantage"));
+    }
+}
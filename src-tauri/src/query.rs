@@ -0,0 +1,227 @@
+use std::path::Path;
+use std::time::Duration;
+
+use base64::Engine;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteRow};
+use sqlx::{Column, Row, SqlitePool, TypeInfo, ValueRef};
+use tauri::AppHandle;
+
+use crate::diagnostics;
+
+const MAX_ROWS: usize = 1_000;
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize)]
+pub struct QueryResult {
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+    /// More rows matched than the cap lets through.
+    truncated: bool,
+}
+
+/// Accept a single SELECT, WITH or PRAGMA statement and nothing else. This
+/// is the first line of defence; the connection itself is read-only too.
+pub(crate) fn check_read_only(sql: &str) -> Result<&str, String> {
+    let sql = sql.trim().trim_end_matches(';').trim_end();
+    if sql.is_empty() {
+        return Err("Query is empty".into());
+    }
+    if sql.contains(';') {
+        return Err("Only one statement can be run at a time".into());
+    }
+
+    let keyword = sql
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or_default()
+        .to_uppercase();
+    match keyword.as_str() {
+        "SELECT" | "WITH" => Ok(sql),
+        // `PRAGMA name = value` changes settings rather than reading them
+        "PRAGMA" if !sql.contains('=') => Ok(sql),
+        "PRAGMA" => Err("PRAGMA assignments aren't allowed; only reads are".into()),
+        _ => Err(format!(
+            "Only SELECT and PRAGMA queries are allowed, not {}",
+            if keyword.is_empty() { "this" } else { &keyword }
+        )),
+    }
+}
+
+pub(crate) fn json_value(row: &SqliteRow, index: usize) -> Result<Value, String> {
+    let raw = row.try_get_raw(index).map_err(|e| e.to_string())?;
+    if raw.is_null() {
+        return Ok(Value::Null);
+    }
+    let kind = raw.type_info().name().to_string();
+    let value = match kind.as_str() {
+        "INTEGER" => Value::from(row.try_get::<i64, _>(index).map_err(|e| e.to_string())?),
+        "REAL" => Value::from(row.try_get::<f64, _>(index).map_err(|e| e.to_string())?),
+        "BLOB" => Value::from(
+            base64::engine::general_purpose::STANDARD.encode(
+                row.try_get::<Vec<u8>, _>(index)
+                    .map_err(|e| e.to_string())?,
+            ),
+        ),
+        _ => Value::from(row.try_get::<String, _>(index).map_err(|e| e.to_string())?),
+    };
+    Ok(value)
+}
+
+/// Run a checked query on `pool`, capping rows and time.
+pub(crate) async fn run_on(pool: &SqlitePool, sql: &str) -> Result<QueryResult, String> {
+    let sql = check_read_only(sql)?;
+    // SQLite accepts CTEs in subqueries, so both SELECT forms can be capped
+    // in the database; PRAGMA output is small and is cut afterwards.
+    let is_pragma = sql
+        .get(..6)
+        .is_some_and(|k| k.eq_ignore_ascii_case("PRAGMA"));
+    let capped = if is_pragma {
+        sql.to_string()
+    } else {
+        format!("SELECT * FROM ({}) LIMIT {}", sql, MAX_ROWS + 1)
+    };
+
+    let mut rows = tokio::time::timeout(QUERY_TIMEOUT, sqlx::query(&capped).fetch_all(pool))
+        .await
+        .map_err(|_| format!("Query took longer than {}s", QUERY_TIMEOUT.as_secs()))?
+        .map_err(|e| e.to_string())?;
+
+    let truncated = rows.len() > MAX_ROWS;
+    rows.truncate(MAX_ROWS);
+    let columns = rows
+        .first()
+        .map(|row| row.columns().iter().map(|c| c.name().to_string()).collect())
+        .unwrap_or_default();
+    let rows = rows
+        .iter()
+        .map(|row| (0..row.len()).map(|i| json_value(row, i)).collect())
+        .collect::<Result<_, String>>()?;
+
+    Ok(QueryResult {
+        columns,
+        rows,
+        truncated,
+    })
+}
+
+/// A fresh read-only connection to the database file, separate from the
+/// SQL plugin's read-write pool.
+async fn read_only_pool(path: &Path) -> Result<SqlitePool, String> {
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Run a power user's own SELECT or PRAGMA. Anything that could write is
+/// rejected before it reaches SQLite, and the connection is read-only
+/// regardless. The SQL isn't logged, since it may quote personal data.
+#[tauri::command]
+pub async fn run_query(app: AppHandle, sql: String) -> Result<QueryResult, String> {
+    logged!("run_query", [], async {
+        let pool = read_only_pool(&diagnostics::db_path(&app)?).await?;
+        let result = run_on(&pool, &sql).await;
+        pool.close().await;
+        result
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing;
+
+    #[test]
+    fn writes_are_rejected_before_running() {
+        for sql in [
+            "INSERT INTO trades (symbol) VALUES ('AAPL')",
+            "update trades SET quantity = 0",
+            "  DELETE FROM trades",
+            "DROP TABLE trades",
+            "SELECT 1; DELETE FROM trades",
+            "PRAGMA user_version = 5",
+            "ATTACH DATABASE 'x.db' AS x",
+            "",
+        ] {
+            assert!(check_read_only(sql).is_err(), "{} should be rejected", sql);
+        }
+        assert!(check_read_only("select * from trades;").is_ok());
+        assert!(check_read_only("PRAGMA table_info(trades)").is_ok());
+    }
+
+    #[tokio::test]
+    async fn select_returns_columns_and_typed_rows() {
+        let pool = testing::pool().await;
+        testing::insert_open(&pool, "AAPL", 10, 150.5).await;
+
+        let result = run_on(
+            &pool,
+            "SELECT symbol, quantity, entry_price, exit_price FROM trades",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            result.columns,
+            ["symbol", "quantity", "entry_price", "exit_price"]
+        );
+        assert_eq!(
+            result.rows,
+            vec![vec![
+                Value::from("AAPL"),
+                Value::from(10),
+                Value::from(150.5),
+                Value::Null
+            ]]
+        );
+        assert!(!result.truncated);
+    }
+
+    #[tokio::test]
+    async fn row_count_is_capped() {
+        let pool = testing::pool().await;
+        let result = run_on(
+            &pool,
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n LIMIT 5000) \
+             SELECT i FROM n",
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.rows.len(), MAX_ROWS);
+        assert!(result.truncated);
+    }
+
+    #[tokio::test]
+    async fn connection_refuses_writes_that_slip_through() {
+        let path = std::env::temp_dir().join(format!("sa-run-query-{}.db", std::process::id()));
+        let writable = SqlitePoolOptions::new()
+            .connect_with(
+                SqliteConnectOptions::new()
+                    .filename(&path)
+                    .create_if_missing(true),
+            )
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE t (x INTEGER)")
+            .execute(&writable)
+            .await
+            .unwrap();
+        writable.close().await;
+
+        let pool = read_only_pool(&path).await.unwrap();
+        let err = sqlx::query("INSERT INTO t VALUES (1)")
+            .execute(&pool)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("readonly"));
+        pool.close().await;
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,401 @@
+use printpdf::{
+    BuiltinFont, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference,
+};
+use tauri::{AppHandle, Emitter};
+
+use crate::db::{self, TradeRow};
+use crate::{export, format, settings};
+
+const TOP_MOVERS: usize = 5;
+
+// ---------------------------------------------------------------------------
+// Report model
+// ---------------------------------------------------------------------------
+
+pub(crate) struct ReportSummary {
+    pub open_positions: usize,
+    pub open_cost_basis: f64,
+    pub trades_closed: usize,
+    pub realized_pnl: f64,
+    pub win_rate: Option<f64>,
+}
+
+pub(crate) struct PositionLine {
+    pub symbol: String,
+    pub quantity: i64,
+    pub entry_price: f64,
+    pub entry_date: String,
+    pub cost_basis: f64,
+}
+
+#[derive(Clone)]
+pub(crate) struct RealizedLine {
+    pub symbol: String,
+    pub quantity: i64,
+    pub entry_price: f64,
+    pub exit_price: f64,
+    pub exit_date: String,
+    pub pnl_dollars: f64,
+    pub pnl_percent: f64,
+}
+
+/// Everything that goes into a monthly report, independent of layout.
+pub(crate) struct ReportModel {
+    pub month: String,
+    pub summary: ReportSummary,
+    pub positions: Vec<PositionLine>,
+    pub realized: Vec<RealizedLine>,
+    pub top_movers: Vec<RealizedLine>,
+}
+
+/// Validate a `YYYY-MM` month string.
+pub(crate) fn validate_month(month: &str) -> Result<(), String> {
+    let invalid = || format!("Invalid month '{}', expected YYYY-MM", month);
+
+    let (year, mon) = month.split_once('-').ok_or_else(invalid)?;
+    if year.len() != 4 || mon.len() != 2 {
+        return Err(invalid());
+    }
+    year.parse::<u16>().map_err(|_| invalid())?;
+    match mon.parse::<u8>() {
+        Ok(1..=12) => Ok(()),
+        _ => Err(invalid()),
+    }
+}
+
+/// `YYYY-MM` prefix of an ISO-8601 date.
+fn month_of(date: &str) -> &str {
+    date.get(..7).unwrap_or(date)
+}
+
+/// `YYYY-MM-DD` prefix of an ISO-8601 timestamp.
+fn date_only(date: &str) -> &str {
+    date.get(..10).unwrap_or(date)
+}
+
+/// Build the report for `month` from the full trade ledger.
+///
+/// Positions are those open at the end of the month; realized gains are
+/// trades closed during it.
+pub(crate) fn build_report_model(trades: &[TradeRow], month: &str) -> ReportModel {
+    let positions: Vec<PositionLine> = trades
+        .iter()
+        .filter(|t| month_of(&t.entry_date) <= month)
+        .filter(|t| match &t.exit_date {
+            Some(exit) => month_of(exit) > month,
+            None => t.is_open(),
+        })
+        .map(|t| PositionLine {
+            symbol: t.symbol.clone(),
+            quantity: t.quantity,
+            entry_price: t.entry_price,
+            entry_date: t.entry_date.clone(),
+            cost_basis: t.quantity as f64 * t.entry_price,
+        })
+        .collect();
+
+    let realized: Vec<RealizedLine> = trades
+        .iter()
+        .filter(|t| !t.is_open())
+        .filter_map(|t| {
+            let exit_date = t.exit_date.as_deref()?;
+            if month_of(exit_date) != month {
+                return None;
+            }
+            Some(RealizedLine {
+                symbol: t.symbol.clone(),
+                quantity: t.quantity,
+                entry_price: t.entry_price,
+                exit_price: t.exit_price?,
+                exit_date: exit_date.to_string(),
+                pnl_dollars: t.pnl_dollars.unwrap_or(0.0),
+                pnl_percent: t.pnl_percent.unwrap_or(0.0),
+            })
+        })
+        .collect();
+
+    let mut top_movers = realized.clone();
+    top_movers.sort_by(|a, b| b.pnl_percent.abs().total_cmp(&a.pnl_percent.abs()));
+    top_movers.truncate(TOP_MOVERS);
+
+    let wins = realized.iter().filter(|r| r.pnl_dollars > 0.0).count();
+    let summary = ReportSummary {
+        open_positions: positions.len(),
+        open_cost_basis: positions.iter().map(|p| p.cost_basis).sum(),
+        trades_closed: realized.len(),
+        realized_pnl: realized.iter().map(|r| r.pnl_dollars).sum(),
+        win_rate: (!realized.is_empty()).then(|| wins as f64 / realized.len() as f64 * 100.0),
+    };
+
+    ReportModel {
+        month: month.to_string(),
+        summary,
+        positions,
+        realized,
+        top_movers,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PDF layout
+// ---------------------------------------------------------------------------
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const LINE_HEIGHT: f32 = 6.0;
+
+/// Minimal top-to-bottom text writer that starts a new page when it runs
+/// out of room.
+struct PdfWriter {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    y: f32,
+}
+
+impl PdfWriter {
+    fn new(title: &str) -> Result<Self, String> {
+        let (doc, page, layer) =
+            PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+        let regular = doc
+            .add_builtin_font(BuiltinFont::Helvetica)
+            .map_err(|e| e.to_string())?;
+        let bold = doc
+            .add_builtin_font(BuiltinFont::HelveticaBold)
+            .map_err(|e| e.to_string())?;
+        let layer = doc.get_page(page).get_layer(layer);
+
+        Ok(Self {
+            doc,
+            layer,
+            regular,
+            bold,
+            y: PAGE_HEIGHT - MARGIN,
+        })
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            let (page, layer) = self
+                .doc
+                .add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    fn title(&mut self, text: &str) {
+        self.ensure_space(LINE_HEIGHT * 2.0);
+        self.layer
+            .use_text(text, 18.0, Mm(MARGIN), Mm(self.y), &self.bold);
+        self.y -= LINE_HEIGHT * 2.0;
+    }
+
+    fn heading(&mut self, text: &str) {
+        self.y -= LINE_HEIGHT / 2.0;
+        self.ensure_space(LINE_HEIGHT * 2.0);
+        self.layer
+            .use_text(text, 13.0, Mm(MARGIN), Mm(self.y), &self.bold);
+        self.y -= LINE_HEIGHT * 1.5;
+    }
+
+    fn line(&mut self, text: &str) {
+        self.ensure_space(LINE_HEIGHT);
+        self.layer
+            .use_text(text, 10.0, Mm(MARGIN), Mm(self.y), &self.regular);
+        self.y -= LINE_HEIGHT;
+    }
+
+    /// Table row with each cell placed at a fixed offset from the margin.
+    fn row(&mut self, cells: &[(f32, String)], header: bool) {
+        self.ensure_space(LINE_HEIGHT);
+        let font = if header { &self.bold } else { &self.regular };
+        for (offset, text) in cells {
+            self.layer
+                .use_text(text.as_str(), 9.0, Mm(MARGIN + offset), Mm(self.y), font);
+        }
+        self.y -= LINE_HEIGHT;
+    }
+
+    fn finish(self) -> Result<Vec<u8>, String> {
+        self.doc.save_to_bytes().map_err(|e| e.to_string())
+    }
+}
+
+fn money(value: f64) -> String {
+    if value < 0.0 {
+        format!("-${:.2}", -value)
+    } else {
+        format!("${:.2}", value)
+    }
+}
+
+fn price(value: f64, precision: Option<u32>) -> String {
+    let places = format::price_places(value, precision);
+    if value < 0.0 {
+        format!("-${:.*}", places, -value)
+    } else {
+        format!("${:.*}", places, value)
+    }
+}
+
+fn realized_table(pdf: &mut PdfWriter, lines: &[RealizedLine], precision: Option<u32>) {
+    pdf.row(
+        &[
+            (0.0, "Symbol".into()),
+            (22.0, "Qty".into()),
+            (40.0, "Entry".into()),
+            (65.0, "Exit".into()),
+            (90.0, "Closed".into()),
+            (120.0, "P&L".into()),
+            (148.0, "Return".into()),
+        ],
+        true,
+    );
+    for line in lines {
+        pdf.row(
+            &[
+                (0.0, line.symbol.clone()),
+                (22.0, line.quantity.to_string()),
+                (40.0, price(line.entry_price, precision)),
+                (65.0, price(line.exit_price, precision)),
+                (90.0, date_only(&line.exit_date).to_string()),
+                (120.0, money(line.pnl_dollars)),
+                (148.0, format!("{:+.2}%", line.pnl_percent)),
+            ],
+            false,
+        );
+    }
+}
+
+/// `price_precision` is the user's fixed places for share prices, if any.
+pub(crate) fn render_pdf(
+    model: &ReportModel,
+    price_precision: Option<u32>,
+) -> Result<Vec<u8>, String> {
+    let mut pdf = PdfWriter::new(&format!("Stock Advisors Report {}", model.month))?;
+    pdf.title(&format!("Stock Advisors - Monthly Report {}", model.month));
+
+    let summary = &model.summary;
+    pdf.heading("Summary");
+    pdf.line(&format!("Open positions: {}", summary.open_positions));
+    pdf.line(&format!(
+        "Open cost basis: {}",
+        money(summary.open_cost_basis)
+    ));
+    pdf.line(&format!("Trades closed: {}", summary.trades_closed));
+    pdf.line(&format!("Realized P&L: {}", money(summary.realized_pnl)));
+    match summary.win_rate {
+        Some(rate) => pdf.line(&format!("Win rate: {:.1}%", rate)),
+        None => pdf.line("Win rate: n/a"),
+    }
+
+    pdf.heading("Open Positions");
+    if model.positions.is_empty() {
+        pdf.line("No open positions at month end.");
+    } else {
+        pdf.row(
+            &[
+                (0.0, "Symbol".into()),
+                (30.0, "Qty".into()),
+                (55.0, "Entry".into()),
+                (85.0, "Opened".into()),
+                (120.0, "Cost basis".into()),
+            ],
+            true,
+        );
+        for position in &model.positions {
+            pdf.row(
+                &[
+                    (0.0, position.symbol.clone()),
+                    (30.0, position.quantity.to_string()),
+                    (55.0, price(position.entry_price, price_precision)),
+                    (85.0, date_only(&position.entry_date).to_string()),
+                    (120.0, money(position.cost_basis)),
+                ],
+                false,
+            );
+        }
+    }
+
+    pdf.heading("Realized Gains");
+    if model.realized.is_empty() {
+        pdf.line("No trades closed this month.");
+    } else {
+        realized_table(&mut pdf, &model.realized, price_precision);
+    }
+
+    if !model.top_movers.is_empty() {
+        pdf.heading("Top Movers");
+        realized_table(&mut pdf, &model.top_movers, price_precision);
+    }
+
+    pdf.finish()
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn generate_report(app: AppHandle, dest: String, month: String) -> Result<(), String> {
+    logged!("generate_report", [dest, month], async {
+        validate_month(&month)?;
+        let path = export::validate_destination(&dest, "pdf")?;
+
+        let pool = db::pool(&app).await?;
+        let trades = db::load_trades(&pool).await?;
+        let model = build_report_model(&trades, &month);
+        let precision = settings::load_settings(&app).price_decimals;
+        let bytes = render_pdf(&model, precision)?;
+
+        export::write_file(&app, path.clone(), &bytes)?;
+        let _ = app.emit("report-generated", path.to_string_lossy().to_string());
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing::{closed, open};
+
+    #[test]
+    fn validate_month_accepts_only_yyyy_mm() {
+        assert!(validate_month("2024-03").is_ok());
+        assert!(validate_month("2024-13").is_err());
+        assert!(validate_month("2024-3").is_err());
+        assert!(validate_month("March").is_err());
+    }
+
+    #[test]
+    fn positions_are_those_open_at_month_end() {
+        let trades = vec![
+            // Opened in February, closed in April: still open at end of March
+            closed(1, "AAPL", 10, "2024-02-10", "2024-04-02", 100.0),
+            // Closed during March: realized, not open
+            closed(2, "MSFT", 5, "2024-02-10", "2024-03-20", -50.0),
+            // Still open, opened in March
+            open(3, "NVDA", 2, "2024-03-05"),
+            // Opened after the month
+            open(4, "AMD", 8, "2024-04-01"),
+        ];
+
+        let model = build_report_model(&trades, "2024-03");
+        let symbols: Vec<&str> = model.positions.iter().map(|p| p.symbol.as_str()).collect();
+        assert_eq!(symbols, ["AAPL", "NVDA"]);
+
+        assert_eq!(model.realized.len(), 1);
+        assert_eq!(model.summary.realized_pnl, -50.0);
+        assert_eq!(model.summary.win_rate, Some(0.0));
+    }
+
+    #[test]
+    fn empty_month_has_no_win_rate() {
+        let model = build_report_model(&[], "2024-03");
+        assert_eq!(model.summary.trades_closed, 0);
+        assert!(model.summary.win_rate.is_none());
+    }
+}
@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter};
+
+use crate::paper::{AccountState, ValidatedOrder};
+use crate::{db, settings, tax};
+
+/// Paper-trading risk limits. Unset limits aren't enforced.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RiskLimits {
+    /// Largest share of equity one symbol may take after a buy, in percent.
+    pub max_position_pct: Option<f64>,
+    /// Realized loss for the day, in dollars, after which buys are blocked.
+    pub max_daily_loss: Option<f64>,
+    /// Gross position value over equity. The paper account is cash-only, so
+    /// values below 1.0 cap how much of the account can be invested.
+    pub max_leverage: Option<f64>,
+}
+
+impl RiskLimits {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if let Some(pct) = self.max_position_pct {
+            if !pct.is_finite() || pct <= 0.0 || pct > 100.0 {
+                return Err("Max position size must be between 0 and 100%".into());
+            }
+        }
+        for (name, value) in [
+            ("Max daily loss", self.max_daily_loss),
+            ("Max leverage", self.max_leverage),
+        ] {
+            if value.is_some_and(|v| !v.is_finite() || v <= 0.0) {
+                return Err(format!("{} must be positive", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct RiskBreachPayload {
+    limit: &'static str,
+    realized_loss: f64,
+    cap: f64,
+}
+
+#[derive(sqlx::FromRow)]
+struct FillRow {
+    symbol: String,
+    side: String,
+    quantity: i64,
+    price: f64,
+    filled_at: String,
+}
+
+/// Realized P&L from sells filled on `day`, against each symbol's average
+/// cost over the whole fill history.
+fn realized_on(fills: &[FillRow], day: NaiveDate) -> f64 {
+    // (shares, total cost) per symbol
+    let mut books: HashMap<&str, (i64, f64)> = HashMap::new();
+    let mut realized = 0.0;
+
+    for fill in fills {
+        let (shares, cost) = books.entry(&fill.symbol).or_default();
+        if fill.side == "buy" {
+            *shares += fill.quantity;
+            *cost += fill.quantity as f64 * fill.price;
+            continue;
+        }
+        if *shares <= 0 {
+            continue;
+        }
+        let sold = fill.quantity.min(*shares);
+        let average = *cost / *shares as f64;
+        *shares -= sold;
+        *cost -= average * sold as f64;
+        if tax::trade_date(&fill.filled_at) == Some(day) {
+            realized += (fill.price - average) * sold as f64;
+        }
+    }
+    realized
+}
+
+/// Today's realized paper loss as a positive amount, or 0 on a winning day.
+/// The session is the UTC calendar day, matching `paper_fills.filled_at`.
+pub(crate) async fn daily_loss(pool: &SqlitePool) -> Result<f64, String> {
+    let fills: Vec<FillRow> = sqlx::query_as(
+        "SELECT symbol, side, quantity, price, filled_at FROM paper_fills ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok((-realized_on(&fills, Utc::now().date_naive())).max(0.0))
+}
+
+/// Limits `order` would breach, each message naming the limit. Sells only
+/// reduce exposure, so they are always allowed.
+pub(crate) fn check(
+    limits: &RiskLimits,
+    order: &ValidatedOrder,
+    account: &AccountState,
+    loss_today: f64,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    if order.side != "buy" {
+        return problems;
+    }
+
+    if let Some(cap) = limits.max_daily_loss {
+        if loss_today >= cap {
+            problems.push(format!(
+                "max_daily_loss: down {:.2} today against a {:.2} cap, so new entries are \
+                 blocked until tomorrow",
+                loss_today, cap
+            ));
+        }
+    }
+    if limits.max_position_pct.is_none() && limits.max_leverage.is_none() {
+        return problems;
+    }
+
+    // Buying swaps cash for shares, so equity itself doesn't change
+    let mut equity = account.cash;
+    let mut gross = 0.0;
+    for (symbol, &held) in &account.positions {
+        if symbol == &order.symbol || held == 0 {
+            continue;
+        }
+        let Some(&quote) = account.quotes.get(symbol) else {
+            problems.push(format!(
+                "No quote for {}, so position limits can't be checked",
+                symbol
+            ));
+            return problems;
+        };
+        equity += held as f64 * quote;
+        gross += held.abs() as f64 * quote;
+    }
+    let held = account.positions.get(&order.symbol).copied().unwrap_or(0);
+    equity += held as f64 * account.market_price;
+    let position = (held + order.quantity) as f64 * account.market_price;
+    gross += position.abs();
+
+    if equity <= 0.0 {
+        problems.push("Account has no equity to size a position against".into());
+        return problems;
+    }
+    if let Some(cap) = limits.max_position_pct {
+        let pct = position / equity * 100.0;
+        if pct > cap {
+            problems.push(format!(
+                "max_position_pct: {} would be {:.1}% of equity, over the {}% cap",
+                order.symbol, pct, cap
+            ));
+        }
+    }
+    if let Some(cap) = limits.max_leverage {
+        let leverage = gross / equity;
+        if leverage > cap {
+            problems.push(format!(
+                "max_leverage: gross exposure would be {:.2}x equity, over the {}x cap",
+                leverage, cap
+            ));
+        }
+    }
+    problems
+}
+
+/// Check `order` against the configured limits, reading today's loss only
+/// when a daily cap is set.
+pub(crate) async fn review(
+    app: &AppHandle,
+    order: &ValidatedOrder,
+    account: &AccountState,
+) -> Result<(), Vec<String>> {
+    let limits = settings::load_settings(app).risk_limits;
+    let loss_today = match limits.max_daily_loss {
+        Some(_) => {
+            let pool = db::pool(app).await.map_err(|e| vec![e])?;
+            daily_loss(&pool).await.map_err(|e| vec![e])?
+        }
+        None => 0.0,
+    };
+
+    let problems = check(&limits, order, account, loss_today);
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+/// After new fills, emit `risk-limit-breached` if they pushed the day's loss
+/// past the cap.
+pub(crate) async fn notify_breach(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    loss_before: f64,
+) -> Result<(), String> {
+    let Some(cap) = settings::load_settings(app).risk_limits.max_daily_loss else {
+        return Ok(());
+    };
+    let loss = daily_loss(pool).await?;
+    if loss_before < cap && loss >= cap {
+        let _ = app.emit(
+            "risk-limit-breached",
+            RiskBreachPayload {
+                limit: "max_daily_loss",
+                realized_loss: loss,
+                cap,
+            },
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(side: &str, quantity: i64, price: f64, filled_at: &str) -> FillRow {
+        FillRow {
+            symbol: "AAPL".into(),
+            side: side.into(),
+            quantity,
+            price,
+            filled_at: filled_at.into(),
+        }
+    }
+
+    fn buy(quantity: i64) -> ValidatedOrder {
+        ValidatedOrder {
+            symbol: "AAPL".into(),
+            side: "buy".into(),
+            quantity,
+            order_type: "market".into(),
+            limit_price: None,
+            stop_price: None,
+            estimated_value: quantity as f64 * 100.0,
+        }
+    }
+
+    fn account(positions: &[(&str, i64)], quotes: &[(&str, f64)]) -> AccountState {
+        AccountState {
+            cash: 10_000.0,
+            positions: positions.iter().map(|&(s, q)| (s.to_string(), q)).collect(),
+            market_price: 100.0,
+            quotes: quotes.iter().map(|&(s, p)| (s.to_string(), p)).collect(),
+        }
+    }
+
+    #[test]
+    fn realized_loss_uses_average_cost_and_counts_only_the_day() {
+        let fills = vec![
+            fill("buy", 10, 100.0, "2024-03-01 15:00:00"),
+            fill("buy", 10, 110.0, "2024-03-02 15:00:00"),
+            fill("sell", 5, 95.0, "2024-03-02 16:00:00"),
+            fill("sell", 5, 90.0, "2024-03-03 16:00:00"),
+        ];
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+
+        assert_eq!(realized_on(&fills, day(2)), -50.0);
+        assert_eq!(realized_on(&fills, day(3)), -75.0);
+        assert_eq!(realized_on(&fills, day(1)), 0.0);
+    }
+
+    #[test]
+    fn position_cap_blocks_oversized_buys() {
+        let limits = RiskLimits {
+            max_position_pct: Some(25.0),
+            ..Default::default()
+        };
+        let account = account(&[("MSFT", 50)], &[("MSFT", 200.0)]);
+
+        // Equity is 20,000, so 25% allows 50 shares at 100
+        assert!(check(&limits, &buy(50), &account, 0.0).is_empty());
+        let problems = check(&limits, &buy(51), &account, 0.0);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("max_position_pct"));
+    }
+
+    #[test]
+    fn daily_loss_locks_out_entries_but_not_exits() {
+        let limits = RiskLimits {
+            max_daily_loss: Some(500.0),
+            ..Default::default()
+        };
+        let account = account(&[("AAPL", 10)], &[]);
+        assert!(check(&limits, &buy(1), &account, 499.0).is_empty());
+
+        let problems = check(&limits, &buy(1), &account, 500.0);
+        assert!(problems[0].starts_with("max_daily_loss"));
+
+        let mut sell = buy(10);
+        sell.side = "sell".into();
+        assert!(check(&limits, &sell, &account, 900.0).is_empty());
+    }
+
+    #[test]
+    fn missing_quotes_and_leverage_are_reported() {
+        let limits = RiskLimits {
+            max_leverage: Some(0.5),
+            ..Default::default()
+        };
+        let unpriced = check(&limits, &buy(1), &account(&[("MSFT", 5)], &[]), 0.0);
+        assert!(unpriced[0].contains("No quote for MSFT"));
+
+        let problems = check(&limits, &buy(60), &account(&[], &[]), 0.0);
+        assert!(problems[0].starts_with("max_leverage"));
+    }
+
+    #[test]
+    fn limits_must_be_sane() {
+        assert!(RiskLimits::default().validate().is_ok());
+        let bad = RiskLimits {
+            max_position_pct: Some(150.0),
+            ..Default::default()
+        };
+        assert!(bad.validate().is_err());
+        let bad = RiskLimits {
+            max_daily_loss: Some(-1.0),
+            ..Default::default()
+        };
+        assert!(bad.validate().is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Utc, Weekday};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::monitors::MonitorState;
+use crate::{db, snapshots, timezone};
+
+/// Jobs that can be scheduled. The backend runs `portfolio_snapshot`
+/// itself; everything else is run by the frontend when `scheduled-job` fires.
+const JOB_KINDS: &[&str] = &["run_analysis", "portfolio_snapshot"];
+
+/// Market days. Weekends are never scheduled; exchange holidays aren't
+/// tracked, so jobs still fire on those.
+const DAYS: &[(&str, Weekday)] = &[
+    ("mon", Weekday::Mon),
+    ("tue", Weekday::Tue),
+    ("wed", Weekday::Wed),
+    ("thu", Weekday::Thu),
+    ("fri", Weekday::Fri),
+];
+
+/// Checked more than once a minute so a slow tick can't skip a job's minute.
+const TICK: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ScheduledJob {
+    id: i64,
+    kind: String,
+    time_of_day: String,
+    days: String,
+    enabled: bool,
+    last_fired: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+struct ScheduledJobPayload {
+    id: i64,
+    kind: String,
+}
+
+/// Identifies one scheduled minute, so a job fires once per occurrence.
+fn occurrence(now: NaiveDateTime) -> String {
+    now.format("%Y-%m-%dT%H:%M").to_string()
+}
+
+/// Whether `job` should fire at local time `now`.
+pub(crate) fn is_due(job: &ScheduledJob, now: NaiveDateTime) -> bool {
+    let on_day = job
+        .days
+        .split(',')
+        .filter_map(|day| DAYS.iter().find(|(name, _)| *name == day))
+        .any(|(_, weekday)| *weekday == now.weekday());
+
+    job.enabled
+        && on_day
+        && now.format("%H:%M").to_string() == job.time_of_day
+        && job.last_fired.as_deref() != Some(occurrence(now).as_str())
+}
+
+// ---------------------------------------------------------------------------
+// Queries
+// ---------------------------------------------------------------------------
+
+pub(crate) async fn insert_job(
+    pool: &SqlitePool,
+    kind: &str,
+    time_of_day: &str,
+    days: &[String],
+) -> Result<i64, String> {
+    if !JOB_KINDS.contains(&kind) {
+        return Err(format!(
+            "Unknown job kind '{}', expected one of: {}",
+            kind,
+            JOB_KINDS.join(", ")
+        ));
+    }
+    let time = NaiveTime::parse_from_str(time_of_day, "%H:%M")
+        .map_err(|_| format!("Invalid time '{}', expected HH:MM", time_of_day))?;
+
+    let mut names: Vec<&str> = Vec::new();
+    for day in days {
+        let day = day.trim().to_lowercase();
+        let Some((name, _)) = DAYS.iter().find(|(name, _)| *name == day) else {
+            return Err(format!(
+                "Unknown market day '{}', expected one of: mon, tue, wed, thu, fri",
+                day
+            ));
+        };
+        if !names.contains(name) {
+            names.push(name);
+        }
+    }
+    if names.is_empty() {
+        return Err("A job needs at least one day".into());
+    }
+
+    sqlx::query_scalar(
+        "INSERT INTO scheduled_jobs (kind, time_of_day, days) VALUES (?, ?, ?) RETURNING id",
+    )
+    .bind(kind)
+    .bind(time.format("%H:%M").to_string())
+    .bind(names.join(","))
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+pub(crate) async fn load_jobs(pool: &SqlitePool) -> Result<Vec<ScheduledJob>, String> {
+    sqlx::query_as(
+        "SELECT id, kind, time_of_day, days, enabled, last_fired FROM scheduled_jobs ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Mark `job_id` as fired for `occurrence`. Returns `false` if it already
+/// was, so concurrent ticks can't fire it twice.
+async fn claim(pool: &SqlitePool, job_id: i64, occurrence: &str) -> Result<bool, String> {
+    let result = sqlx::query(
+        "UPDATE scheduled_jobs SET last_fired = ? \
+         WHERE id = ? AND (last_fired IS NULL OR last_fired != ?)",
+    )
+    .bind(occurrence)
+    .bind(job_id)
+    .bind(occurrence)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(result.rows_affected() == 1)
+}
+
+// ---------------------------------------------------------------------------
+// Scheduler
+// ---------------------------------------------------------------------------
+
+async fn tick(app: &AppHandle) -> Result<(), String> {
+    if app.state::<MonitorState>().paused() {
+        return Ok(());
+    }
+
+    let pool = db::pool(app).await?;
+    let now = Utc::now()
+        .with_timezone(&timezone::user_timezone(app))
+        .naive_local();
+
+    for job in load_jobs(&pool).await? {
+        if is_due(&job, now) && claim(&pool, job.id, &occurrence(now)).await? {
+            if job.kind == "portfolio_snapshot" {
+                snapshots::snapshot_at_cost(&pool).await?;
+            }
+            let _ = app.emit(
+                "scheduled-job",
+                ScheduledJobPayload {
+                    id: job.id,
+                    kind: job.kind,
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Emit `scheduled-job` whenever a job's time comes up in the user's
+/// timezone. Ticks are skipped until the frontend has loaded the database.
+pub fn spawn_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let _ = tick(&app).await;
+            tokio::time::sleep(TICK).await;
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Schedule `kind` at `time_of_day` (HH:MM, user's timezone) on the given
+/// market days. Returns the new job's id.
+#[tauri::command]
+pub async fn create_scheduled_job(
+    app: AppHandle,
+    kind: String,
+    time_of_day: String,
+    days: Vec<String>,
+) -> Result<i64, String> {
+    logged!("create_scheduled_job", [kind, time_of_day, days], async {
+        let pool = db::pool(&app).await?;
+        insert_job(&pool, &kind, &time_of_day, &days).await
+    })
+}
+
+#[tauri::command]
+pub async fn list_scheduled_jobs(app: AppHandle) -> Result<Vec<ScheduledJob>, String> {
+    logged!("list_scheduled_jobs", [], async {
+        let pool = db::pool(&app).await?;
+        load_jobs(&pool).await
+    })
+}
+
+#[tauri::command]
+pub async fn delete_scheduled_job(app: AppHandle, id: i64) -> Result<(), String> {
+    logged!("delete_scheduled_job", [id], async {
+        let pool = db::pool(&app).await?;
+        sqlx::query("DELETE FROM scheduled_jobs WHERE id = ?")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing;
+
+    fn at(datetime: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn job(time_of_day: &str, days: &str) -> ScheduledJob {
+        ScheduledJob {
+            id: 1,
+            kind: "run_analysis".into(),
+            time_of_day: time_of_day.into(),
+            days: days.into(),
+            enabled: true,
+            last_fired: None,
+        }
+    }
+
+    #[test]
+    fn due_only_at_its_minute_on_its_days() {
+        let job = job("09:45", "mon,wed");
+
+        // 2024-03-04 is a Monday
+        assert!(is_due(&job, at("2024-03-04 09:45")));
+        assert!(!is_due(&job, at("2024-03-04 09:46")));
+        assert!(!is_due(&job, at("2024-03-05 09:45")));
+        assert!(is_due(&job, at("2024-03-06 09:45")));
+    }
+
+    #[test]
+    fn fired_or_disabled_jobs_are_not_due() {
+        let mut fired = job("09:45", "mon");
+        fired.last_fired = Some("2024-03-04T09:45".into());
+        assert!(!is_due(&fired, at("2024-03-04 09:45")));
+        assert!(is_due(&fired, at("2024-03-11 09:45")));
+
+        let mut disabled = job("09:45", "mon");
+        disabled.enabled = false;
+        assert!(!is_due(&disabled, at("2024-03-04 09:45")));
+    }
+
+    #[tokio::test]
+    async fn insert_validates_and_normalizes() {
+        let pool = testing::pool().await;
+        let days = vec!["Mon".to_string(), "fri".to_string(), "mon".to_string()];
+
+        let id = insert_job(&pool, "run_analysis", "9:45", &days)
+            .await
+            .unwrap();
+        let jobs = load_jobs(&pool).await.unwrap();
+        assert_eq!(jobs[0].id, id);
+        assert_eq!(jobs[0].time_of_day, "09:45");
+        assert_eq!(jobs[0].days, "mon,fri");
+
+        let weekend = vec!["sat".to_string()];
+        assert!(insert_job(&pool, "run_analysis", "09:45", &weekend)
+            .await
+            .is_err());
+        assert!(insert_job(&pool, "rebalance", "09:45", &days)
+            .await
+            .is_err());
+        assert!(insert_job(&pool, "run_analysis", "25:00", &days)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn claim_fires_once_per_occurrence() {
+        let pool = testing::pool().await;
+        let id = insert_job(&pool, "run_analysis", "09:45", &["mon".to_string()])
+            .await
+            .unwrap();
+
+        assert!(claim(&pool, id, "2024-03-04T09:45").await.unwrap());
+        assert!(!claim(&pool, id, "2024-03-04T09:45").await.unwrap());
+        assert!(claim(&pool, id, "2024-03-11T09:45").await.unwrap());
+    }
+}
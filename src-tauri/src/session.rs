@@ -0,0 +1,62 @@
+//! Counters for the current run, for diagnostics. They live in the process,
+//! so a relaunch starts them from zero.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use serde::Serialize;
+use tauri::State;
+
+/// Statics rather than managed state so `logged!` can count without an
+/// app handle.
+static COMMANDS_INVOKED: AtomicU64 = AtomicU64::new(0);
+static QUOTES_FETCHED: AtomicU64 = AtomicU64::new(0);
+static ALERTS_FIRED: AtomicU64 = AtomicU64::new(0);
+
+/// When the app started.
+pub struct SessionStart(Instant);
+
+impl Default for SessionStart {
+    fn default() -> Self {
+        Self(Instant::now())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionStats {
+    uptime_secs: u64,
+    quotes_fetched: u64,
+    alerts_fired: u64,
+    /// Commands that go through `logged!`, which is every fallible one.
+    commands_invoked: u64,
+}
+
+pub(crate) fn command_invoked() {
+    COMMANDS_INVOKED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Count alerts emitted to the frontend: saved alerts, gaps, volume spikes
+/// and allocation drift.
+pub(crate) fn alerts_fired(count: usize) {
+    ALERTS_FIRED.fetch_add(count as u64, Ordering::Relaxed);
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Quotes are fetched by the frontend, which reports each batch here.
+#[tauri::command]
+pub fn count_quotes_fetched(count: u32) {
+    QUOTES_FETCHED.fetch_add(count.into(), Ordering::Relaxed);
+}
+
+#[tauri::command]
+pub fn session_stats(start: State<'_, SessionStart>) -> SessionStats {
+    SessionStats {
+        uptime_secs: start.0.elapsed().as_secs(),
+        quotes_fetched: QUOTES_FETCHED.load(Ordering::Relaxed),
+        alerts_fired: ALERTS_FIRED.load(Ordering::Relaxed),
+        commands_invoked: COMMANDS_INVOKED.load(Ordering::Relaxed),
+    }
+}
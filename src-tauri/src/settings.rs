@@ -0,0 +1,901 @@
+use chrono::NaiveTime;
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_store::StoreExt;
+
+use crate::{charts, delivery, format, notifications, risk, tray, STORE_FILE};
+
+pub(crate) const ONBOARDED_KEY: &str = "onboarded";
+pub(crate) const SETTINGS_KEY: &str = "settings";
+pub(crate) const LAST_VIEW_KEY: &str = "last_view";
+
+/// Top-level routes the app can open on, named after their paths in App.tsx.
+const VIEWS: &[&str] = &[
+    "dashboard",
+    "analysis",
+    "portfolio",
+    "watchlist",
+    "history",
+    "performance",
+    "paper-trading",
+    "settings",
+];
+
+/// Startup-view setting that resolves to the last route the user had open.
+const LAST_VIEW: &str = "last";
+const DEFAULT_VIEW: &str = "dashboard";
+
+const THEMES: &[&str] = &["system", "light", "dark"];
+const POLL_INTERVAL_SECS: std::ops::RangeInclusive<u32> = 15..=3600;
+const NOTIFICATIONS_PER_MINUTE: std::ops::RangeInclusive<u32> = 1..=60;
+/// Up to 5% either way, which is already a very illiquid market.
+const MAX_SLIPPAGE_BPS: u32 = 500;
+const VOLUME_SPIKE_MULTIPLIER: std::ops::RangeInclusive<f64> = 1.1..=20.0;
+const GAP_THRESHOLD_PCT: std::ops::RangeInclusive<f64> = 0.1..=50.0;
+const MAX_POSITION_WEIGHT_PCT: std::ops::RangeInclusive<f64> = 1.0..=100.0;
+const ALLOCATION_DRIFT_BAND_PCT: std::ops::RangeInclusive<f64> = 0.5..=50.0;
+/// Bounds on the main window's minimum size, in logical pixels.
+pub(crate) const MIN_WINDOW_WIDTH: std::ops::RangeInclusive<f64> = 640.0..=2560.0;
+pub(crate) const MIN_WINDOW_HEIGHT: std::ops::RangeInclusive<f64> = 480.0..=1440.0;
+
+/// What a left click on the tray icon does: toggle the main window, open
+/// the popover, or open the menu like a right click.
+const TRAY_LEFT_CLICK: &[&str] = &["window", "popover", "menu"];
+
+/// Menu-bar apps on macOS conventionally open their menu on left click.
+#[cfg(target_os = "macos")]
+const DEFAULT_TRAY_LEFT_CLICK: &str = "menu";
+#[cfg(not(target_os = "macos"))]
+const DEFAULT_TRAY_LEFT_CLICK: &str = "window";
+
+#[derive(Clone, Serialize)]
+struct AppReadyPayload {
+    startup_view: String,
+}
+
+pub(crate) fn validate_view(view: &str) -> Result<(), String> {
+    if VIEWS.contains(&view) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unknown view '{}', expected one of: {}",
+            view,
+            VIEWS.join(", ")
+        ))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Typed settings
+// ---------------------------------------------------------------------------
+
+/// Daily window, in the user's timezone, during which alerts stay quiet.
+/// `end` may be earlier than `start` for windows that span midnight.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+/// User preferences, stored as a single object under `settings` so every
+/// update is written in one go.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub base_currency: String,
+    pub theme: String,
+    pub poll_interval_secs: u32,
+    pub quiet_hours: Option<QuietHours>,
+    pub startup_view: String,
+    pub timezone_override: Option<String>,
+    pub tray_left_click: String,
+    pub notify_on_analysis_complete: bool,
+    pub fractional_shares_allowed: bool,
+    pub notifications_per_minute: u32,
+    pub locale: String,
+    /// Simulated market-order slippage for paper trading, in basis points.
+    pub paper_slippage_bps: u32,
+    pub risk_limits: risk::RiskLimits,
+    /// Projected volume, as a multiple of average, that counts as a spike.
+    pub volume_spike_multiplier: f64,
+    /// Overnight move, in percent either way, that counts as a gap.
+    pub gap_threshold_pct: f64,
+    pub refresh_on_resume: bool,
+    /// Share of the portfolio, in percent, past which a position is flagged.
+    pub max_position_weight_pct: f64,
+    /// Percentage points a holding may drift from its allocation target.
+    pub allocation_drift_band_pct: f64,
+    pub notify_on_allocation_drift: bool,
+    /// Fixed decimal places for share prices; `None` picks them by price.
+    pub price_decimals: Option<u32>,
+    /// Smallest size the main window can be resized to, in logical pixels.
+    pub min_window_width: f64,
+    pub min_window_height: f64,
+    /// Chart range preset id charts open at.
+    pub default_chart_range: String,
+    /// https endpoint fired alerts are also POSTed to.
+    pub alert_webhook_url: Option<String>,
+    /// Payload format for the webhook, one of `delivery::WEBHOOK_FORMATS`.
+    pub alert_webhook_format: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            base_currency: "USD".into(),
+            theme: "system".into(),
+            poll_interval_secs: 60,
+            quiet_hours: None,
+            startup_view: DEFAULT_VIEW.into(),
+            timezone_override: None,
+            tray_left_click: DEFAULT_TRAY_LEFT_CLICK.into(),
+            notify_on_analysis_complete: true,
+            fractional_shares_allowed: true,
+            notifications_per_minute: 10,
+            locale: "en-US".into(),
+            paper_slippage_bps: 5,
+            risk_limits: risk::RiskLimits::default(),
+            volume_spike_multiplier: 2.0,
+            gap_threshold_pct: 2.0,
+            refresh_on_resume: true,
+            max_position_weight_pct: 20.0,
+            allocation_drift_band_pct: 5.0,
+            notify_on_allocation_drift: true,
+            price_decimals: None,
+            // Matches minWidth and minHeight in tauri.conf.json
+            min_window_width: 1024.0,
+            min_window_height: 700.0,
+            default_chart_range: "1M".into(),
+            alert_webhook_url: None,
+            alert_webhook_format: "auto".into(),
+        }
+    }
+}
+
+/// Treat a present field as `Some(value)` even when the value is `null`, so
+/// patches can tell "clear this" apart from "leave it alone".
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Partial update for [`Settings`]; omitted fields are left unchanged.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SettingsPatch {
+    pub base_currency: Option<String>,
+    pub theme: Option<String>,
+    pub poll_interval_secs: Option<u32>,
+    #[serde(deserialize_with = "present")]
+    pub quiet_hours: Option<Option<QuietHours>>,
+    pub startup_view: Option<String>,
+    #[serde(deserialize_with = "present")]
+    pub timezone_override: Option<Option<String>>,
+    pub tray_left_click: Option<String>,
+    pub notify_on_analysis_complete: Option<bool>,
+    pub fractional_shares_allowed: Option<bool>,
+    pub notifications_per_minute: Option<u32>,
+    pub locale: Option<String>,
+    pub paper_slippage_bps: Option<u32>,
+    /// Replaces every limit at once; send the full set.
+    pub risk_limits: Option<risk::RiskLimits>,
+    pub volume_spike_multiplier: Option<f64>,
+    pub gap_threshold_pct: Option<f64>,
+    pub refresh_on_resume: Option<bool>,
+    pub max_position_weight_pct: Option<f64>,
+    pub allocation_drift_band_pct: Option<f64>,
+    pub notify_on_allocation_drift: Option<bool>,
+    #[serde(deserialize_with = "present")]
+    pub price_decimals: Option<Option<u32>>,
+    pub min_window_width: Option<f64>,
+    pub min_window_height: Option<f64>,
+    pub default_chart_range: Option<String>,
+    #[serde(deserialize_with = "present")]
+    pub alert_webhook_url: Option<Option<String>>,
+    pub alert_webhook_format: Option<String>,
+}
+
+impl Settings {
+    fn apply(&mut self, patch: SettingsPatch) {
+        if let Some(currency) = patch.base_currency {
+            self.base_currency = currency.trim().to_uppercase();
+        }
+        if let Some(theme) = patch.theme {
+            self.theme = theme;
+        }
+        if let Some(secs) = patch.poll_interval_secs {
+            self.poll_interval_secs = secs;
+        }
+        if let Some(quiet_hours) = patch.quiet_hours {
+            self.quiet_hours = quiet_hours;
+        }
+        if let Some(view) = patch.startup_view {
+            self.startup_view = view;
+        }
+        if let Some(tz) = patch.timezone_override {
+            self.timezone_override = tz
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty());
+        }
+        if let Some(action) = patch.tray_left_click {
+            self.tray_left_click = action;
+        }
+        if let Some(notify) = patch.notify_on_analysis_complete {
+            self.notify_on_analysis_complete = notify;
+        }
+        if let Some(allowed) = patch.fractional_shares_allowed {
+            self.fractional_shares_allowed = allowed;
+        }
+        if let Some(rate) = patch.notifications_per_minute {
+            self.notifications_per_minute = rate;
+        }
+        if let Some(locale) = patch.locale {
+            self.locale = locale;
+        }
+        if let Some(bps) = patch.paper_slippage_bps {
+            self.paper_slippage_bps = bps;
+        }
+        if let Some(limits) = patch.risk_limits {
+            self.risk_limits = limits;
+        }
+        if let Some(multiplier) = patch.volume_spike_multiplier {
+            self.volume_spike_multiplier = multiplier;
+        }
+        if let Some(pct) = patch.gap_threshold_pct {
+            self.gap_threshold_pct = pct;
+        }
+        if let Some(enabled) = patch.refresh_on_resume {
+            self.refresh_on_resume = enabled;
+        }
+        if let Some(pct) = patch.max_position_weight_pct {
+            self.max_position_weight_pct = pct;
+        }
+        if let Some(band) = patch.allocation_drift_band_pct {
+            self.allocation_drift_band_pct = band;
+        }
+        if let Some(notify) = patch.notify_on_allocation_drift {
+            self.notify_on_allocation_drift = notify;
+        }
+        if let Some(places) = patch.price_decimals {
+            self.price_decimals = places;
+        }
+        if let Some(width) = patch.min_window_width {
+            self.min_window_width = width;
+        }
+        if let Some(height) = patch.min_window_height {
+            self.min_window_height = height;
+        }
+        if let Some(range) = patch.default_chart_range {
+            self.default_chart_range = range.trim().to_uppercase();
+        }
+        if let Some(url) = patch.alert_webhook_url {
+            self.alert_webhook_url = url
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty());
+        }
+        if let Some(format) = patch.alert_webhook_format {
+            self.alert_webhook_format = format.trim().to_lowercase();
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let currency = &self.base_currency;
+        if currency.len() != 3 || !currency.bytes().all(|b| b.is_ascii_uppercase()) {
+            return Err(format!(
+                "Invalid currency '{}', expected a 3-letter code",
+                currency
+            ));
+        }
+
+        if !THEMES.contains(&self.theme.as_str()) {
+            return Err(format!(
+                "Unknown theme '{}', expected one of: {}",
+                self.theme,
+                THEMES.join(", ")
+            ));
+        }
+
+        if !POLL_INTERVAL_SECS.contains(&self.poll_interval_secs) {
+            return Err(format!(
+                "Poll interval must be between {} and {} seconds",
+                POLL_INTERVAL_SECS.start(),
+                POLL_INTERVAL_SECS.end()
+            ));
+        }
+
+        if !NOTIFICATIONS_PER_MINUTE.contains(&self.notifications_per_minute) {
+            return Err(format!(
+                "Notification rate must be between {} and {} per minute",
+                NOTIFICATIONS_PER_MINUTE.start(),
+                NOTIFICATIONS_PER_MINUTE.end()
+            ));
+        }
+
+        if !format::LOCALES.contains(&self.locale.as_str()) {
+            return Err(format!(
+                "Unsupported locale '{}', expected one of: {}",
+                self.locale,
+                format::LOCALES.join(", ")
+            ));
+        }
+
+        if self.paper_slippage_bps > MAX_SLIPPAGE_BPS {
+            return Err(format!(
+                "Slippage must be at most {} basis points",
+                MAX_SLIPPAGE_BPS
+            ));
+        }
+
+        self.risk_limits.validate()?;
+
+        if !VOLUME_SPIKE_MULTIPLIER.contains(&self.volume_spike_multiplier) {
+            return Err(format!(
+                "Volume spike multiplier must be between {} and {}",
+                VOLUME_SPIKE_MULTIPLIER.start(),
+                VOLUME_SPIKE_MULTIPLIER.end()
+            ));
+        }
+
+        if !GAP_THRESHOLD_PCT.contains(&self.gap_threshold_pct) {
+            return Err(format!(
+                "Gap threshold must be between {}% and {}%",
+                GAP_THRESHOLD_PCT.start(),
+                GAP_THRESHOLD_PCT.end()
+            ));
+        }
+
+        if !MAX_POSITION_WEIGHT_PCT.contains(&self.max_position_weight_pct) {
+            return Err(format!(
+                "Max position weight must be between {}% and {}%",
+                MAX_POSITION_WEIGHT_PCT.start(),
+                MAX_POSITION_WEIGHT_PCT.end()
+            ));
+        }
+
+        if !ALLOCATION_DRIFT_BAND_PCT.contains(&self.allocation_drift_band_pct) {
+            return Err(format!(
+                "Allocation drift band must be between {} and {} points",
+                ALLOCATION_DRIFT_BAND_PCT.start(),
+                ALLOCATION_DRIFT_BAND_PCT.end()
+            ));
+        }
+
+        if !MIN_WINDOW_WIDTH.contains(&self.min_window_width)
+            || !MIN_WINDOW_HEIGHT.contains(&self.min_window_height)
+        {
+            return Err(format!(
+                "Minimum window size must be between {}x{} and {}x{}",
+                MIN_WINDOW_WIDTH.start(),
+                MIN_WINDOW_HEIGHT.start(),
+                MIN_WINDOW_WIDTH.end(),
+                MIN_WINDOW_HEIGHT.end()
+            ));
+        }
+
+        let ranges = charts::range_ids();
+        if !ranges.contains(&self.default_chart_range.as_str()) {
+            return Err(format!(
+                "Unknown chart range '{}', expected one of: {}",
+                self.default_chart_range,
+                ranges.join(", ")
+            ));
+        }
+
+        if self
+            .price_decimals
+            .is_some_and(|places| places > format::MAX_PRICE_DECIMALS)
+        {
+            return Err(format!(
+                "Price decimals must be at most {}",
+                format::MAX_PRICE_DECIMALS
+            ));
+        }
+
+        if let Some(quiet) = &self.quiet_hours {
+            for time in [&quiet.start, &quiet.end] {
+                NaiveTime::parse_from_str(time, "%H:%M")
+                    .map_err(|_| format!("Invalid quiet-hours time '{}', expected HH:MM", time))?;
+            }
+            if quiet.start == quiet.end {
+                return Err("Quiet hours must start and end at different times".into());
+            }
+        }
+
+        if self.startup_view != LAST_VIEW {
+            validate_view(&self.startup_view)?;
+        }
+
+        if let Some(name) = &self.timezone_override {
+            name.parse::<Tz>()
+                .map_err(|_| format!("Unknown timezone '{}'", name))?;
+        }
+
+        if !TRAY_LEFT_CLICK.contains(&self.tray_left_click.as_str()) {
+            return Err(format!(
+                "Unknown tray click action '{}', expected one of: {}",
+                self.tray_left_click,
+                TRAY_LEFT_CLICK.join(", ")
+            ));
+        }
+
+        if let Some(url) = &self.alert_webhook_url {
+            delivery::validate_webhook_url(url)?;
+        }
+
+        if !delivery::WEBHOOK_FORMATS.contains(&self.alert_webhook_format.as_str()) {
+            return Err(format!(
+                "Unknown webhook format '{}', expected one of: {}",
+                self.alert_webhook_format,
+                delivery::WEBHOOK_FORMATS.join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Rebuild settings from a stored object, keeping every field that is
+    /// still valid on its own and defaulting the rest, so one bad field
+    /// from an older build doesn't reset everything.
+    fn from_stored(value: &Value) -> Self {
+        let mut settings = Self::default();
+        let Some(fields) = value.as_object() else {
+            return settings;
+        };
+
+        for (name, field) in fields {
+            let single = serde_json::json!({ name: field });
+            let Ok(patch) = serde_json::from_value::<SettingsPatch>(single) else {
+                continue;
+            };
+            let mut candidate = settings.clone();
+            candidate.apply(patch);
+            if candidate.validate().is_ok() {
+                settings = candidate;
+            }
+        }
+        settings
+    }
+}
+
+/// Current settings, with defaults for anything unset.
+pub(crate) fn load_settings<R: Runtime>(app: &AppHandle<R>) -> Settings {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|store| store.get(SETTINGS_KEY))
+        .map(|value| Settings::from_stored(&value))
+        .unwrap_or_default()
+}
+
+/// Apply `patch`, validate the result and persist it, emitting
+/// `settings-changed` with the new settings.
+pub(crate) fn update_settings(app: &AppHandle, patch: SettingsPatch) -> Result<Settings, String> {
+    let mut settings = load_settings(app);
+    settings.apply(patch);
+    settings.validate()?;
+
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    let value = serde_json::to_value(&settings).map_err(|e| e.to_string())?;
+    store.set(SETTINGS_KEY, value);
+    store.save().map_err(|e| e.to_string())?;
+
+    tray::sync_left_click(app, &settings);
+    if let Some(limiter) = app.try_state::<notifications::NotificationLimiter>() {
+        limiter.set_rate(settings.notifications_per_minute);
+    }
+    let _ = app.emit("settings-changed", settings.clone());
+    Ok(settings)
+}
+
+/// The view to open on launch, with `last` resolved to a concrete route.
+fn resolve_startup_view<R: Runtime>(app: &AppHandle<R>) -> String {
+    let setting = load_settings(app).startup_view;
+    if setting != LAST_VIEW {
+        return setting;
+    }
+
+    let last = app
+        .store(STORE_FILE)
+        .ok()
+        .and_then(|store| store.get(LAST_VIEW_KEY))
+        .and_then(|v| v.as_str().map(str::to_string));
+    match last {
+        // Routes can be renamed between builds
+        Some(view) if validate_view(&view).is_ok() => view,
+        _ => DEFAULT_VIEW.into(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Schema
+// ---------------------------------------------------------------------------
+
+enum Constraint {
+    None,
+    OneOf(Vec<String>),
+    Range(f64, f64),
+    /// Free-form text in a described format, e.g. `HH:MM`.
+    Format(&'static str),
+}
+
+/// One setting as the settings screen needs to render and check it.
+#[derive(Debug, Serialize)]
+pub struct SettingDescriptor {
+    key: &'static str,
+    /// `string`, `integer`, `number`, `boolean` or `object`.
+    #[serde(rename = "type")]
+    kind: &'static str,
+    group: &'static str,
+    nullable: bool,
+    default: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    allowed: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<&'static str>,
+}
+
+/// Key, JSON type and constraint.
+type Field = (&'static str, &'static str, Constraint);
+
+fn one_of(values: &[&str]) -> Constraint {
+    Constraint::OneOf(values.iter().map(|v| v.to_string()).collect())
+}
+
+fn range<T: Into<f64> + Copy>(range: &std::ops::RangeInclusive<T>) -> Constraint {
+    Constraint::Range((*range.start()).into(), (*range.end()).into())
+}
+
+/// Every setting with its type, group, default and the limits `validate`
+/// enforces, which it shares constants with.
+pub(crate) fn schema() -> Vec<SettingDescriptor> {
+    let mut views = vec![LAST_VIEW];
+    views.extend(VIEWS);
+    let groups: Vec<(&str, Vec<Field>)> = vec![
+        (
+            "general",
+            vec![
+                (
+                    "base_currency",
+                    "string",
+                    Constraint::Format("ISO 4217 code"),
+                ),
+                ("theme", "string", one_of(THEMES)),
+                ("locale", "string", one_of(format::LOCALES)),
+                (
+                    "price_decimals",
+                    "integer",
+                    range(&(0..=format::MAX_PRICE_DECIMALS)),
+                ),
+                ("startup_view", "string", one_of(&views)),
+                (
+                    "timezone_override",
+                    "string",
+                    Constraint::Format("IANA timezone"),
+                ),
+                ("tray_left_click", "string", one_of(TRAY_LEFT_CLICK)),
+                ("refresh_on_resume", "boolean", Constraint::None),
+                (
+                    "default_chart_range",
+                    "string",
+                    one_of(&charts::range_ids()),
+                ),
+                ("min_window_width", "number", range(&MIN_WINDOW_WIDTH)),
+                ("min_window_height", "number", range(&MIN_WINDOW_HEIGHT)),
+            ],
+        ),
+        (
+            "monitoring",
+            vec![
+                ("poll_interval_secs", "integer", range(&POLL_INTERVAL_SECS)),
+                (
+                    "volume_spike_multiplier",
+                    "number",
+                    range(&VOLUME_SPIKE_MULTIPLIER),
+                ),
+                ("gap_threshold_pct", "number", range(&GAP_THRESHOLD_PCT)),
+            ],
+        ),
+        (
+            "notifications",
+            vec![
+                (
+                    "quiet_hours",
+                    "object",
+                    Constraint::Format("{start, end} as HH:MM"),
+                ),
+                ("notify_on_analysis_complete", "boolean", Constraint::None),
+                ("notify_on_allocation_drift", "boolean", Constraint::None),
+                (
+                    "notifications_per_minute",
+                    "integer",
+                    range(&NOTIFICATIONS_PER_MINUTE),
+                ),
+                (
+                    "alert_webhook_url",
+                    "string",
+                    Constraint::Format("https URL"),
+                ),
+                (
+                    "alert_webhook_format",
+                    "string",
+                    one_of(delivery::WEBHOOK_FORMATS),
+                ),
+            ],
+        ),
+        (
+            "trading",
+            vec![
+                ("fractional_shares_allowed", "boolean", Constraint::None),
+                (
+                    "paper_slippage_bps",
+                    "integer",
+                    range(&(0..=MAX_SLIPPAGE_BPS)),
+                ),
+                (
+                    "max_position_weight_pct",
+                    "number",
+                    range(&MAX_POSITION_WEIGHT_PCT),
+                ),
+                (
+                    "allocation_drift_band_pct",
+                    "number",
+                    range(&ALLOCATION_DRIFT_BAND_PCT),
+                ),
+                (
+                    "risk_limits",
+                    "object",
+                    Constraint::Format("optional limits"),
+                ),
+            ],
+        ),
+    ];
+
+    // Optional settings are the ones that default to null
+    let defaults = serde_json::to_value(Settings::default()).unwrap_or_default();
+    groups
+        .into_iter()
+        .flat_map(|(group, fields)| fields.into_iter().map(move |f| (group, f)))
+        .map(|(group, (key, kind, constraint))| {
+            let default = defaults.get(key).cloned().unwrap_or(Value::Null);
+            let mut descriptor = SettingDescriptor {
+                key,
+                kind,
+                group,
+                nullable: default.is_null(),
+                default,
+                allowed: None,
+                min: None,
+                max: None,
+                format: None,
+            };
+            match constraint {
+                Constraint::None => {}
+                Constraint::OneOf(values) => descriptor.allowed = Some(values),
+                Constraint::Range(min, max) => {
+                    descriptor.min = Some(min);
+                    descriptor.max = Some(max);
+                }
+                Constraint::Format(format) => descriptor.format = Some(format),
+            }
+            descriptor
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_settings(app: AppHandle) -> Settings {
+    load_settings(&app)
+}
+
+/// Type, default and limits of every setting, so the settings screen can be
+/// generated instead of kept in step by hand.
+#[tauri::command]
+pub fn settings_schema() -> Vec<SettingDescriptor> {
+    schema()
+}
+
+/// Update several settings at once. Nothing is saved unless the result is
+/// valid as a whole.
+#[tauri::command]
+pub fn set_setting(app: AppHandle, patch: SettingsPatch) -> Result<Settings, String> {
+    logged!("set_setting", [], { update_settings(&app, patch) })
+}
+
+#[tauri::command]
+pub fn complete_onboarding(app: AppHandle) -> Result<(), String> {
+    logged!("complete_onboarding", [], {
+        let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+        store.set(ONBOARDED_KEY, true);
+        store.save().map_err(|e| e.to_string())
+    })
+}
+
+#[tauri::command]
+pub fn set_startup_view(app: AppHandle, view: String) -> Result<(), String> {
+    logged!("set_startup_view", [view], {
+        let patch = SettingsPatch {
+            startup_view: Some(view),
+            ..Default::default()
+        };
+        update_settings(&app, patch).map(|_| ())
+    })
+}
+
+/// The raw setting, which may be `last`, for the preferences screen.
+#[tauri::command]
+pub fn get_startup_view(app: AppHandle) -> String {
+    load_settings(&app).startup_view
+}
+
+#[tauri::command]
+pub fn set_fractional_shares(app: AppHandle, allowed: bool) -> Result<(), String> {
+    logged!("set_fractional_shares", [allowed], {
+        let patch = SettingsPatch {
+            fractional_shares_allowed: Some(allowed),
+            ..Default::default()
+        };
+        update_settings(&app, patch).map(|_| ())
+    })
+}
+
+#[tauri::command]
+pub fn get_fractional_shares(app: AppHandle) -> bool {
+    load_settings(&app).fractional_shares_allowed
+}
+
+/// Record the route the user is on so a `last` startup view can return to it.
+#[tauri::command]
+pub fn save_last_view(app: AppHandle, view: String) -> Result<(), String> {
+    logged!("save_last_view", [view], {
+        validate_view(&view)?;
+
+        let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+        store.set(LAST_VIEW_KEY, view);
+        store.save().map_err(|e| e.to_string())
+    })
+}
+
+/// Wipe every backend-persisted preference, including the onboarding flag,
+/// so the next launch behaves like a fresh install.
+#[tauri::command]
+pub fn reset_settings(app: AppHandle) -> Result<(), String> {
+    logged!("reset_settings", [], {
+        let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+        store.clear();
+        store.save().map_err(|e| e.to_string())
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Startup
+// ---------------------------------------------------------------------------
+
+/// Emit `app-ready` with the view the frontend should route to.
+pub fn emit_app_ready<R: Runtime>(app: &AppHandle<R>) {
+    let payload = AppReadyPayload {
+        startup_view: resolve_startup_view(app),
+    };
+    let _ = app.emit("app-ready", payload);
+}
+
+/// Emit `onboarding` if the user has never finished onboarding.
+pub fn emit_onboarding<R: Runtime>(app: &AppHandle<R>) {
+    let Ok(store) = app.store(STORE_FILE) else {
+        return;
+    };
+
+    let onboarded = store
+        .get(ONBOARDED_KEY)
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if !onboarded {
+        let _ = app.emit("onboarding", ());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patch(json: &str) -> SettingsPatch {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn every_setting_has_a_descriptor() {
+        let defaults = serde_json::to_value(Settings::default()).unwrap();
+        let mut fields: Vec<&str> = defaults
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut described: Vec<&str> = schema().iter().map(|d| d.key).collect();
+        fields.sort();
+        described.sort();
+        assert_eq!(fields, described);
+    }
+
+    #[test]
+    fn descriptor_defaults_satisfy_their_constraints() {
+        for d in schema() {
+            if let (Some(allowed), Some(default)) = (&d.allowed, d.default.as_str()) {
+                assert!(allowed.iter().any(|a| a == default), "{}", d.key);
+            }
+            // Optional settings default to null
+            if let (Some(min), Some(max), false) = (d.min, d.max, d.nullable) {
+                let default = d.default.as_f64().unwrap();
+                assert!((min..=max).contains(&default), "{}", d.key);
+            }
+        }
+    }
+
+    #[test]
+    fn patch_distinguishes_null_from_missing() {
+        let mut settings = Settings {
+            timezone_override: Some("Europe/Paris".into()),
+            ..Default::default()
+        };
+
+        settings.apply(patch(r#"{ "theme": "dark" }"#));
+        assert_eq!(settings.timezone_override.as_deref(), Some("Europe/Paris"));
+
+        settings.apply(patch(r#"{ "timezone_override": null }"#));
+        assert!(settings.timezone_override.is_none());
+    }
+
+    #[test]
+    fn patch_rejects_unknown_fields() {
+        assert!(serde_json::from_str::<SettingsPatch>(r#"{ "colour": "red" }"#).is_err());
+    }
+
+    #[test]
+    fn validate_catches_bad_values() {
+        let cases = [
+            r#"{ "base_currency": "dollars" }"#,
+            r#"{ "theme": "neon" }"#,
+            r#"{ "poll_interval_secs": 1 }"#,
+            r#"{ "quiet_hours": { "start": "22:00", "end": "25:00" } }"#,
+            r#"{ "startup_view": "nowhere" }"#,
+            r#"{ "timezone_override": "Mars/Olympus_Mons" }"#,
+            r#"{ "tray_left_click": "double" }"#,
+            r#"{ "locale": "klingon" }"#,
+            r#"{ "default_chart_range": "2W" }"#,
+            r#"{ "alert_webhook_url": "http://example.com/hook" }"#,
+            r#"{ "alert_webhook_format": "slack" }"#,
+        ];
+        for case in cases {
+            let mut settings = Settings::default();
+            settings.apply(patch(case));
+            assert!(settings.validate().is_err(), "{} should be rejected", case);
+        }
+    }
+
+    #[test]
+    fn from_stored_keeps_valid_fields_only() {
+        let stored = serde_json::json!({
+            "base_currency": "eur",
+            "theme": "neon",
+            "poll_interval_secs": "fast",
+            "startup_view": "last"
+        });
+
+        let settings = Settings::from_stored(&stored);
+        assert_eq!(settings.base_currency, "EUR");
+        assert_eq!(settings.theme, "system");
+        assert_eq!(settings.poll_interval_secs, 60);
+        assert_eq!(settings.startup_view, LAST_VIEW);
+    }
+}
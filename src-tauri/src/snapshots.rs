@@ -0,0 +1,318 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+
+use crate::db::{self, TradeRow};
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Holding {
+    pub(crate) symbol: String,
+    /// Negative for shorts.
+    quantity: i64,
+    pub(crate) value: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Snapshot {
+    id: i64,
+    taken_at: String,
+    total_value: f64,
+    holdings: Vec<Holding>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PositionChange {
+    symbol: String,
+    /// `added`, `removed` or `changed`.
+    change: &'static str,
+    quantity_delta: i64,
+    value_delta: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SnapshotComparison {
+    from: String,
+    to: String,
+    total_value_delta: f64,
+    changes: Vec<PositionChange>,
+}
+
+/// Open positions per symbol. Symbols without a price in `prices` are
+/// valued at cost.
+pub(crate) fn holdings(trades: &[TradeRow], prices: &HashMap<String, f64>) -> Vec<Holding> {
+    let mut by_symbol: BTreeMap<&str, (i64, f64)> = BTreeMap::new();
+    for trade in trades.iter().filter(|t| t.is_open()) {
+        let signed = if trade.is_long() {
+            trade.quantity
+        } else {
+            -trade.quantity
+        };
+        let price = prices
+            .get(&trade.symbol)
+            .copied()
+            .unwrap_or(trade.entry_price);
+        let entry = by_symbol.entry(&trade.symbol).or_default();
+        entry.0 += signed;
+        entry.1 += signed as f64 * price;
+    }
+    by_symbol
+        .into_iter()
+        .filter(|(_, (quantity, _))| *quantity != 0)
+        .map(|(symbol, (quantity, value))| Holding {
+            symbol: symbol.to_string(),
+            quantity,
+            value,
+        })
+        .collect()
+}
+
+/// What changed from one set of holdings to another, by symbol. Positions
+/// that didn't move are left out.
+pub(crate) fn diff(from: &[Holding], to: &[Holding]) -> Vec<PositionChange> {
+    let mut pairs: BTreeMap<&str, (Option<&Holding>, Option<&Holding>)> = BTreeMap::new();
+    for holding in from {
+        pairs.entry(&holding.symbol).or_default().0 = Some(holding);
+    }
+    for holding in to {
+        pairs.entry(&holding.symbol).or_default().1 = Some(holding);
+    }
+
+    pairs
+        .into_iter()
+        .filter_map(|(symbol, (before, after))| {
+            let (change, quantity_delta, value_delta) = match (before, after) {
+                (None, Some(a)) => ("added", a.quantity, a.value),
+                (Some(b), None) => ("removed", -b.quantity, -b.value),
+                (Some(b), Some(a)) if b.quantity != a.quantity || b.value != a.value => {
+                    ("changed", a.quantity - b.quantity, a.value - b.value)
+                }
+                _ => return None,
+            };
+            Some(PositionChange {
+                symbol: symbol.to_string(),
+                change,
+                quantity_delta,
+                value_delta,
+            })
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Queries
+// ---------------------------------------------------------------------------
+
+pub(crate) async fn save_snapshot(
+    pool: &SqlitePool,
+    holdings: &[Holding],
+) -> Result<Snapshot, String> {
+    let total_value: f64 = holdings.iter().map(|h| h.value).sum();
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let (id, taken_at): (i64, String) = sqlx::query_as(
+        "INSERT INTO portfolio_snapshots (total_value) VALUES (?) RETURNING id, taken_at",
+    )
+    .bind(total_value)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    for holding in holdings {
+        sqlx::query(
+            "INSERT INTO portfolio_snapshot_holdings (snapshot_id, symbol, quantity, value) \
+             VALUES (?, ?, ?, ?)",
+        )
+        .bind(id)
+        .bind(&holding.symbol)
+        .bind(holding.quantity)
+        .bind(holding.value)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(Snapshot {
+        id,
+        taken_at,
+        total_value,
+        holdings: holdings.to_vec(),
+    })
+}
+
+/// The last snapshot taken on or before `date` (YYYY-MM-DD).
+async fn snapshot_as_of(pool: &SqlitePool, date: &str) -> Result<Snapshot, String> {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", date))?;
+
+    let (id, taken_at, total_value): (i64, String, f64) = sqlx::query_as(
+        "SELECT id, taken_at, total_value FROM portfolio_snapshots \
+         WHERE date(taken_at) <= ? ORDER BY taken_at DESC, id DESC LIMIT 1",
+    )
+    .bind(date)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("No snapshot on or before {}", date))?;
+
+    let holdings = sqlx::query_as(
+        "SELECT symbol, quantity, value FROM portfolio_snapshot_holdings \
+         WHERE snapshot_id = ? ORDER BY symbol",
+    )
+    .bind(id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(Snapshot {
+        id,
+        taken_at,
+        total_value,
+        holdings,
+    })
+}
+
+pub(crate) async fn compare(
+    pool: &SqlitePool,
+    from_date: &str,
+    to_date: &str,
+) -> Result<SnapshotComparison, String> {
+    let from = snapshot_as_of(pool, from_date).await?;
+    let to = snapshot_as_of(pool, to_date).await?;
+    Ok(SnapshotComparison {
+        total_value_delta: to.total_value - from.total_value,
+        changes: diff(&from.holdings, &to.holdings),
+        from: from.taken_at,
+        to: to.taken_at,
+    })
+}
+
+/// Snapshot current holdings at cost. Used by the scheduler, which has no
+/// quotes of its own.
+pub(crate) async fn snapshot_at_cost(pool: &SqlitePool) -> Result<Snapshot, String> {
+    let trades = db::load_trades(pool).await?;
+    save_snapshot(pool, &holdings(&trades, &HashMap::new())).await
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Record the current holdings and their total value. `prices` maps
+/// symbols to their latest quote; positions without one are valued at cost.
+#[tauri::command]
+pub async fn take_snapshot(
+    app: AppHandle,
+    prices: Option<HashMap<String, f64>>,
+) -> Result<Snapshot, String> {
+    logged!("take_snapshot", [], async {
+        let pool = db::pool(&app).await?;
+        let trades = db::load_trades(&pool).await?;
+        save_snapshot(&pool, &holdings(&trades, &prices.unwrap_or_default())).await
+    })
+}
+
+/// How holdings changed between the snapshots in effect on two dates.
+#[tauri::command]
+pub async fn compare_snapshots(
+    app: AppHandle,
+    from_date: String,
+    to_date: String,
+) -> Result<SnapshotComparison, String> {
+    logged!("compare_snapshots", [from_date, to_date], async {
+        let pool = db::pool(&app).await?;
+        compare(&pool, &from_date, &to_date).await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing::{self, closed, open};
+
+    fn holding(symbol: &str, quantity: i64, value: f64) -> Holding {
+        Holding {
+            symbol: symbol.into(),
+            quantity,
+            value,
+        }
+    }
+
+    #[test]
+    fn holdings_merge_lots_and_skip_closed() {
+        let trades = vec![
+            open(1, "AAPL", 10, "2024-01-02"),
+            open(2, "AAPL", 5, "2024-02-02"),
+            closed(3, "MSFT", 10, "2024-01-02", "2024-03-01", 50.0),
+        ];
+        let prices = HashMap::from([("AAPL".to_string(), 120.0)]);
+
+        let held = holdings(&trades, &prices);
+        assert_eq!(held.len(), 1);
+        assert_eq!((held[0].quantity, held[0].value), (15, 1_800.0));
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed() {
+        let from = [
+            holding("AAPL", 10, 1_500.0),
+            holding("MSFT", 5, 2_000.0),
+            holding("NVDA", 2, 1_000.0),
+        ];
+        let to = [
+            holding("AAPL", 10, 1_500.0),
+            holding("MSFT", 8, 3_300.0),
+            holding("TSLA", 4, 800.0),
+        ];
+
+        assert_eq!(
+            diff(&from, &to),
+            vec![
+                PositionChange {
+                    symbol: "MSFT".into(),
+                    change: "changed",
+                    quantity_delta: 3,
+                    value_delta: 1_300.0,
+                },
+                PositionChange {
+                    symbol: "NVDA".into(),
+                    change: "removed",
+                    quantity_delta: -2,
+                    value_delta: -1_000.0,
+                },
+                PositionChange {
+                    symbol: "TSLA".into(),
+                    change: "added",
+                    quantity_delta: 4,
+                    value_delta: 800.0,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn compare_uses_the_latest_snapshot_per_date() {
+        let pool = testing::pool().await;
+        save_snapshot(&pool, &[holding("AAPL", 10, 1_000.0)])
+            .await
+            .unwrap();
+        sqlx::query("UPDATE portfolio_snapshots SET taken_at = '2024-03-01 21:00:00'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        save_snapshot(
+            &pool,
+            &[holding("AAPL", 10, 1_000.0), holding("MSFT", 1, 400.0)],
+        )
+        .await
+        .unwrap();
+
+        let today = chrono::Utc::now().date_naive().to_string();
+        let comparison = compare(&pool, "2024-03-15", &today).await.unwrap();
+        assert_eq!(comparison.total_value_delta, 400.0);
+        assert_eq!(comparison.changes.len(), 1);
+        assert_eq!(comparison.changes[0].change, "added");
+        assert!(compare(&pool, "2024-02-01", &today).await.is_err());
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::db;
+use crate::snapshots::{self, Holding};
+
+/// A shock as fractional price moves. Sectors use GICS names; anything in
+/// another sector, or without one, moves by `market` scaled by its beta.
+struct Scenario {
+    name: &'static str,
+    market: f64,
+    sectors: &'static [(&'static str, f64)],
+}
+
+/// Rough peak-to-trough moves of the S&P 500 and its sectors.
+const SCENARIOS: &[Scenario] = &[
+    Scenario {
+        name: "2008",
+        market: -0.50,
+        sectors: &[
+            ("Financials", -0.75),
+            ("Real Estate", -0.65),
+            ("Consumer Discretionary", -0.55),
+            ("Industrials", -0.55),
+            ("Materials", -0.55),
+            ("Energy", -0.50),
+            ("Information Technology", -0.48),
+            ("Communication Services", -0.45),
+            ("Health Care", -0.35),
+            ("Utilities", -0.35),
+            ("Consumer Staples", -0.25),
+        ],
+    },
+    Scenario {
+        name: "covid_crash",
+        market: -0.34,
+        sectors: &[
+            ("Energy", -0.55),
+            ("Financials", -0.40),
+            ("Industrials", -0.40),
+            ("Real Estate", -0.40),
+            ("Utilities", -0.36),
+            ("Materials", -0.35),
+            ("Consumer Discretionary", -0.33),
+            ("Information Technology", -0.30),
+            ("Communication Services", -0.30),
+            ("Health Care", -0.28),
+            ("Consumer Staples", -0.24),
+        ],
+    },
+    Scenario {
+        name: "rate_spike",
+        market: -0.25,
+        sectors: &[
+            ("Communication Services", -0.40),
+            ("Consumer Discretionary", -0.37),
+            ("Information Technology", -0.35),
+            ("Real Estate", -0.30),
+            ("Financials", -0.15),
+            ("Materials", -0.15),
+            ("Industrials", -0.10),
+            ("Consumer Staples", -0.05),
+            ("Health Care", -0.05),
+            ("Utilities", -0.05),
+            ("Energy", 0.45),
+        ],
+    },
+];
+
+/// What the frontend knows about a held symbol.
+#[derive(Debug, Default, Deserialize)]
+pub struct PositionMeta {
+    price: Option<f64>,
+    sector: Option<String>,
+    beta: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionImpact {
+    symbol: String,
+    value: f64,
+    /// Price move applied, in percent.
+    shock_pct: f64,
+    /// Change in value; a short gains when its price falls.
+    impact: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StressResult {
+    scenario: String,
+    current_value: f64,
+    projected_value: f64,
+    change_pct: f64,
+    /// Worst first.
+    positions: Vec<PositionImpact>,
+    worst: Option<PositionImpact>,
+    best: Option<PositionImpact>,
+}
+
+fn scenario(name: &str) -> Result<&'static Scenario, String> {
+    SCENARIOS.iter().find(|s| s.name == name).ok_or_else(|| {
+        let names: Vec<&str> = SCENARIOS.iter().map(|s| s.name).collect();
+        format!(
+            "Unknown scenario '{}', expected one of: {}",
+            name,
+            names.join(", ")
+        )
+    })
+}
+
+fn shock_for(scenario: &Scenario, meta: Option<&PositionMeta>) -> f64 {
+    let sector = meta.and_then(|m| m.sector.as_deref()).and_then(|sector| {
+        scenario
+            .sectors
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(sector.trim()))
+    });
+    match sector {
+        Some((_, shock)) => *shock,
+        None => scenario.market * meta.and_then(|m| m.beta).unwrap_or(1.0),
+    }
+}
+
+/// Apply `scenario` to `holdings`, valued as they are now.
+fn apply(
+    scenario: &Scenario,
+    holdings: &[Holding],
+    metadata: &HashMap<String, PositionMeta>,
+) -> StressResult {
+    let mut positions: Vec<PositionImpact> = holdings
+        .iter()
+        .map(|h| {
+            // A full loss is the floor, whatever the beta
+            let shock = shock_for(scenario, metadata.get(&h.symbol)).max(-1.0);
+            PositionImpact {
+                symbol: h.symbol.clone(),
+                value: h.value,
+                shock_pct: shock * 100.0,
+                impact: h.value * shock,
+            }
+        })
+        .collect();
+    positions.sort_by(|a, b| a.impact.total_cmp(&b.impact));
+
+    let current_value: f64 = positions.iter().map(|p| p.value).sum();
+    let change: f64 = positions.iter().map(|p| p.impact).sum();
+    StressResult {
+        scenario: scenario.name.to_string(),
+        current_value,
+        projected_value: current_value + change,
+        change_pct: if current_value != 0.0 {
+            change / current_value.abs() * 100.0
+        } else {
+            0.0
+        },
+        worst: positions.first().cloned(),
+        best: positions.last().cloned(),
+        positions,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Project the open positions through a bundled shock: `2008`,
+/// `covid_crash` or `rate_spike`. `metadata` carries each symbol's latest
+/// price, GICS sector and beta from the frontend; without a price a position
+/// is valued at cost, and without a sector or beta it moves with the market.
+#[tauri::command]
+pub async fn stress_test(
+    app: AppHandle,
+    scenario: String,
+    metadata: Option<HashMap<String, PositionMeta>>,
+) -> Result<StressResult, String> {
+    logged!("stress_test", [scenario], async {
+        let picked = self::scenario(&scenario)?;
+        let metadata = metadata.unwrap_or_default();
+        let prices: HashMap<String, f64> = metadata
+            .iter()
+            .filter_map(|(symbol, meta)| Some((symbol.clone(), meta.price?)))
+            .collect();
+
+        let pool = db::pool(&app).await?;
+        let trades = db::load_trades(&pool).await?;
+        let holdings = snapshots::holdings(&trades, &prices);
+        Ok(apply(picked, &holdings, &metadata))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing::open;
+
+    fn meta(sector: Option<&str>, beta: Option<f64>) -> PositionMeta {
+        PositionMeta {
+            price: None,
+            sector: sector.map(String::from),
+            beta,
+        }
+    }
+
+    #[test]
+    fn scenario_applies_signed_sector_and_beta_shocks() {
+        let mut short = open(3, "MSFT", 10, "2024-01-02");
+        short.action = "SELL".into();
+        let trades = vec![
+            open(1, "JPM", 10, "2024-01-02"),
+            open(2, "XYZ", 10, "2024-01-02"),
+            short,
+        ];
+        let holdings = snapshots::holdings(&trades, &HashMap::new());
+        let metadata = HashMap::from([
+            ("JPM".to_string(), meta(Some("financials"), None)),
+            ("XYZ".to_string(), meta(None, Some(1.5))),
+            (
+                "MSFT".to_string(),
+                meta(Some("Information Technology"), None),
+            ),
+        ]);
+
+        let result = apply(scenario("2008").unwrap(), &holdings, &metadata);
+        let impacts: Vec<(&str, f64)> = result
+            .positions
+            .iter()
+            .map(|p| (p.symbol.as_str(), p.impact))
+            .collect();
+        // 1,000 long in financials, 1,000 long at beta 1.5, 1,000 short tech
+        assert_eq!(impacts, [("JPM", -750.0), ("XYZ", -750.0), ("MSFT", 480.0)]);
+        assert_eq!(result.current_value, 1_000.0);
+        assert_eq!(result.projected_value, -20.0);
+        assert_eq!(result.best.unwrap().symbol, "MSFT");
+    }
+
+    #[test]
+    fn unknown_scenarios_list_the_bundled_ones() {
+        let err = scenario("1929").err().unwrap();
+        assert!(err.contains("2008, covid_crash, rate_spike"));
+    }
+}
@@ -0,0 +1,317 @@
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+
+use crate::{db, format};
+
+/// Data providers whose symbol formats we know.
+pub(crate) const PROVIDERS: &[&str] = &["alphavantage", "yahoo", "polygon", "finnhub"];
+
+/// Non-US listings, keyed by the exchange code used in canonical symbols,
+/// with the suffix each provider expects. `None` means the provider doesn't
+/// cover that exchange.
+const EXCHANGES: &[Exchange] = &[
+    Exchange::new("LSE", Some(".LON"), Some(".L"), Some(".L")),
+    Exchange::new("TSX", Some(".TRT"), Some(".TO"), Some(".TO")),
+    Exchange::new("TSXV", Some(".TRV"), Some(".V"), Some(".V")),
+    Exchange::new("XETRA", Some(".DEX"), Some(".DE"), Some(".DE")),
+    Exchange::new("FRA", Some(".FRK"), Some(".F"), Some(".F")),
+    Exchange::new("EPA", Some(".PAR"), Some(".PA"), Some(".PA")),
+    Exchange::new("AMS", Some(".AMS"), Some(".AS"), Some(".AS")),
+    Exchange::new("BSE", Some(".BSE"), Some(".BO"), Some(".BO")),
+    Exchange::new("SSE", Some(".SHH"), Some(".SS"), Some(".SS")),
+    Exchange::new("SZSE", Some(".SHZ"), Some(".SZ"), Some(".SZ")),
+    Exchange::new("HKEX", None, Some(".HK"), Some(".HK")),
+    Exchange::new("ASX", None, Some(".AX"), Some(".AX")),
+];
+
+struct Exchange {
+    code: &'static str,
+    alphavantage: Option<&'static str>,
+    yahoo: Option<&'static str>,
+    finnhub: Option<&'static str>,
+}
+
+impl Exchange {
+    const fn new(
+        code: &'static str,
+        alphavantage: Option<&'static str>,
+        yahoo: Option<&'static str>,
+        finnhub: Option<&'static str>,
+    ) -> Self {
+        Self {
+            code,
+            alphavantage,
+            yahoo,
+            finnhub,
+        }
+    }
+
+    fn suffix(&self, provider: &str) -> Option<&'static str> {
+        match provider {
+            "alphavantage" => self.alphavantage,
+            "yahoo" => self.yahoo,
+            "finnhub" => self.finnhub,
+            // Polygon's stock endpoints are US-only
+            _ => None,
+        }
+    }
+}
+
+/// A symbol split into its parts. The canonical form is
+/// `BASE[.CLASS][:EXCHANGE]`, e.g. `BRK.B` or `TSCO:LSE`; no exchange
+/// means a US listing.
+struct Parts {
+    base: String,
+    class: Option<String>,
+    exchange: Option<&'static str>,
+}
+
+fn check_provider(provider: &str) -> Result<(), String> {
+    if PROVIDERS.contains(&provider) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unknown provider '{}', expected one of: {}",
+            provider,
+            PROVIDERS.join(", ")
+        ))
+    }
+}
+
+/// Split `ticker` on the last of `separators` when what follows looks like
+/// a share class (one or two letters).
+fn split_class(ticker: &str, separators: &[char]) -> Result<(String, Option<String>), String> {
+    let (base, class) = match ticker.rfind(separators) {
+        Some(i) if (1..=2).contains(&(ticker.len() - i - 1)) => {
+            (&ticker[..i], Some(&ticker[i + 1..]))
+        }
+        _ => (ticker, None),
+    };
+
+    let valid_base =
+        !base.is_empty() && base.chars().all(|c| c.is_ascii_alphanumeric() || c == '&');
+    if !valid_base || class.is_some_and(|c| !c.chars().all(|c| c.is_ascii_alphabetic())) {
+        return Err(format!("'{}' isn't a valid ticker", ticker));
+    }
+    Ok((base.to_string(), class.map(str::to_string)))
+}
+
+fn parse_canonical(symbol: &str) -> Result<Parts, String> {
+    let symbol = symbol.trim().to_uppercase();
+    let (ticker, exchange) = match symbol.split_once(':') {
+        Some((ticker, code)) => {
+            let exchange = EXCHANGES
+                .iter()
+                .find(|e| e.code == code)
+                .ok_or_else(|| format!("Unknown exchange '{}'", code))?;
+            (ticker, Some(exchange.code))
+        }
+        None => (symbol.as_str(), None),
+    };
+
+    // Accept the common provider separators too, so pasted symbols work
+    let (base, class) = split_class(ticker, &['.', '-', '/'])?;
+    Ok(Parts {
+        base,
+        class,
+        exchange,
+    })
+}
+
+fn class_separator(provider: &str) -> char {
+    match provider {
+        "alphavantage" | "yahoo" => '-',
+        _ => '.',
+    }
+}
+
+/// Map a canonical symbol to the format `provider` expects.
+pub(crate) fn normalize(symbol: &str, provider: &str) -> Result<String, String> {
+    check_provider(provider)?;
+    let parts = parse_canonical(symbol)?;
+
+    let mut out = parts.base;
+    if let Some(class) = parts.class {
+        out.push(class_separator(provider));
+        out.push_str(&class);
+    }
+    if let Some(code) = parts.exchange {
+        let exchange = EXCHANGES.iter().find(|e| e.code == code).expect("parsed");
+        let suffix = exchange
+            .suffix(provider)
+            .ok_or_else(|| format!("{} doesn't cover the {} exchange", provider, code))?;
+        out.push_str(suffix);
+    }
+    Ok(out)
+}
+
+/// Map a symbol in `provider`'s format back to the canonical form.
+pub(crate) fn canonicalize(symbol: &str, provider: &str) -> Result<String, String> {
+    check_provider(provider)?;
+    let symbol = symbol.trim().to_uppercase();
+
+    // Longest suffix first, so `.TO` isn't mistaken for a class share `T`
+    let mut suffixes: Vec<(&str, &str)> = EXCHANGES
+        .iter()
+        .filter_map(|e| Some((e.suffix(provider)?, e.code)))
+        .collect();
+    suffixes.sort_by_key(|(suffix, _)| std::cmp::Reverse(suffix.len()));
+    let (ticker, exchange) = suffixes
+        .iter()
+        .find_map(|(suffix, code)| Some((symbol.strip_suffix(suffix)?, Some(*code))))
+        .unwrap_or((symbol.as_str(), None));
+
+    let (base, class) = split_class(ticker, &[class_separator(provider), '/'])?;
+    let mut out = base;
+    if let Some(class) = class {
+        out.push('.');
+        out.push_str(&class);
+    }
+    if let Some(code) = exchange {
+        out.push(':');
+        out.push_str(code);
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Provider metadata
+// ---------------------------------------------------------------------------
+
+/// Store the price precision a provider reports for `symbol`, or forget it
+/// with `None`.
+pub(crate) async fn save_price_precision(
+    pool: &SqlitePool,
+    symbol: &str,
+    places: Option<u32>,
+) -> Result<(), String> {
+    let symbol = db::clean_symbol(symbol)?;
+    if places.is_some_and(|p| p > format::MAX_PRICE_DECIMALS) {
+        return Err(format!(
+            "Price precision must be at most {} places",
+            format::MAX_PRICE_DECIMALS
+        ));
+    }
+    sqlx::query(
+        "INSERT INTO symbol_meta (symbol, price_precision, updated_at) \
+         VALUES (?, ?, datetime('now')) \
+         ON CONFLICT(symbol) DO UPDATE SET price_precision = excluded.price_precision, \
+         updated_at = excluded.updated_at",
+    )
+    .bind(&symbol)
+    .bind(places)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub(crate) async fn price_precision(
+    pool: &SqlitePool,
+    symbol: &str,
+) -> Result<Option<u32>, String> {
+    let symbol = db::clean_symbol(symbol)?;
+    let places: Option<Option<u32>> =
+        sqlx::query_scalar("SELECT price_precision FROM symbol_meta WHERE symbol = ?")
+            .bind(&symbol)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    Ok(places.flatten())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Provider-specific form of a canonical symbol such as `BRK.B` or
+/// `TSCO:LSE`. Call before every provider request.
+#[tauri::command]
+pub fn normalize_symbol(symbol: String, provider: String) -> Result<String, String> {
+    logged!("normalize_symbol", [symbol, provider], {
+        normalize(&symbol, &provider)
+    })
+}
+
+#[tauri::command]
+pub fn canonicalize_symbol(symbol: String, provider: String) -> Result<String, String> {
+    logged!("canonicalize_symbol", [symbol, provider], {
+        canonicalize(&symbol, &provider)
+    })
+}
+
+/// Record the decimal places the provider quotes `symbol` in, from its tick
+/// size, so prices for it are formatted to match. `None` clears it.
+#[tauri::command]
+pub async fn set_symbol_price_precision(
+    app: AppHandle,
+    symbol: String,
+    places: Option<u32>,
+) -> Result<(), String> {
+    logged!("set_symbol_price_precision", [symbol, places], async {
+        let pool = db::pool(&app).await?;
+        save_price_precision(&pool, &symbol, places).await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing;
+
+    #[tokio::test]
+    async fn provider_precision_overrides_the_magnitude_rule() {
+        let pool = testing::pool().await;
+        assert_eq!(price_precision(&pool, "BTC-USD").await.unwrap(), None);
+        assert_eq!(format::price_places(64_250.5, None), 2);
+
+        save_price_precision(&pool, "btc-usd", Some(5))
+            .await
+            .unwrap();
+        let places = price_precision(&pool, "BTC-USD").await.unwrap();
+        assert_eq!(places, Some(5));
+        assert_eq!(
+            format::format(64_250.5, "price", "USD", "en-US", places).unwrap(),
+            "$64,250.50000"
+        );
+
+        save_price_precision(&pool, "BTC-USD", None).await.unwrap();
+        assert_eq!(price_precision(&pool, "BTC-USD").await.unwrap(), None);
+        assert!(save_price_precision(&pool, "BTC-USD", Some(12))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn class_shares_use_each_providers_separator() {
+        assert_eq!(normalize("BRK.B", "alphavantage").unwrap(), "BRK-B");
+        assert_eq!(normalize("brk.b", "yahoo").unwrap(), "BRK-B");
+        assert_eq!(normalize("BRK/B", "polygon").unwrap(), "BRK.B");
+        assert_eq!(canonicalize("BF-B", "yahoo").unwrap(), "BF.B");
+        assert_eq!(canonicalize("BRK/B", "finnhub").unwrap(), "BRK.B");
+        assert_eq!(normalize("AAPL", "yahoo").unwrap(), "AAPL");
+    }
+
+    #[test]
+    fn international_suffixes_round_trip() {
+        assert_eq!(normalize("TSCO:LSE", "yahoo").unwrap(), "TSCO.L");
+        assert_eq!(normalize("TSCO:LSE", "alphavantage").unwrap(), "TSCO.LON");
+        assert_eq!(normalize("RCI.B:TSX", "yahoo").unwrap(), "RCI-B.TO");
+
+        assert_eq!(canonicalize("TSCO.L", "yahoo").unwrap(), "TSCO:LSE");
+        assert_eq!(canonicalize("SHOP.TO", "finnhub").unwrap(), "SHOP:TSX");
+        assert_eq!(
+            canonicalize("RCI-B.TRT", "alphavantage").unwrap(),
+            "RCI.B:TSX"
+        );
+    }
+
+    #[test]
+    fn unsupported_combinations_are_errors() {
+        assert!(normalize("TSCO:LSE", "polygon").is_err());
+        assert!(normalize("0700:HKEX", "alphavantage").is_err());
+        assert!(normalize("TSCO:MOON", "yahoo").is_err());
+        assert!(normalize("AAPL", "bloomberg").is_err());
+        assert!(normalize("", "yahoo").is_err());
+    }
+}
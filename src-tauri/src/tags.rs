@@ -0,0 +1,215 @@
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+
+use crate::db;
+
+const MAX_TAG_CHARS: usize = 40;
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct TagCount {
+    name: String,
+    symbols: i64,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct TaggedPosition {
+    symbol: String,
+    quantity: i64,
+    cost_basis: f64,
+}
+
+/// Tags are case-insensitive labels: "AI " and "ai" are the same tag.
+fn normalize_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err("Tag name is required".into());
+    }
+    if tag.chars().count() > MAX_TAG_CHARS {
+        return Err(format!(
+            "Tag names are limited to {} characters",
+            MAX_TAG_CHARS
+        ));
+    }
+    Ok(tag)
+}
+
+// ---------------------------------------------------------------------------
+// Queries
+// ---------------------------------------------------------------------------
+
+pub(crate) async fn tag_symbol(pool: &SqlitePool, symbol: &str, tag: &str) -> Result<(), String> {
+    let symbol = db::clean_symbol(symbol)?;
+    let tag = normalize_tag(tag)?;
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query("INSERT OR IGNORE INTO tags (name) VALUES (?)")
+        .bind(&tag)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query(
+        "INSERT OR IGNORE INTO position_tags (tag_id, symbol) \
+         SELECT id, ? FROM tags WHERE name = ?",
+    )
+    .bind(&symbol)
+    .bind(&tag)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+/// Untag `symbol`, dropping the tag entirely once nothing uses it.
+pub(crate) async fn untag_symbol(pool: &SqlitePool, symbol: &str, tag: &str) -> Result<(), String> {
+    let symbol = db::clean_symbol(symbol)?;
+    let tag = normalize_tag(tag)?;
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query(
+        "DELETE FROM position_tags \
+         WHERE symbol = ? AND tag_id = (SELECT id FROM tags WHERE name = ?)",
+    )
+    .bind(&symbol)
+    .bind(&tag)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    sqlx::query(
+        "DELETE FROM tags WHERE name = ? \
+         AND NOT EXISTS (SELECT 1 FROM position_tags WHERE tag_id = tags.id)",
+    )
+    .bind(&tag)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+pub(crate) async fn tag_counts(pool: &SqlitePool) -> Result<Vec<TagCount>, String> {
+    sqlx::query_as(
+        "SELECT t.name AS name, COUNT(pt.symbol) AS symbols \
+         FROM tags t LEFT JOIN position_tags pt ON pt.tag_id = t.id \
+         GROUP BY t.id ORDER BY t.name",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Open positions carrying `tag`, aggregated per symbol.
+pub(crate) async fn tagged_positions(
+    pool: &SqlitePool,
+    tag: &str,
+) -> Result<Vec<TaggedPosition>, String> {
+    let tag = normalize_tag(tag)?;
+
+    sqlx::query_as(
+        "SELECT tr.symbol AS symbol, \
+                SUM(tr.quantity) AS quantity, \
+                SUM(tr.quantity * tr.entry_price) AS cost_basis \
+         FROM tags t \
+         JOIN position_tags pt ON pt.tag_id = t.id \
+         JOIN trades tr ON tr.symbol = pt.symbol AND tr.status = 'open' \
+         WHERE t.name = ? \
+         GROUP BY tr.symbol ORDER BY tr.symbol",
+    )
+    .bind(&tag)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn add_tag(app: AppHandle, symbol: String, tag: String) -> Result<(), String> {
+    logged!("add_tag", [symbol, tag], async {
+        let pool = db::pool(&app).await?;
+        tag_symbol(&pool, &symbol, &tag).await
+    })
+}
+
+#[tauri::command]
+pub async fn remove_tag(app: AppHandle, symbol: String, tag: String) -> Result<(), String> {
+    logged!("remove_tag", [symbol, tag], async {
+        let pool = db::pool(&app).await?;
+        untag_symbol(&pool, &symbol, &tag).await
+    })
+}
+
+#[tauri::command]
+pub async fn list_tags(app: AppHandle) -> Result<Vec<TagCount>, String> {
+    logged!("list_tags", [], async {
+        let pool = db::pool(&app).await?;
+        tag_counts(&pool).await
+    })
+}
+
+#[tauri::command]
+pub async fn positions_by_tag(app: AppHandle, tag: String) -> Result<Vec<TaggedPosition>, String> {
+    logged!("positions_by_tag", [tag], async {
+        let pool = db::pool(&app).await?;
+        tagged_positions(&pool, &tag).await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing;
+
+    #[test]
+    fn normalize_tag_is_case_insensitive() {
+        assert_eq!(normalize_tag("  AI ").unwrap(), "ai");
+        assert!(normalize_tag("   ").is_err());
+        assert!(normalize_tag(&"x".repeat(MAX_TAG_CHARS + 1)).is_err());
+    }
+
+    #[tokio::test]
+    async fn tagging_twice_is_a_no_op() {
+        let pool = testing::pool().await;
+        tag_symbol(&pool, "aapl", "Tech").await.unwrap();
+        tag_symbol(&pool, "AAPL", "tech").await.unwrap();
+
+        let counts = tag_counts(&pool).await.unwrap();
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[0].name, "tech");
+        assert_eq!(counts[0].symbols, 1);
+    }
+
+    #[tokio::test]
+    async fn untagging_last_symbol_drops_tag() {
+        let pool = testing::pool().await;
+        tag_symbol(&pool, "AAPL", "tech").await.unwrap();
+        tag_symbol(&pool, "MSFT", "tech").await.unwrap();
+
+        untag_symbol(&pool, "AAPL", "tech").await.unwrap();
+        assert_eq!(tag_counts(&pool).await.unwrap()[0].symbols, 1);
+
+        untag_symbol(&pool, "MSFT", "tech").await.unwrap();
+        assert!(tag_counts(&pool).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn tagged_positions_sum_open_lots() {
+        let pool = testing::pool().await;
+        testing::insert_open(&pool, "AAPL", 10, 150.0).await;
+        testing::insert_open(&pool, "AAPL", 5, 160.0).await;
+        testing::insert_open(&pool, "MSFT", 3, 400.0).await;
+        sqlx::query("UPDATE trades SET status = 'closed' WHERE symbol = 'MSFT'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        tag_symbol(&pool, "AAPL", "core").await.unwrap();
+        tag_symbol(&pool, "MSFT", "core").await.unwrap();
+
+        let positions = tagged_positions(&pool, "core").await.unwrap();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].symbol, "AAPL");
+        assert_eq!(positions[0].quantity, 15);
+        assert_eq!(positions[0].cost_basis, 2300.0);
+    }
+}
@@ -0,0 +1,204 @@
+use serde::Serialize;
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::AppHandle;
+
+use crate::db;
+
+const MAX_RATIONALE_CHARS: usize = 2_000;
+
+#[derive(sqlx::FromRow)]
+struct TargetRow {
+    symbol: String,
+    target: f64,
+    rationale: String,
+    set_at: String,
+}
+
+#[derive(Serialize)]
+pub struct PriceTarget {
+    symbol: String,
+    pub(crate) target: f64,
+    rationale: String,
+    set_at: String,
+    /// Average entry price of the open long position, if there is one.
+    cost_basis: Option<f64>,
+    /// How far the price has moved from cost towards the target, where 1.0
+    /// means the target has been reached.
+    progress: Option<f64>,
+}
+
+/// `(current - cost) / (target - cost)`, or `None` when the target sits at
+/// cost and progress is meaningless.
+pub(crate) fn target_progress(current: f64, cost: f64, target: f64) -> Option<f64> {
+    let span = target - cost;
+    (span.abs() > f64::EPSILON).then(|| (current - cost) / span)
+}
+
+// ---------------------------------------------------------------------------
+// Queries
+// ---------------------------------------------------------------------------
+
+/// Upsert the price target for `symbol`. Returns the normalized symbol.
+pub(crate) async fn write_target(
+    pool: &SqlitePool,
+    symbol: &str,
+    target: f64,
+    rationale: &str,
+) -> Result<String, String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    write_target_on(&mut conn, symbol, target, rationale).await
+}
+
+/// [`write_target`] on a connection the caller holds, so it can share a
+/// transaction.
+pub(crate) async fn write_target_on(
+    conn: &mut SqliteConnection,
+    symbol: &str,
+    target: f64,
+    rationale: &str,
+) -> Result<String, String> {
+    let symbol = db::clean_symbol(symbol)?;
+    if !target.is_finite() || target <= 0.0 {
+        return Err(format!("Invalid price target {}", target));
+    }
+    let rationale = rationale.trim();
+    if rationale.chars().count() > MAX_RATIONALE_CHARS {
+        return Err(format!(
+            "Rationale exceeds {} characters",
+            MAX_RATIONALE_CHARS
+        ));
+    }
+
+    sqlx::query(
+        "INSERT INTO price_targets (symbol, target, rationale, set_at) \
+         VALUES (?, ?, ?, datetime('now')) \
+         ON CONFLICT(symbol) DO UPDATE SET target = excluded.target, \
+         rationale = excluded.rationale, set_at = excluded.set_at",
+    )
+    .bind(&symbol)
+    .bind(target)
+    .bind(rationale)
+    .execute(conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(symbol)
+}
+
+/// Average entry price of the open long position in `symbol`, which must
+/// already be normalized.
+pub(crate) async fn cost_basis(pool: &SqlitePool, symbol: &str) -> Result<Option<f64>, String> {
+    sqlx::query_scalar(
+        "SELECT SUM(quantity * entry_price) / SUM(quantity) FROM trades \
+         WHERE symbol = ? AND status = 'open' AND action IN ('BUY', 'STRONG_BUY')",
+    )
+    .bind(symbol)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+pub(crate) async fn read_target(
+    pool: &SqlitePool,
+    symbol: &str,
+    current_price: Option<f64>,
+) -> Result<Option<PriceTarget>, String> {
+    let symbol = db::clean_symbol(symbol)?;
+
+    let row: Option<TargetRow> = sqlx::query_as(
+        "SELECT symbol, target, rationale, set_at FROM price_targets WHERE symbol = ?",
+    )
+    .bind(&symbol)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let cost_basis = cost_basis(pool, &symbol).await?;
+
+    let progress = match (current_price, cost_basis) {
+        (Some(current), Some(cost)) => target_progress(current, cost, row.target),
+        _ => None,
+    };
+
+    Ok(Some(PriceTarget {
+        symbol: row.symbol,
+        target: row.target,
+        rationale: row.rationale,
+        set_at: row.set_at,
+        cost_basis,
+        progress,
+    }))
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn set_price_target(
+    app: AppHandle,
+    symbol: String,
+    target: f64,
+    rationale: String,
+) -> Result<(), String> {
+    logged!("set_price_target", [symbol, target], async {
+        let pool = db::pool(&app).await?;
+        write_target(&pool, &symbol, target, &rationale).await?;
+        Ok(())
+    })
+}
+
+/// The target for `symbol`. Pass the latest quote as `current_price` to get
+/// progress towards it.
+#[tauri::command]
+pub async fn get_price_target(
+    app: AppHandle,
+    symbol: String,
+    current_price: Option<f64>,
+) -> Result<Option<PriceTarget>, String> {
+    logged!("get_price_target", [symbol, current_price], async {
+        let pool = db::pool(&app).await?;
+        read_target(&pool, &symbol, current_price).await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing::{self, insert_open};
+
+    #[test]
+    fn progress_is_fraction_of_move_to_target() {
+        assert_eq!(target_progress(150.0, 100.0, 200.0), Some(0.5));
+        assert_eq!(target_progress(90.0, 100.0, 200.0), Some(-0.1));
+        assert_eq!(target_progress(100.0, 100.0, 100.0), None);
+    }
+
+    #[tokio::test]
+    async fn read_reports_progress_against_open_cost() {
+        let pool = testing::pool().await;
+        insert_open(&pool, "AAPL", 10, 100.0).await;
+        insert_open(&pool, "AAPL", 10, 120.0).await;
+        write_target(&pool, "aapl", 210.0, "services margin")
+            .await
+            .unwrap();
+
+        let target = read_target(&pool, "AAPL", Some(160.0))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(target.cost_basis, Some(110.0));
+        assert_eq!(target.progress, Some(0.5));
+    }
+
+    #[tokio::test]
+    async fn rejects_non_positive_target() {
+        let pool = testing::pool().await;
+
+        assert!(write_target(&pool, "AAPL", 0.0, "").await.is_err());
+        assert!(write_target(&pool, "AAPL", f64::NAN, "").await.is_err());
+        assert!(read_target(&pool, "AAPL", None).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,577 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Local, Months, NaiveDate};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::db::{self, TradeRow};
+use crate::export;
+
+/// Replacement purchases within this many days before or after a loss sale
+/// trigger the wash-sale rule.
+const WASH_SALE_WINDOW_DAYS: i64 = 30;
+
+#[derive(Serialize)]
+pub struct WashSaleWarning {
+    symbol: String,
+    sale_trade_id: i64,
+    sale_date: String,
+    sale_quantity: i64,
+    realized_loss: f64,
+    replacement_trade_id: i64,
+    replacement_date: String,
+    replacement_quantity: i64,
+    disallowed_loss: f64,
+}
+
+/// Calendar date of an ISO-8601 timestamp as stored in the trades table.
+pub(crate) fn trade_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// Held for more than a year, so a sale is long-term.
+fn is_long_term(acquired: NaiveDate, sold: NaiveDate) -> bool {
+    acquired
+        .checked_add_months(Months::new(12))
+        .is_some_and(|year_on| sold > year_on)
+}
+
+/// Pair each losing long sale with replacement buys of the same symbol.
+///
+/// Each replacement share can only wash one sold share, so buys are consumed
+/// in date order across sales. A partial repurchase disallows the loss in
+/// proportion to the shares it replaces.
+pub(crate) fn find_wash_sales(trades: &[TradeRow]) -> Vec<WashSaleWarning> {
+    let mut losses: Vec<(&TradeRow, NaiveDate)> = trades
+        .iter()
+        .filter(|t| t.is_long() && !t.is_open() && t.quantity > 0)
+        .filter(|t| t.pnl_dollars.is_some_and(|pnl| pnl < 0.0))
+        .filter_map(|t| Some((t, trade_date(t.exit_date.as_deref()?)?)))
+        .collect();
+    losses.sort_by_key(|(_, sold)| *sold);
+
+    let mut buys: Vec<(&TradeRow, NaiveDate)> = trades
+        .iter()
+        .filter(|t| t.is_long() && t.quantity > 0)
+        .filter_map(|t| Some((t, trade_date(&t.entry_date)?)))
+        .collect();
+    buys.sort_by_key(|(_, bought)| *bought);
+
+    let mut unused: HashMap<i64, i64> = buys.iter().map(|(t, _)| (t.id, t.quantity)).collect();
+    let mut warnings = Vec::new();
+
+    for (sale, sold) in losses {
+        let loss = -sale.pnl_dollars.unwrap_or(0.0);
+        let mut unmatched = sale.quantity;
+
+        for (buy, bought) in &buys {
+            if unmatched == 0 {
+                break;
+            }
+            if buy.id == sale.id || buy.symbol != sale.symbol {
+                continue;
+            }
+            if (*bought - sold).num_days().abs() > WASH_SALE_WINDOW_DAYS {
+                continue;
+            }
+
+            let available = unused.get_mut(&buy.id).expect("every buy is tracked");
+            let replaced = unmatched.min(*available);
+            if replaced == 0 {
+                continue;
+            }
+            *available -= replaced;
+            unmatched -= replaced;
+
+            warnings.push(WashSaleWarning {
+                symbol: sale.symbol.clone(),
+                sale_trade_id: sale.id,
+                sale_date: sold.to_string(),
+                sale_quantity: sale.quantity,
+                realized_loss: loss,
+                replacement_trade_id: buy.id,
+                replacement_date: bought.to_string(),
+                replacement_quantity: replaced,
+                disallowed_loss: loss * replaced as f64 / sale.quantity as f64,
+            });
+        }
+    }
+
+    warnings
+}
+
+// ---------------------------------------------------------------------------
+// Harvest candidates
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Serialize)]
+pub struct HarvestCandidate {
+    trade_id: i64,
+    symbol: String,
+    quantity: i64,
+    cost_basis: f64,
+    market_value: f64,
+    /// Loss realized by selling today, as a positive amount.
+    loss: f64,
+    /// `short` or `long`, as the sale would be reported.
+    holding_period: &'static str,
+    days_held: i64,
+    /// Another lot of the symbol was bought within the wash-sale window,
+    /// so selling now would have the loss disallowed.
+    wash_sale_risk: bool,
+}
+
+/// Open long lots that would realize a loss if sold at `prices` on `today`,
+/// largest loss first. Lots without a price are skipped.
+pub(crate) fn harvest_candidates(
+    trades: &[TradeRow],
+    prices: &HashMap<String, f64>,
+    today: NaiveDate,
+) -> Vec<HarvestCandidate> {
+    let buys: Vec<(&TradeRow, NaiveDate)> = trades
+        .iter()
+        .filter(|t| t.is_long() && t.quantity > 0)
+        .filter_map(|t| Some((t, trade_date(&t.entry_date)?)))
+        .collect();
+
+    let mut candidates: Vec<HarvestCandidate> = buys
+        .iter()
+        .filter(|(t, _)| t.is_open())
+        .filter_map(|&(lot, bought)| {
+            let price = *prices.get(&lot.symbol)?;
+            let cost_basis = lot.entry_price * lot.quantity as f64;
+            let market_value = price * lot.quantity as f64;
+            if market_value >= cost_basis {
+                return None;
+            }
+            let wash_sale_risk = buys.iter().any(|(other, other_bought)| {
+                other.id != lot.id
+                    && other.symbol == lot.symbol
+                    && *other_bought <= today
+                    && (today - *other_bought).num_days() <= WASH_SALE_WINDOW_DAYS
+            });
+            Some(HarvestCandidate {
+                trade_id: lot.id,
+                symbol: lot.symbol.clone(),
+                quantity: lot.quantity,
+                cost_basis,
+                market_value,
+                loss: cost_basis - market_value,
+                holding_period: if is_long_term(bought, today) {
+                    "long"
+                } else {
+                    "short"
+                },
+                days_held: (today - bought).num_days(),
+                wash_sale_risk,
+            })
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.loss.total_cmp(&a.loss));
+    candidates
+}
+
+// ---------------------------------------------------------------------------
+// Form 8949 export
+// ---------------------------------------------------------------------------
+
+/// First tax year the exporter accepts; anything earlier is a typo.
+const MIN_TAX_YEAR: i32 = 2000;
+
+/// TXF reference numbers for short- and long-term security sales.
+const TXF_SHORT_TERM: u32 = 321;
+const TXF_LONG_TERM: u32 = 323;
+
+/// One disposal, as a row of Form 8949.
+pub(crate) struct Disposal {
+    description: String,
+    acquired: NaiveDate,
+    sold: NaiveDate,
+    proceeds: f64,
+    cost: f64,
+    /// Loss disallowed by the wash-sale rule, reported with code W.
+    wash_sale: f64,
+    long_term: bool,
+}
+
+impl Disposal {
+    fn gain(&self) -> f64 {
+        self.proceeds - self.cost + self.wash_sale
+    }
+}
+
+/// Closed trades in `year` as Form 8949 rows. Covering a short counts as
+/// both acquiring and selling on the exit date, which is always short-term.
+pub(crate) fn disposals(trades: &[TradeRow], year: i32) -> Vec<Disposal> {
+    let mut washed: HashMap<i64, f64> = HashMap::new();
+    for warning in find_wash_sales(trades) {
+        *washed.entry(warning.sale_trade_id).or_default() += warning.disallowed_loss;
+    }
+
+    let mut rows: Vec<Disposal> = trades
+        .iter()
+        .filter(|t| !t.is_open())
+        .filter_map(|t| {
+            let sold = trade_date(t.exit_date.as_deref()?)?;
+            let exit_price = t.exit_price?;
+            if sold.year() != year {
+                return None;
+            }
+            let entered = trade_date(&t.entry_date)?;
+            let (entry_value, exit_value) = (
+                t.entry_price * t.quantity as f64,
+                exit_price * t.quantity as f64,
+            );
+
+            Some(if t.is_long() {
+                Disposal {
+                    description: format!("{} sh {}", t.quantity, t.symbol),
+                    acquired: entered,
+                    sold,
+                    proceeds: exit_value,
+                    cost: entry_value,
+                    wash_sale: washed.get(&t.id).copied().unwrap_or(0.0),
+                    long_term: is_long_term(entered, sold),
+                }
+            } else {
+                Disposal {
+                    description: format!("{} sh {} (short)", t.quantity, t.symbol),
+                    acquired: sold,
+                    sold,
+                    proceeds: entry_value,
+                    cost: exit_value,
+                    wash_sale: 0.0,
+                    long_term: false,
+                }
+            })
+        })
+        .collect();
+    rows.sort_by_key(|d| (d.long_term, d.sold));
+    rows
+}
+
+fn us_date(date: NaiveDate) -> String {
+    date.format("%m/%d/%Y").to_string()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Form 8949 layout: Part I (short-term) then Part II (long-term), with
+/// columns (a) through (h).
+pub(crate) fn to_8949_csv(rows: &[Disposal]) -> String {
+    let mut out = String::new();
+    for (long_term, title) in [
+        (false, "Part I - Short-Term"),
+        (true, "Part II - Long-Term"),
+    ] {
+        out.push_str(title);
+        out.push('\n');
+        out.push_str(
+            "Description,Date acquired,Date sold,Proceeds,Cost basis,Code,Adjustment,Gain or loss\n",
+        );
+        for row in rows.iter().filter(|r| r.long_term == long_term) {
+            let (code, adjustment) = if row.wash_sale > 0.0 {
+                ("W", format!("{:.2}", row.wash_sale))
+            } else {
+                ("", String::new())
+            };
+            out.push_str(&format!(
+                "{},{},{},{:.2},{:.2},{},{},{:.2}\n",
+                csv_field(&row.description),
+                us_date(row.acquired),
+                us_date(row.sold),
+                row.proceeds,
+                row.cost,
+                code,
+                adjustment,
+                row.gain()
+            ));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// TXF v042, the import format most US tax software reads.
+pub(crate) fn to_txf(rows: &[Disposal], today: NaiveDate) -> String {
+    let mut out = format!("V042\nAStock Advisors\nD{}\n^\n", us_date(today));
+    for row in rows {
+        let reference = if row.long_term {
+            TXF_LONG_TERM
+        } else {
+            TXF_SHORT_TERM
+        };
+        out.push_str(&format!(
+            "TD\nN{}\nC1\nL1\nP{}\nD{}\nD{}\n${:.2}\n${:.2}\n^\n",
+            reference,
+            row.description,
+            us_date(row.acquired),
+            us_date(row.sold),
+            row.cost,
+            row.proceeds
+        ));
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn wash_sale_check(app: AppHandle) -> Result<Vec<WashSaleWarning>, String> {
+    logged!("wash_sale_check", [], async {
+        let pool = db::pool(&app).await?;
+        let trades = db::load_trades(&pool).await?;
+        Ok(find_wash_sales(&trades))
+    })
+}
+
+/// Open lots worth selling for a tax loss. `prices` maps symbols to their
+/// latest price from the frontend; lots without one are left out.
+#[tauri::command]
+pub async fn tax_harvest_candidates(
+    app: AppHandle,
+    prices: HashMap<String, f64>,
+) -> Result<Vec<HarvestCandidate>, String> {
+    logged!("tax_harvest_candidates", [], async {
+        let pool = db::pool(&app).await?;
+        let trades = db::load_trades(&pool).await?;
+        Ok(harvest_candidates(
+            &trades,
+            &prices,
+            Local::now().date_naive(),
+        ))
+    })
+}
+
+/// Write the year's disposals as a Form 8949 CSV (`8949_csv`) or a TXF file
+/// (`txf`) for tax software.
+#[tauri::command]
+pub async fn export_tax_form(
+    app: AppHandle,
+    year: i32,
+    format: String,
+    dest: String,
+) -> Result<(), String> {
+    logged!("export_tax_form", [year, format, dest], async {
+        let today = Local::now().date_naive();
+        if !(MIN_TAX_YEAR..=today.year()).contains(&year) {
+            return Err(format!(
+                "Tax year must be between {} and {}",
+                MIN_TAX_YEAR,
+                today.year()
+            ));
+        }
+        let extension = match format.as_str() {
+            "8949_csv" => "csv",
+            "txf" => "txf",
+            other => {
+                return Err(format!(
+                    "Unknown format '{}', expected 8949_csv or txf",
+                    other
+                ))
+            }
+        };
+        let path = export::validate_destination(&dest, extension)?;
+
+        let pool = db::pool(&app).await?;
+        let trades = db::load_trades(&pool).await?;
+        let rows = disposals(&trades, year);
+        let contents = if extension == "csv" {
+            to_8949_csv(&rows)
+        } else {
+            to_txf(&rows, today)
+        };
+        export::write_file(&app, path, contents.as_bytes())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing::{closed, open};
+
+    #[test]
+    fn rebuy_within_window_washes_whole_loss() {
+        let trades = vec![
+            closed(1, "AAPL", 10, "2024-01-02", "2024-03-01", -500.0),
+            open(2, "AAPL", 10, "2024-03-15"),
+        ];
+
+        let warnings = find_wash_sales(&trades);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].replacement_trade_id, 2);
+        assert_eq!(warnings[0].disallowed_loss, 500.0);
+    }
+
+    #[test]
+    fn rebuy_outside_window_is_fine() {
+        let trades = vec![
+            closed(1, "AAPL", 10, "2024-01-02", "2024-03-01", -500.0),
+            open(2, "AAPL", 10, "2024-04-05"),
+        ];
+
+        assert!(find_wash_sales(&trades).is_empty());
+    }
+
+    #[test]
+    fn partial_rebuy_disallows_proportionally() {
+        let trades = vec![
+            closed(1, "AAPL", 10, "2024-01-02", "2024-03-01", -500.0),
+            open(2, "AAPL", 4, "2024-02-20"),
+        ];
+
+        let warnings = find_wash_sales(&trades);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].replacement_quantity, 4);
+        assert_eq!(warnings[0].disallowed_loss, 200.0);
+    }
+
+    #[test]
+    fn replacement_shares_wash_only_once() {
+        let trades = vec![
+            closed(1, "AAPL", 10, "2024-01-02", "2024-03-01", -500.0),
+            closed(2, "AAPL", 10, "2024-01-02", "2024-03-05", -300.0),
+            open(3, "AAPL", 10, "2024-03-10"),
+        ];
+
+        let warnings = find_wash_sales(&trades);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].sale_trade_id, 1);
+    }
+
+    #[test]
+    fn gains_and_other_symbols_are_ignored() {
+        let trades = vec![
+            closed(1, "AAPL", 10, "2024-01-02", "2024-03-01", 250.0),
+            closed(2, "MSFT", 10, "2024-01-02", "2024-03-01", -250.0),
+            open(3, "AAPL", 10, "2024-03-10"),
+        ];
+
+        assert!(find_wash_sales(&trades).is_empty());
+    }
+
+    #[test]
+    fn rebuy_on_day_30_is_washed_and_day_31_is_not() {
+        // sold 2024-03-01
+        for (rebuy, washed) in [("2024-03-31", true), ("2024-04-01", false)] {
+            let trades = vec![
+                closed(1, "AAPL", 10, "2024-01-02", "2024-03-01", -500.0),
+                open(2, "AAPL", 10, rebuy),
+            ];
+            assert_eq!(!find_wash_sales(&trades).is_empty(), washed, "{}", rebuy);
+        }
+    }
+
+    #[test]
+    fn buy_30_days_before_the_sale_is_washed_and_31_is_not() {
+        for (bought, washed) in [("2024-01-31", true), ("2024-01-30", false)] {
+            let trades = vec![
+                closed(1, "AAPL", 10, "2023-06-01", "2024-03-01", -500.0),
+                closed(2, "AAPL", 10, bought, "2024-06-03", 100.0),
+            ];
+            assert_eq!(!find_wash_sales(&trades).is_empty(), washed, "{}", bought);
+
+            // the same boundary when the sale would be today
+            let trades = vec![
+                open(1, "AAPL", 10, "2023-06-01"),
+                open(2, "AAPL", 10, bought),
+            ];
+            let prices = HashMap::from([("AAPL".to_string(), 80.0)]);
+            let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+            let old_lot = harvest_candidates(&trades, &prices, today)
+                .into_iter()
+                .find(|c| c.trade_id == 1)
+                .unwrap();
+            assert_eq!(old_lot.wash_sale_risk, washed, "{}", bought);
+        }
+    }
+
+    #[test]
+    fn harvest_flags_lots_with_a_recent_rebuy() {
+        let mut winner = open(4, "MSFT", 10, "2024-01-02");
+        winner.entry_price = 50.0;
+        let trades = vec![
+            open(1, "AAPL", 10, "2023-01-03"),
+            open(2, "NVDA", 20, "2024-03-01"),
+            open(3, "NVDA", 5, "2024-05-20"),
+            winner,
+        ];
+        let prices = HashMap::from([
+            ("AAPL".to_string(), 80.0),
+            ("NVDA".to_string(), 90.0),
+            ("MSFT".to_string(), 60.0),
+        ]);
+        let today = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+
+        let candidates = harvest_candidates(&trades, &prices, today);
+        let found: Vec<(i64, f64, &str, bool)> = candidates
+            .iter()
+            .map(|c| (c.trade_id, c.loss, c.holding_period, c.wash_sale_risk))
+            .collect();
+        // AAPL is clean, but the older NVDA lot would be washed by the one
+        // bought 14 days ago
+        assert_eq!(
+            found,
+            [
+                (1, 200.0, "long", false),
+                (2, 200.0, "short", true),
+                (3, 50.0, "short", false)
+            ]
+        );
+    }
+
+    #[test]
+    fn disposals_split_by_holding_period_and_carry_wash_sales() {
+        let trades = vec![
+            closed(1, "AAPL", 10, "2024-01-02", "2024-03-01", -500.0),
+            open(2, "AAPL", 10, "2024-03-15"),
+            closed(3, "MSFT", 5, "2022-06-01", "2024-02-01", 250.0),
+            closed(4, "NVDA", 5, "2023-01-02", "2023-12-01", 100.0),
+        ];
+
+        let rows = disposals(&trades, 2024);
+        assert_eq!(rows.len(), 2);
+        assert!(!rows[0].long_term);
+        assert_eq!(rows[0].wash_sale, 500.0);
+        assert_eq!(rows[0].gain(), 0.0);
+        assert!(rows[1].long_term);
+        assert_eq!((rows[1].proceeds, rows[1].cost), (750.0, 500.0));
+    }
+
+    #[test]
+    fn csv_has_both_parts_with_code_w() {
+        let trades = vec![
+            closed(1, "AAPL", 10, "2024-01-02", "2024-03-01", -500.0),
+            open(2, "AAPL", 10, "2024-03-15"),
+        ];
+        let csv = to_8949_csv(&disposals(&trades, 2024));
+
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "Part I - Short-Term");
+        assert_eq!(
+            lines[2],
+            "10 sh AAPL,01/02/2024,03/01/2024,500.00,1000.00,W,500.00,0.00"
+        );
+        assert_eq!(lines[4], "Part II - Long-Term");
+    }
+
+    #[test]
+    fn txf_records_use_term_reference_numbers() {
+        let trades = vec![closed(3, "MSFT", 5, "2022-06-01", "2024-02-01", 250.0)];
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let txf = to_txf(&disposals(&trades, 2024), today);
+
+        assert!(txf.starts_with("V042\nAStock Advisors\nD03/01/2025\n^\n"));
+        assert!(txf.contains(
+            "TD\nN323\nC1\nL1\nP5 sh MSFT\nD06/01/2022\nD02/01/2024\n$500.00\n$750.00\n^\n"
+        ));
+    }
+}
@@ -0,0 +1,45 @@
+use chrono_tz::Tz;
+use tauri::{AppHandle, Runtime};
+
+use crate::settings::{self, SettingsPatch};
+
+/// The OS timezone, or UTC when it can't be determined or isn't in the tz
+/// database (some minimal Linux images report nothing useful).
+fn system_timezone() -> Tz {
+    iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|name| name.parse().ok())
+        .unwrap_or(Tz::UTC)
+}
+
+/// The timezone all time-of-day logic should use: the user's override if
+/// one is set, otherwise the OS timezone.
+pub(crate) fn user_timezone<R: Runtime>(app: &AppHandle<R>) -> Tz {
+    settings::load_settings(app)
+        .timezone_override
+        .and_then(|name| name.parse().ok())
+        .unwrap_or_else(system_timezone)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// IANA name of the effective timezone, e.g. `America/New_York`.
+#[tauri::command]
+pub fn get_timezone(app: AppHandle) -> String {
+    user_timezone(&app).name().to_string()
+}
+
+/// Force a timezone instead of the OS one. `None` (or an empty string)
+/// goes back to following the OS.
+#[tauri::command]
+pub fn set_timezone_override(app: AppHandle, tz: Option<String>) -> Result<(), String> {
+    logged!("set_timezone_override", [tz], {
+        let patch = SettingsPatch {
+            timezone_override: Some(tz),
+            ..Default::default()
+        };
+        settings::update_settings(&app, patch).map(|_| ())
+    })
+}
@@ -0,0 +1,159 @@
+use sqlx::SqlitePool;
+use tauri::menu::MenuItem;
+use tauri::{AppHandle, Manager, State, Wry};
+
+use crate::db;
+use crate::popover;
+use crate::settings::{self, Settings};
+use crate::TRAY_TOOLTIP;
+
+pub(crate) const TRAY_ID: &str = "main";
+
+pub(crate) fn tooltip(paused: bool) -> String {
+    if paused {
+        format!("{} (monitors paused)", TRAY_TOOLTIP)
+    } else {
+        TRAY_TOOLTIP.to_string()
+    }
+}
+
+pub(crate) fn sync_tooltip(app: &AppHandle, paused: bool) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(tooltip(paused)));
+    }
+}
+
+/// Whether the OS should open the tray menu on left click, per the
+/// `tray_left_click` setting. Ignored on Linux, where the menu always
+/// opens on click.
+pub(crate) fn menu_on_left_click(settings: &Settings) -> bool {
+    settings.tray_left_click == "menu"
+}
+
+/// Bring the main window forward, or hide it if it's already showing.
+fn toggle_main_window(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    let showing = window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false);
+    if showing {
+        let _ = window.hide();
+    } else {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Handle a left click according to the user's `tray_left_click` setting.
+/// The `menu` action is handled by the OS.
+pub fn on_left_click(app: &AppHandle) {
+    match settings::load_settings(app).tray_left_click.as_str() {
+        "window" => toggle_main_window(app),
+        "popover" => popover::show_popover(app),
+        _ => {}
+    }
+}
+
+/// Apply a changed `tray_left_click` to the live tray icon.
+pub(crate) fn sync_left_click(app: &AppHandle, settings: &Settings) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_show_menu_on_left_click(menu_on_left_click(settings));
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Menu state
+// ---------------------------------------------------------------------------
+
+/// Handles to the tray menu items whose enabled state or label depends on
+/// app data.
+pub struct TrayMenu {
+    pub run_analysis: MenuItem<Wry>,
+    pub alerts_muted: MenuItem<Wry>,
+}
+
+pub(crate) fn alerts_muted_label(muted: bool) -> &'static str {
+    if muted {
+        "Alerts: Muted"
+    } else {
+        "Alerts: ON"
+    }
+}
+
+pub(crate) fn sync_alerts_muted(app: &AppHandle, muted: bool) {
+    if let Some(menu) = app.try_state::<TrayMenu>() {
+        let _ = menu.alerts_muted.set_text(alerts_muted_label(muted));
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct MenuEnabled {
+    pub run_analysis: bool,
+}
+
+/// Which tray items make sense to offer given the current app state.
+pub(crate) fn menu_enabled(open_positions: i64) -> MenuEnabled {
+    MenuEnabled {
+        run_analysis: open_positions > 0,
+    }
+}
+
+async fn count_open_positions(pool: &SqlitePool) -> Result<i64, String> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM trades WHERE status = 'open'")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Recompute which tray items are enabled. The frontend calls this after
+/// anything that changes the open positions.
+#[tauri::command]
+pub async fn refresh_tray_menu(app: AppHandle, menu: State<'_, TrayMenu>) -> Result<(), String> {
+    logged!("refresh_tray_menu", [], async {
+        let pool = db::pool(&app).await?;
+        let enabled = menu_enabled(count_open_positions(&pool).await?);
+
+        menu.run_analysis
+            .set_enabled(enabled.run_analysis)
+            .map_err(|e| e.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing::{self, insert_open};
+
+    #[test]
+    fn tooltip_flags_paused_monitors() {
+        assert_eq!(tooltip(false), "Stock Advisors");
+        assert_eq!(tooltip(true), "Stock Advisors (monitors paused)");
+    }
+
+    #[test]
+    fn run_analysis_needs_a_position() {
+        assert!(!menu_enabled(0).run_analysis);
+        assert!(menu_enabled(1).run_analysis);
+    }
+
+    #[tokio::test]
+    async fn counts_only_open_positions() {
+        let pool = testing::pool().await;
+        assert_eq!(count_open_positions(&pool).await.unwrap(), 0);
+
+        insert_open(&pool, "AAPL", 10, 150.0).await;
+        insert_open(&pool, "MSFT", 5, 400.0).await;
+        sqlx::query("UPDATE trades SET status = 'closed' WHERE symbol = 'MSFT'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(count_open_positions(&pool).await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::America::New_York;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::monitors::MonitorState;
+use crate::settings::{self, SettingsPatch};
+use crate::{db, i18n, market, notifications, session};
+
+/// Share of a regular session's volume traded by the end of each half hour
+/// from the open. Volume clusters at the open and close, so a flat pace
+/// would flag every morning as a spike.
+const CUMULATIVE_SHARE: [f64; 14] = [
+    0.0, 0.12, 0.20, 0.27, 0.33, 0.38, 0.43, 0.48, 0.53, 0.58, 0.64, 0.71, 0.81, 1.0,
+];
+const SESSION_MINUTES: f64 = 390.0;
+
+/// The first few minutes are too noisy to project from.
+const MIN_ELAPSED_MINUTES: f64 = 15.0;
+
+/// Daily volumes used to average, most recent last. About a month.
+const MAX_AVERAGE_DAYS: usize = 30;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VolumeSpike {
+    symbol: String,
+    volume: f64,
+    projected: f64,
+    average: f64,
+    /// Projected volume as a multiple of the average.
+    multiple: f64,
+}
+
+struct CachedAverage {
+    day: NaiveDate,
+    average: f64,
+    /// Already notified this session.
+    alerted: bool,
+}
+
+/// Average daily volume per symbol, refreshed once per session.
+#[derive(Default)]
+pub struct VolumeCache(Mutex<HashMap<String, CachedAverage>>);
+
+/// Expected share of the session's volume traded `minutes` after the open.
+pub(crate) fn session_share(minutes: f64) -> f64 {
+    let minutes = minutes.clamp(0.0, SESSION_MINUTES);
+    let slot = ((minutes / 30.0) as usize).min(CUMULATIVE_SHARE.len() - 2);
+    let within = (minutes - slot as f64 * 30.0) / 30.0;
+    CUMULATIVE_SHARE[slot] + (CUMULATIVE_SHARE[slot + 1] - CUMULATIVE_SHARE[slot]) * within
+}
+
+/// Full-session volume implied by `volume` traded `minutes` after the open,
+/// or `None` while it's too early to tell.
+pub(crate) fn project(volume: f64, minutes: f64) -> Option<f64> {
+    (minutes >= MIN_ELAPSED_MINUTES).then(|| volume / session_share(minutes))
+}
+
+pub(crate) fn average(daily_volumes: &[f64]) -> Result<f64, String> {
+    let recent = &daily_volumes[daily_volumes.len().saturating_sub(MAX_AVERAGE_DAYS)..];
+    if recent.is_empty() || recent.iter().any(|v| !v.is_finite() || *v < 0.0) {
+        return Err("Daily volumes must be non-negative numbers".into());
+    }
+    let average = recent.iter().sum::<f64>() / recent.len() as f64;
+    if average <= 0.0 {
+        return Err("Average volume is zero".into());
+    }
+    Ok(average)
+}
+
+/// Minutes since today's open, or `None` outside the regular session.
+fn minutes_into_session(now: DateTime<Utc>) -> Option<f64> {
+    if !market::market_is_open(now) {
+        return None;
+    }
+    let local = now.with_timezone(&New_York).time();
+    Some((local - market::OPEN).num_seconds() as f64 / 60.0)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Check `symbol`'s intraday `volume` against its average daily volume,
+/// projected over the rest of the session. Pass `daily_volumes` (oldest
+/// first) on the first check of each session; later checks reuse the
+/// cached average. Emits `volume-spike` and notifies once per session when
+/// the projection reaches the configured multiple.
+#[tauri::command]
+pub fn check_volume(
+    app: AppHandle,
+    monitors: State<'_, MonitorState>,
+    cache: State<'_, VolumeCache>,
+    symbol: String,
+    volume: f64,
+    daily_volumes: Option<Vec<f64>>,
+) -> Result<Option<VolumeSpike>, String> {
+    logged!("check_volume", [symbol, volume], {
+        let symbol = db::clean_symbol(&symbol)?;
+        if !volume.is_finite() || volume < 0.0 {
+            return Err("Volume must be a non-negative number".into());
+        }
+        let now = Utc::now();
+        let today = now.with_timezone(&New_York).date_naive();
+
+        let mut cache = cache.0.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(daily) = daily_volumes {
+            let average = average(&daily)?;
+            let alerted = cache
+                .get(&symbol)
+                .is_some_and(|c| c.day == today && c.alerted);
+            cache.insert(
+                symbol.clone(),
+                CachedAverage {
+                    day: today,
+                    average,
+                    alerted,
+                },
+            );
+        }
+        let cached = cache
+            .get_mut(&symbol)
+            .filter(|c| c.day == today)
+            .ok_or_else(|| format!("No average volume for {} today; pass daily_volumes", symbol))?;
+
+        if monitors.paused() || cached.alerted {
+            return Ok(None);
+        }
+        let Some(projected) = minutes_into_session(now).and_then(|m| project(volume, m)) else {
+            return Ok(None);
+        };
+        let multiplier = settings::load_settings(&app).volume_spike_multiplier;
+        let (average, multiple) = (cached.average, projected / cached.average);
+        if multiple < multiplier {
+            return Ok(None);
+        }
+        cached.alerted = true;
+        drop(cache);
+
+        let spike = VolumeSpike {
+            symbol,
+            volume,
+            projected,
+            average,
+            multiple,
+        };
+        session::alerts_fired(1);
+        let _ = app.emit("volume-spike", &spike);
+        let title = i18n::user_text(&app, "notify.volume_spike", &[("symbol", &spike.symbol)]);
+        let body = i18n::user_text(
+            &app,
+            "notify.volume_spike_pace",
+            &[("multiple", &format!("{:.1}", multiple))],
+        );
+        notifications::show_limited(&app, &title, &body)?;
+        Ok(Some(spike))
+    })
+}
+
+/// How many times its average volume a symbol has to be on pace for before
+/// `check_volume` flags it.
+#[tauri::command]
+pub fn set_volume_spike_multiplier(app: AppHandle, multiplier: f64) -> Result<(), String> {
+    logged!("set_volume_spike_multiplier", [multiplier], {
+        let patch = SettingsPatch {
+            volume_spike_multiplier: Some(multiplier),
+            ..Default::default()
+        };
+        settings::update_settings(&app, patch).map(|_| ())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn share_follows_the_intraday_curve() {
+        assert_eq!(session_share(0.0), 0.0);
+        assert_eq!(session_share(30.0), 0.12);
+        assert!((session_share(45.0) - 0.16).abs() < 1e-9);
+        assert_eq!(session_share(SESSION_MINUTES), 1.0);
+        assert_eq!(session_share(500.0), 1.0);
+        assert!(CUMULATIVE_SHARE.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn projection_scales_by_time_of_day() {
+        // 1.2M in the first half hour is an ordinary 10M day
+        assert_eq!(project(1_200_000.0, 30.0), Some(10_000_000.0));
+        // four hours in, just over half the day's volume has traded
+        let midday = project(5_300_000.0, 240.0).unwrap();
+        assert!((midday - 10_000_000.0).abs() < 1e-3);
+        assert_eq!(project(3_000_000.0, 390.0), Some(3_000_000.0));
+        assert_eq!(project(500_000.0, 5.0), None);
+    }
+
+    #[test]
+    fn average_uses_the_most_recent_month() {
+        let mut daily = vec![100.0; 10];
+        daily.extend(vec![1_000.0; MAX_AVERAGE_DAYS]);
+        assert_eq!(average(&daily).unwrap(), 1_000.0);
+        assert!(average(&[]).is_err());
+        assert!(average(&[0.0, 0.0]).is_err());
+        assert!(average(&[100.0, f64::NAN]).is_err());
+    }
+}
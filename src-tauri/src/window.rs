@@ -0,0 +1,92 @@
+use tauri::{AppHandle, LogicalSize, Manager};
+
+use crate::settings::{self, SettingsPatch, MIN_WINDOW_HEIGHT, MIN_WINDOW_WIDTH};
+
+/// Pull a requested minimum into the supported range. Below it the layout
+/// breaks; above it the window couldn't fit on a laptop screen.
+pub(crate) fn clamp_min_size(width: f64, height: f64) -> (f64, f64) {
+    let clamp = |value: f64, range: &std::ops::RangeInclusive<f64>| {
+        if value.is_nan() {
+            *range.start()
+        } else {
+            value.clamp(*range.start(), *range.end())
+        }
+    };
+    (
+        clamp(width, &MIN_WINDOW_WIDTH),
+        clamp(height, &MIN_WINDOW_HEIGHT),
+    )
+}
+
+/// The window's size grown, where needed, to at least the minimum.
+pub(crate) fn enlarged(current: (f64, f64), min: (f64, f64)) -> Option<(f64, f64)> {
+    let size = (current.0.max(min.0), current.1.max(min.1));
+    (size != current).then_some(size)
+}
+
+/// Apply the configured minimum to the main window, growing it first if
+/// it opened smaller, so no saved or OS-chosen size can break the layout.
+pub(crate) fn apply_min_size(app: &AppHandle) -> Result<(), String> {
+    let Some(window) = app.get_webview_window("main") else {
+        return Ok(());
+    };
+    let settings = settings::load_settings(app);
+    let min = (settings.min_window_width, settings.min_window_height);
+
+    let scale = window.scale_factor().map_err(|e| e.to_string())?;
+    let current = window
+        .inner_size()
+        .map_err(|e| e.to_string())?
+        .to_logical::<f64>(scale);
+    window
+        .set_min_size(Some(LogicalSize::new(min.0, min.1)))
+        .map_err(|e| e.to_string())?;
+    if let Some((width, height)) = enlarged((current.width, current.height), min) {
+        window
+            .set_size(LogicalSize::new(width, height))
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Override the main window's minimum size, in logical pixels. Values are
+/// clamped to the supported range; returns the size applied.
+#[tauri::command]
+pub fn set_min_window_size(app: AppHandle, width: f64, height: f64) -> Result<(f64, f64), String> {
+    logged!("set_min_window_size", [width, height], {
+        let (width, height) = clamp_min_size(width, height);
+        let patch = SettingsPatch {
+            min_window_width: Some(width),
+            min_window_height: Some(height),
+            ..Default::default()
+        };
+        settings::update_settings(&app, patch)?;
+        apply_min_size(&app)?;
+        Ok((width, height))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requested_minimums_are_clamped() {
+        assert_eq!(clamp_min_size(800.0, 600.0), (800.0, 600.0));
+        assert_eq!(clamp_min_size(100.0, 10_000.0), (640.0, 1_440.0));
+        assert_eq!(clamp_min_size(f64::NAN, 600.0), (640.0, 600.0));
+    }
+
+    #[test]
+    fn undersized_windows_grow_to_the_minimum() {
+        assert_eq!(
+            enlarged((900.0, 800.0), (1_024.0, 700.0)),
+            Some((1_024.0, 800.0))
+        );
+        assert_eq!(enlarged((1_440.0, 900.0), (1_024.0, 700.0)), None);
+    }
+}
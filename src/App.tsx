@@ -19,12 +19,18 @@ import WatchlistPage from './pages/WatchlistPage';
 import HistoryPage from './pages/HistoryPage';
 import PerformancePage from './pages/PerformancePage';
 import PaperTradingPage from './pages/PaperTradingPage';
+import TrayPopoverPage from './pages/TrayPopoverPage';
 
 // -----------------------------------------------------------------------------
 // App
 // -----------------------------------------------------------------------------
 
 export default function App() {
+  // The tray popover is its own window with no app chrome
+  if (window.location.pathname === '/tray-popover') {
+    return <TrayPopoverPage />;
+  }
+
   return (
     <BrowserRouter>
       <AppShell>
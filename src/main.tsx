@@ -1,8 +1,11 @@
 import React from 'react';
 import ReactDOM from 'react-dom/client';
 import App from './App';
+import { clearTradesOnDataWipe } from './services/database/TradeMemory';
 import './styles/globals.css';
 
+clearTradesOnDataWipe();
+
 ReactDOM.createRoot(document.getElementById('root')!).render(
   <React.StrictMode>
     <App />
@@ -0,0 +1,111 @@
+// =============================================================================
+// Stock Advisors - Tray Popover
+// =============================================================================
+// Compact portfolio summary shown in the small window anchored to the tray
+// icon. Rendered outside AppShell since the popover has no chrome; reads
+// open positions straight from TradeMemory each time the window is shown.
+// =============================================================================
+
+import { useState, useEffect, useCallback } from 'react';
+import { Briefcase } from 'lucide-react';
+import { initializeDatabase } from '../services/database/Database';
+import { getOpenTrades } from '../services/database/TradeMemory';
+import type { TradeRecord } from '../services/database/TradeMemory';
+import { formatCurrency } from '../lib/formatters';
+
+/** Largest positions listed in the popover */
+const TOP_POSITIONS = 5;
+
+// -----------------------------------------------------------------------------
+// Summary
+// -----------------------------------------------------------------------------
+
+interface PositionSummary {
+  symbol: string;
+  quantity: number;
+  costBasis: number;
+}
+
+function summarize(trades: TradeRecord[]): PositionSummary[] {
+  const bySymbol = new Map<string, PositionSummary>();
+  for (const trade of trades) {
+    const entry = bySymbol.get(trade.symbol) ?? {
+      symbol: trade.symbol,
+      quantity: 0,
+      costBasis: 0,
+    };
+    entry.quantity += trade.quantity;
+    entry.costBasis += trade.quantity * trade.entry_price;
+    bySymbol.set(trade.symbol, entry);
+  }
+  return [...bySymbol.values()].sort((a, b) => b.costBasis - a.costBasis);
+}
+
+// -----------------------------------------------------------------------------
+// Page
+// -----------------------------------------------------------------------------
+
+export default function TrayPopoverPage() {
+  const [positions, setPositions] = useState<PositionSummary[]>([]);
+  const [error, setError] = useState<string | null>(null);
+
+  const load = useCallback(async () => {
+    try {
+      await initializeDatabase();
+      setPositions(summarize(await getOpenTrades()));
+      setError(null);
+    } catch (err) {
+      setError(err instanceof Error ? err.message : 'Failed to load positions');
+    }
+  }, []);
+
+  // The window is hidden rather than closed on blur, so refresh on every show
+  useEffect(() => {
+    load();
+    window.addEventListener('focus', load);
+    return () => window.removeEventListener('focus', load);
+  }, [load]);
+
+  const totalCost = positions.reduce((sum, p) => sum + p.costBasis, 0);
+
+  return (
+    <div className="flex h-screen flex-col gap-3 bg-[var(--color-sa-bg-secondary)] p-4">
+      <div className="flex items-center gap-2">
+        <Briefcase size={16} className="text-[var(--color-sa-accent)]" />
+        <h1 className="text-[13px] font-semibold text-[var(--color-sa-text-primary)]">
+          Portfolio
+        </h1>
+      </div>
+
+      <div className="rounded-lg border border-[var(--color-sa-border)] px-3 py-2.5">
+        <p className="text-[11px] font-semibold uppercase tracking-wider text-[var(--color-sa-text-dim)]">
+          {positions.length} open {positions.length === 1 ? 'position' : 'positions'}
+        </p>
+        <p className="text-[16px] font-bold text-[var(--color-sa-text-primary)] tabular-nums">
+          {formatCurrency(totalCost)}
+        </p>
+        <p className="text-[11px] text-[var(--color-sa-text-muted)]">Cost basis</p>
+      </div>
+
+      {error ? (
+        <p className="text-[12px] text-[var(--color-sa-red)]">{error}</p>
+      ) : (
+        <ul className="flex flex-col gap-1.5">
+          {positions.slice(0, TOP_POSITIONS).map((p) => (
+            <li
+              key={p.symbol}
+              className="flex items-center justify-between text-[12px] tabular-nums"
+            >
+              <span className="font-semibold text-[var(--color-sa-text-primary)]">
+                {p.symbol}
+              </span>
+              <span className="text-[var(--color-sa-text-muted)]">
+                {p.quantity} · {formatCurrency(p.costBasis)}
+              </span>
+            </li>
+          ))}
+        </ul>
+      )}
+    </div>
+  );
+}
@@ -253,6 +253,31 @@ export async function updateTradeStatus(
   }
 }
 
+/**
+ * Delete every trade.
+ */
+export async function deleteAllTrades(): Promise<void> {
+  await execute('DELETE FROM trades');
+}
+
+/**
+ * Clear trades when the backend emits `data-wiped`. The backend empties its
+ * own tables but leaves this one to us. Does nothing outside Tauri.
+ */
+export function clearTradesOnDataWipe(): void {
+  import('@tauri-apps/api/event')
+    .then(({ listen }) =>
+      listen('data-wiped', () => {
+        deleteAllTrades().catch((err) => {
+          console.error('[TradeMemory] Failed to clear trades after data wipe:', err);
+        });
+      })
+    )
+    .catch(() => {
+      // Not running in Tauri - there is no backend to wipe anything
+    });
+}
+
 // -----------------------------------------------------------------------------
 // Portfolio Aggregations
 // -----------------------------------------------------------------------------